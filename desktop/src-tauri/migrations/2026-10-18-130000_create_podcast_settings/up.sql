@@ -0,0 +1,10 @@
+CREATE TABLE podcast_settings (
+    id INTEGER PRIMARY KEY NOT NULL,
+    podcast_id INTEGER NOT NULL UNIQUE REFERENCES podcasts(id),
+    playback_speed REAL,
+    skip_intro_seconds INTEGER NOT NULL DEFAULT 0,
+    auto_download TEXT NOT NULL DEFAULT 'off',
+    sort_order TEXT NOT NULL DEFAULT 'newest_first',
+    notifications BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMP NOT NULL
+);
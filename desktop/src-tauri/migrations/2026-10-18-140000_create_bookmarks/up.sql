@@ -0,0 +1,11 @@
+CREATE TABLE bookmarks (
+    id INTEGER PRIMARY KEY NOT NULL,
+    guid TEXT NOT NULL UNIQUE,
+    episode_id INTEGER NOT NULL REFERENCES episodes(id),
+    start_seconds INTEGER NOT NULL,
+    end_seconds INTEGER,
+    note TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    deleted_at TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL
+);
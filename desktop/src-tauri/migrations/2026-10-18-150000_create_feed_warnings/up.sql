@@ -0,0 +1,10 @@
+CREATE TABLE feed_warnings (
+    id INTEGER PRIMARY KEY NOT NULL,
+    podcast_id INTEGER NOT NULL REFERENCES podcasts(id),
+    item_index INTEGER NOT NULL,
+    guid TEXT,
+    title TEXT,
+    message TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
+CREATE INDEX feed_warnings_podcast_id ON feed_warnings(podcast_id);
@@ -0,0 +1,3 @@
+ALTER TABLE podcasts DROP COLUMN content_hash;
+ALTER TABLE podcasts DROP COLUMN http_last_modified;
+ALTER TABLE podcasts DROP COLUMN http_etag;
@@ -0,0 +1,3 @@
+ALTER TABLE podcasts ADD COLUMN http_etag TEXT;
+ALTER TABLE podcasts ADD COLUMN http_last_modified TEXT;
+ALTER TABLE podcasts ADD COLUMN content_hash TEXT;
@@ -0,0 +1,2 @@
+DROP TABLE chapters;
+ALTER TABLE episodes DROP COLUMN chapters_url;
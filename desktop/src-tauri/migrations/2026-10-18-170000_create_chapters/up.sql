@@ -0,0 +1,14 @@
+ALTER TABLE episodes ADD COLUMN chapters_url TEXT NOT NULL DEFAULT '';
+CREATE TABLE chapters (
+    id INTEGER PRIMARY KEY NOT NULL,
+    episode_id INTEGER NOT NULL REFERENCES episodes(id),
+    start_seconds DOUBLE NOT NULL,
+    end_seconds DOUBLE,
+    title TEXT NOT NULL,
+    image_url TEXT NOT NULL,
+    url TEXT NOT NULL
+);
+CREATE INDEX chapters_episode_id ON chapters(episode_id);
+-- feeds are only parsed again when they change, so forget their validators to fetch every feed once
+-- more and give existing episodes their chapters URL
+UPDATE podcasts SET http_etag = NULL, http_last_modified = NULL, content_hash = NULL;
@@ -0,0 +1,3 @@
+DROP TABLE transcript_segments;
+ALTER TABLE episodes DROP COLUMN transcript_type;
+ALTER TABLE episodes DROP COLUMN transcript_url;
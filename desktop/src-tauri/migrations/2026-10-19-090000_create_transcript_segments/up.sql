@@ -0,0 +1,13 @@
+ALTER TABLE episodes ADD COLUMN transcript_url TEXT NOT NULL DEFAULT '';
+ALTER TABLE episodes ADD COLUMN transcript_type TEXT NOT NULL DEFAULT '';
+CREATE TABLE transcript_segments (
+    id INTEGER PRIMARY KEY NOT NULL,
+    episode_id INTEGER NOT NULL REFERENCES episodes(id),
+    start_seconds DOUBLE NOT NULL,
+    end_seconds DOUBLE,
+    speaker TEXT NOT NULL,
+    body TEXT NOT NULL
+);
+CREATE INDEX transcript_segments_episode_id ON transcript_segments(episode_id, start_seconds);
+-- fetch every feed once more, as for chapters, so existing episodes get their transcript URL
+UPDATE podcasts SET http_etag = NULL, http_last_modified = NULL, content_hash = NULL;
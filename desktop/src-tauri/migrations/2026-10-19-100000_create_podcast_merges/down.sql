@@ -0,0 +1 @@
+DROP TABLE podcast_merges;
@@ -0,0 +1,8 @@
+CREATE TABLE podcast_merges (
+    id INTEGER PRIMARY KEY NOT NULL,
+    old_guid TEXT NOT NULL UNIQUE,
+    new_guid TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
+-- fetch every feed again once so existing podcasts move to their stable guid
+UPDATE podcasts SET http_etag = NULL, http_last_modified = NULL, content_hash = NULL;
@@ -0,0 +1,9 @@
+CREATE TABLE feed_moves (
+    id INTEGER PRIMARY KEY NOT NULL,
+    podcast_id INTEGER NOT NULL REFERENCES podcasts(id),
+    old_url TEXT NOT NULL,
+    new_url TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
+CREATE INDEX feed_moves_podcast_id ON feed_moves(podcast_id);
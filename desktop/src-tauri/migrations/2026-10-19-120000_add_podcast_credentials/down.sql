@@ -0,0 +1,2 @@
+ALTER TABLE podcasts DROP COLUMN auth_password;
+ALTER TABLE podcasts DROP COLUMN auth_username;
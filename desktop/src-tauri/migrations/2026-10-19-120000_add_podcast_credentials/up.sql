@@ -0,0 +1,2 @@
+ALTER TABLE podcasts ADD COLUMN auth_username TEXT;
+ALTER TABLE podcasts ADD COLUMN auth_password TEXT;
@@ -0,0 +1,6 @@
+DROP INDEX episodes_podcast_id_season;
+ALTER TABLE podcasts DROP COLUMN podcast_type;
+ALTER TABLE episodes DROP COLUMN explicit;
+ALTER TABLE episodes DROP COLUMN episode_type;
+ALTER TABLE episodes DROP COLUMN episode_number;
+ALTER TABLE episodes DROP COLUMN season;
@@ -0,0 +1,8 @@
+ALTER TABLE episodes ADD COLUMN season INTEGER;
+ALTER TABLE episodes ADD COLUMN episode_number INTEGER;
+ALTER TABLE episodes ADD COLUMN episode_type TEXT NOT NULL DEFAULT 'full';
+ALTER TABLE episodes ADD COLUMN explicit BOOLEAN;
+ALTER TABLE podcasts ADD COLUMN podcast_type TEXT NOT NULL DEFAULT 'episodic';
+CREATE INDEX episodes_podcast_id_season ON episodes(podcast_id, season);
+-- fetch every feed again once so existing episodes get their seasons and numbers
+UPDATE podcasts SET http_etag = NULL, http_last_modified = NULL, content_hash = NULL;
@@ -0,0 +1,11 @@
+CREATE TABLE feed_health (
+    id INTEGER PRIMARY KEY NOT NULL,
+    podcast_id INTEGER NOT NULL UNIQUE REFERENCES podcasts(id),
+    last_attempt_at TIMESTAMP NOT NULL,
+    last_success_at TIMESTAMP,
+    last_error TEXT,
+    last_error_at TIMESTAMP,
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    http_status INTEGER,
+    next_attempt_at TIMESTAMP
+);
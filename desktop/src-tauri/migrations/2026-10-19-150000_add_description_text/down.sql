@@ -0,0 +1,2 @@
+ALTER TABLE episodes DROP COLUMN description_text;
+ALTER TABLE podcasts DROP COLUMN description_text;
@@ -0,0 +1,4 @@
+ALTER TABLE podcasts ADD COLUMN description_text TEXT NOT NULL DEFAULT '';
+ALTER TABLE episodes ADD COLUMN description_text TEXT NOT NULL DEFAULT '';
+-- fetch every feed once more, as for chapters, so stored descriptions get sanitized
+UPDATE podcasts SET http_etag = NULL, http_last_modified = NULL, content_hash = NULL;
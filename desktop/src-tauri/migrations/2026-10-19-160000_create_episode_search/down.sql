@@ -0,0 +1,5 @@
+DROP TRIGGER episode_search_podcast_rename;
+DROP TRIGGER episode_search_delete;
+DROP TRIGGER episode_search_update;
+DROP TRIGGER episode_search_insert;
+DROP TABLE episode_search;
@@ -0,0 +1,28 @@
+-- rowid is the episode id
+CREATE VIRTUAL TABLE episode_search USING fts5(
+    title,
+    description,
+    podcast_name,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+INSERT INTO episode_search(rowid, title, description, podcast_name)
+SELECT episodes.id, episodes.title, episodes.description_text, podcasts.name
+FROM episodes
+INNER JOIN podcasts ON podcasts.id = episodes.podcast_id;
+
+CREATE TRIGGER episode_search_insert AFTER INSERT ON episodes BEGIN
+    INSERT INTO episode_search(rowid, title, description, podcast_name)
+    SELECT new.id, new.title, new.description_text, podcasts.name FROM podcasts WHERE podcasts.id = new.podcast_id;
+END;
+CREATE TRIGGER episode_search_update AFTER UPDATE OF title, description_text, podcast_id ON episodes BEGIN
+    DELETE FROM episode_search WHERE rowid = old.id;
+    INSERT INTO episode_search(rowid, title, description, podcast_name)
+    SELECT new.id, new.title, new.description_text, podcasts.name FROM podcasts WHERE podcasts.id = new.podcast_id;
+END;
+CREATE TRIGGER episode_search_delete AFTER DELETE ON episodes BEGIN
+    DELETE FROM episode_search WHERE rowid = old.id;
+END;
+CREATE TRIGGER episode_search_podcast_rename AFTER UPDATE OF name ON podcasts BEGIN
+    UPDATE episode_search SET podcast_name = new.name
+    WHERE rowid IN (SELECT id FROM episodes WHERE podcast_id = new.id);
+END;
@@ -0,0 +1,5 @@
+-- episodes whose transcript was tried, so the background fetch doesn't keep retrying broken ones
+CREATE TABLE transcript_fetches (
+    episode_id INTEGER PRIMARY KEY NOT NULL REFERENCES episodes(id),
+    fetched_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,349 @@
+use std::collections::HashSet;
+use std::fs;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::anyhow;
+use diesel::prelude::*;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::imageops::FilterType;
+use image::DynamicImage;
+use sha2::{Digest, Sha256};
+use tauri::http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use tauri::http::{Response, Uri};
+use uuid::Uuid;
+
+use crate::database::db_connect;
+use crate::directories::images_dir;
+use crate::errors::{AppError, AppResult};
+use crate::models::feed_auth::FeedAuth;
+use crate::models::{episode, podcast};
+
+/// Sizes, in pixels along the longest side, artwork is stored at. Smaller images are stored as they
+/// are, never scaled up.
+const SIZES: &[u32] = &[64, 128, 256, 512];
+const JPEG_QUALITY: u8 = 85;
+/// Files this new may belong to artwork whose download hasn't been saved to its podcast or episode
+/// yet, so they're never taken for orphans.
+const ORPHAN_MIN_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Downloads artwork and stores it at every size in `SIZES`. Returns the SHA-256 of the original
+/// file, which is what podcasts and episodes keep as their local image; artwork shared by several of
+/// them, or downloaded again, is only decoded and stored once.
+pub async fn download(image_url: &str, auth: &FeedAuth) -> AppResult<String> {
+    let response = auth
+        .apply(reqwest::Client::new().get(image_url), image_url)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("Failed to download image: {}", response.status()).into());
+    }
+    let bytes = response.bytes().await?;
+    tokio::task::spawn_blocking(move || store(&images_dir(), &bytes)).await?
+}
+
+fn store(dir: &Path, bytes: &[u8]) -> AppResult<String> {
+    let hash = format!("{:x}", Sha256::digest(bytes));
+    if SIZES.iter().all(|size| thumbnail_path(dir, &hash, *size).is_some()) {
+        return Ok(hash);
+    }
+    let image = image::load_from_memory(bytes)?;
+    let extension = if image.color().has_alpha() { "png" } else { "jpg" };
+    for size in SIZES {
+        let thumbnail = if image.width().max(image.height()) > *size {
+            image.resize(*size, *size, FilterType::Lanczos3)
+        } else {
+            image.clone()
+        };
+        let path = dir.join(format!("{hash}-{size}.{extension}"));
+        // unique, as the same artwork may be stored by several feeds at once
+        let partial_path = path.with_extension(format!("{}.part", Uuid::new_v4()));
+        let mut file = BufWriter::new(File::create(&partial_path)?);
+        if extension == "png" {
+            thumbnail.write_with_encoder(PngEncoder::new(&mut file))?;
+        } else {
+            DynamicImage::from(thumbnail.to_rgb8())
+                .write_with_encoder(JpegEncoder::new_with_quality(&mut file, JPEG_QUALITY))?;
+        }
+        file.flush()?;
+        drop(file);
+        fs::rename(partial_path, path)?;
+    }
+    tracing::debug!("saved artwork {hash}");
+    Ok(hash)
+}
+
+fn thumbnail_path(dir: &Path, hash: &str, size: u32) -> Option<PathBuf> {
+    ["jpg", "png"]
+        .iter()
+        .map(|extension| dir.join(format!("{hash}-{size}.{extension}")))
+        .find(|path| path.exists())
+}
+
+/// Whether a local image is a hash from `download` rather than the path of a file saved whole by
+/// versions before artwork was resized.
+fn is_hash(local_image: &str) -> bool {
+    local_image.len() == 64 && local_image.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// Answers `localimages://podcast/{id}` and `localimages://episode/{id}`. A `size` parameter picks the
+/// smallest stored size at least that large; without it the largest is served.
+pub fn serve(uri: &Uri) -> Response<Vec<u8>> {
+    let Some(path) = find(&images_dir(), uri, &mut db_connect()) else {
+        return Response::builder().status(404).body(Vec::new()).unwrap();
+    };
+    let Ok(content) = fs::read(&path) else {
+        return Response::builder().status(404).body(Vec::new()).unwrap();
+    };
+    let content_type = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("png") => "image/png",
+        _ => "image/jpeg",
+    };
+    // the frontend puts the local image in the URL, so a URL's content never changes
+    Response::builder()
+        .status(200)
+        .header(CONTENT_TYPE, content_type)
+        .header(CACHE_CONTROL, "max-age=31536000, immutable")
+        .body(content)
+        .unwrap()
+}
+
+fn find(dir: &Path, uri: &Uri, conn: &mut SqliteConnection) -> Option<PathBuf> {
+    let id: i32 = uri.path().trim_matches('/').parse().ok()?;
+    let requested_size = uri
+        .query()
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .find_map(|parameter| parameter.strip_prefix("size="))
+        .and_then(|size| size.parse::<u32>().ok());
+    let local_image = match uri.host()? {
+        "podcast" => podcast::find_one(id, conn).ok()?.local_image_path,
+        "episode" => episode::find_one(id, conn).ok()?.image_local_path,
+        _ => return None,
+    };
+    if local_image.is_empty() {
+        return None;
+    }
+    if !is_hash(&local_image) {
+        return Some(PathBuf::from(local_image)).filter(|path| path.exists());
+    }
+    let largest = SIZES[SIZES.len() - 1];
+    let size = requested_size.map_or(largest, |requested| {
+        SIZES.iter().copied().find(|size| *size >= requested).unwrap_or(largest)
+    });
+    thumbnail_path(dir, &local_image, size)
+}
+
+/// Resizes artwork older versions saved whole, then deletes the files no podcast or episode uses any
+/// more: artwork a feed has since replaced, and that of deleted podcasts and episodes. Deleted
+/// podcasts lose their local images, which are downloaded again if they're ever restored.
+pub fn tidy_up(conn: &mut SqliteConnection) -> AppResult<()> {
+    tidy_up_dir(&images_dir(), conn)
+}
+
+fn tidy_up_dir(dir: &Path, conn: &mut SqliteConnection) -> AppResult<()> {
+    convert_whole_images(dir, conn)?;
+    forget_deleted_podcasts(conn)?;
+    let in_use: HashSet<String> = {
+        use crate::schema::{episodes, podcasts};
+        let mut in_use: HashSet<String> = podcasts::table
+            .select(podcasts::local_image_path)
+            .load::<String>(conn)?
+            .into_iter()
+            .collect();
+        in_use.extend(
+            episodes::table
+                .select(episodes::image_local_path)
+                .distinct()
+                .load::<String>(conn)?,
+        );
+        in_use
+    };
+    let now = SystemTime::now();
+    let mut removed = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let hash = name.split('-').next().unwrap_or_default();
+        if in_use.contains(hash) {
+            continue;
+        }
+        let age = entry.metadata()?.modified().map_or(Duration::ZERO, |modified| {
+            now.duration_since(modified).unwrap_or_default()
+        });
+        if age >= ORPHAN_MIN_AGE {
+            fs::remove_file(entry.path())?;
+            removed += 1;
+        }
+    }
+    if removed > 0 {
+        tracing::info!("Removed {removed} unused artwork files");
+    }
+    Ok(())
+}
+
+/// Stores whole images as `download` would, leaving the old files for `tidy_up` to remove. Images
+/// that can't be read are forgotten, so the next sync downloads them again.
+fn convert_whole_images(dir: &Path, conn: &mut SqliteConnection) -> AppResult<()> {
+    use crate::schema::{episodes, podcasts};
+    let podcast_images: Vec<(i32, String)> = podcasts::table
+        .select((podcasts::id, podcasts::local_image_path))
+        .filter(podcasts::local_image_path.ne(""))
+        .load(conn)?;
+    for (id, path) in podcast_images.into_iter().filter(|(_, path)| !is_hash(path)) {
+        diesel::update(podcasts::table.filter(podcasts::id.eq(id)))
+            .set(podcasts::local_image_path.eq(convert_whole_image(dir, &path)))
+            .execute(conn)?;
+    }
+    let episode_images: Vec<(i32, String)> = episodes::table
+        .select((episodes::id, episodes::image_local_path))
+        .filter(episodes::image_local_path.ne(""))
+        .load(conn)?;
+    for (id, path) in episode_images.into_iter().filter(|(_, path)| !is_hash(path)) {
+        diesel::update(episodes::table.filter(episodes::id.eq(id)))
+            .set(episodes::image_local_path.eq(convert_whole_image(dir, &path)))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+fn convert_whole_image(dir: &Path, path: &str) -> String {
+    let stored = fs::read(path)
+        .map_err(AppError::from)
+        .and_then(|bytes| store(dir, &bytes));
+    stored.unwrap_or_else(|e| {
+        tracing::info!("Could not resize artwork {path}: {:?}", e);
+        String::new()
+    })
+}
+
+fn forget_deleted_podcasts(conn: &mut SqliteConnection) -> AppResult<()> {
+    use crate::schema::{episodes, podcasts};
+    let deleted_ids = podcasts::table
+        .filter(podcasts::deleted_at.is_not_null())
+        .select(podcasts::id);
+    diesel::update(episodes::table.filter(episodes::podcast_id.eq_any(deleted_ids)))
+        .set(episodes::image_local_path.eq(""))
+        .execute(conn)?;
+    diesel::update(podcasts::table.filter(podcasts::deleted_at.is_not_null()))
+        .set(podcasts::local_image_path.eq(""))
+        .execute(conn)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_connection;
+    use diesel::connection::SimpleConnection;
+    use image::{ImageFormat, Rgb, RgbImage};
+    use std::io::Cursor;
+
+    fn test_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dimppl-artwork-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn png(width: u32, height: u32, shade: u8) -> Vec<u8> {
+        let image = DynamicImage::from(RgbImage::from_pixel(width, height, Rgb([shade, shade, shade])));
+        let mut bytes = Vec::new();
+        image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png).unwrap();
+        bytes
+    }
+
+    fn insert_podcast(local_image: &str, conn: &mut SqliteConnection) {
+        conn.batch_execute(&format!(
+            "INSERT INTO podcasts (id, guid, author, local_image_path, image_url, feed_url, name, description, \
+             created_at, updated_at) VALUES (1, 'a', '', '{local_image}', '', '', '', '', '2024-01-01', '2024-01-01');"
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn test_store() {
+        let dir = test_dir();
+        let bytes = png(600, 300, 10);
+        let hash = store(&dir, &bytes).unwrap();
+        assert_eq!(format!("{:x}", Sha256::digest(&bytes)), hash);
+        let smallest = dir.join(format!("{hash}-64.jpg"));
+        assert_eq!((64, 32), image::image_dimensions(&smallest).unwrap());
+        assert_eq!(
+            (512, 256),
+            image::image_dimensions(dir.join(format!("{hash}-512.jpg"))).unwrap()
+        );
+
+        // stored again, the existing files are kept as they are
+        fs::write(&smallest, b"kept").unwrap();
+        assert_eq!(hash, store(&dir, &bytes).unwrap());
+        assert_eq!(b"kept".to_vec(), fs::read(&smallest).unwrap());
+        assert_eq!(SIZES.len(), fs::read_dir(&dir).unwrap().count());
+
+        let small = store(&dir, &png(100, 50, 20)).unwrap();
+        assert_eq!(
+            (100, 50),
+            image::image_dimensions(dir.join(format!("{small}-512.jpg"))).unwrap()
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_find() {
+        let dir = test_dir();
+        let conn = &mut test_connection();
+        let hash = store(&dir, &png(600, 300, 10)).unwrap();
+        insert_podcast(&hash, conn);
+        let found = |uri: &str, conn: &mut SqliteConnection| {
+            find(&dir, &uri.parse().unwrap(), conn).map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        };
+        assert_eq!(
+            Some(format!("{hash}-128.jpg")),
+            found("localimages://podcast/1?size=100", conn)
+        );
+        assert_eq!(
+            Some(format!("{hash}-64.jpg")),
+            found("localimages://podcast/1?size=64", conn)
+        );
+        assert_eq!(
+            Some(format!("{hash}-512.jpg")),
+            found("localimages://podcast/1?size=2000", conn)
+        );
+        assert_eq!(Some(format!("{hash}-512.jpg")), found("localimages://podcast/1", conn));
+        assert_eq!(
+            Some(format!("{hash}-512.jpg")),
+            found("localimages://podcast/1?size=big", conn)
+        );
+        assert_eq!(None, found("localimages://podcast/2", conn));
+        assert_eq!(None, found("localimages://episode/1", conn));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_tidy_up() {
+        let dir = test_dir();
+        let conn = &mut test_connection();
+        let in_use = store(&dir, &png(100, 100, 10)).unwrap();
+        let replaced = store(&dir, &png(100, 100, 20)).unwrap();
+        insert_podcast(&in_use, conn);
+        let long_ago = SystemTime::now() - ORPHAN_MIN_AGE * 2;
+        for entry in fs::read_dir(&dir).unwrap() {
+            let file = File::options().write(true).open(entry.unwrap().path()).unwrap();
+            file.set_modified(long_ago).unwrap();
+        }
+        let just_downloaded = store(&dir, &png(100, 100, 30)).unwrap();
+
+        tidy_up_dir(&dir, conn).unwrap();
+        for size in SIZES {
+            assert!(thumbnail_path(&dir, &in_use, *size).is_some());
+            assert!(thumbnail_path(&dir, &replaced, *size).is_none());
+            assert!(thumbnail_path(&dir, &just_downloaded, *size).is_some());
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
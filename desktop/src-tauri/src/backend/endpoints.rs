@@ -1,7 +1,20 @@
-use crate::backend::models::{CreateDeviceRequest, CreateDeviceResponse, CreateUserResponse};
+use crate::backend::models::{
+    CreateDeviceRequest, CreateDeviceResponse, CreatePairingCodeResponse, CreateUserResponse, RedeemPairingCodeRequest,
+};
 use crate::environment::API_URL;
 use crate::errors::AppResult;
+use anyhow::anyhow;
+use derive_more::{Display, Error};
+use dimppl_shared::encoding::{ContentEncoding, ContentType, ACCEPT_ENCODING_ALL};
+use dimppl_shared::protocol::{ProtocolInfo, CAPABILITIES_HEADER, PROTOCOL_VERSION_HEADER};
 use dimppl_shared::sync::{SyncStateRequest, SyncStateResponse};
+use reqwest::header::{ACCEPT, ACCEPT_ENCODING, AUTHORIZATION, CONTENT_ENCODING, CONTENT_TYPE};
+use reqwest::StatusCode;
+
+/// The server and this build have no protocol version in common.
+#[derive(Debug, Display, Error)]
+#[display("Esta versão do dimppl não é mais compatível com o servidor. Por favor, atualize o aplicativo.")]
+pub struct IncompatibleProtocolError;
 
 pub async fn create_user() -> AppResult<CreateUserResponse> {
     let client = reqwest::Client::new();
@@ -26,15 +39,92 @@ pub async fn create_device(request: &CreateDeviceRequest) -> AppResult<CreateDev
     Ok(response)
 }
 
-pub async fn sync_remote_podcasts(token: &str, request: &SyncStateRequest) -> AppResult<SyncStateResponse> {
+pub async fn create_pairing_code(token: &str) -> AppResult<CreatePairingCodeResponse> {
     let client = reqwest::Client::new();
     let response = client
-        .post(format!("{API_URL}/sync"))
-        .header("Authorization", format!("Bearer {token}"))
+        .post(format!("{API_URL}/pairing_codes"))
+        .header(AUTHORIZATION, format!("Bearer {token}"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<CreatePairingCodeResponse>()
+        .await?;
+    Ok(response)
+}
+
+pub async fn redeem_pairing_code(request: &RedeemPairingCodeRequest) -> AppResult<CreateDeviceResponse> {
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!("{API_URL}/pairing_codes/redeem"))
         .json(request)
         .send()
         .await?
-        .json::<SyncStateResponse>()
+        .error_for_status()?
+        .json::<CreateDeviceResponse>()
         .await?;
     Ok(response)
 }
+
+pub async fn sync_remote_podcasts(token: &str, request: &SyncStateRequest) -> AppResult<SyncStateResponse> {
+    let response = post_sync(token, request, ContentType::MessagePack, ContentEncoding::Zstd).await?;
+    // older servers only speak JSON
+    let response = if response.status() == StatusCode::UNSUPPORTED_MEDIA_TYPE {
+        post_sync(token, request, ContentType::Json, ContentEncoding::Identity).await?
+    } else {
+        response
+    };
+    if response.status() == StatusCode::UPGRADE_REQUIRED {
+        return Err(IncompatibleProtocolError.into());
+    }
+    let response = response.error_for_status()?;
+    let server_protocol = ProtocolInfo::from_headers(
+        header_str(&response, PROTOCOL_VERSION_HEADER),
+        header_str(&response, CAPABILITIES_HEADER),
+    );
+    if !server_protocol.is_some_and(|protocol| protocol.is_supported()) {
+        return Err(IncompatibleProtocolError.into());
+    }
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(ContentType::from_mime)
+        .unwrap_or_default();
+    let content_encoding = response
+        .headers()
+        .get(CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .and_then(ContentEncoding::from_token)
+        .unwrap_or_default();
+    let body = content_encoding
+        .decompress(response.bytes().await?.to_vec())
+        .map_err(|e| anyhow!(e))?;
+    Ok(content_type.deserialize(&body).map_err(|e| anyhow!(e))?)
+}
+
+async fn post_sync(
+    token: &str,
+    request: &SyncStateRequest,
+    content_type: ContentType,
+    content_encoding: ContentEncoding,
+) -> AppResult<reqwest::Response> {
+    let body = content_type.serialize(request).map_err(|e| anyhow!(e))?;
+    let body = content_encoding.compress(body).map_err(|e| anyhow!(e))?;
+    let protocol = ProtocolInfo::current();
+    let mut builder = reqwest::Client::new()
+        .post(format!("{API_URL}/sync"))
+        .header(AUTHORIZATION, format!("Bearer {token}"))
+        .header(CONTENT_TYPE, content_type.mime())
+        .header(ACCEPT, "application/msgpack, application/json;q=0.5")
+        .header(ACCEPT_ENCODING, ACCEPT_ENCODING_ALL)
+        .header(PROTOCOL_VERSION_HEADER, protocol.version_header())
+        .header(CAPABILITIES_HEADER, protocol.capabilities_header());
+    if content_encoding != ContentEncoding::Identity {
+        builder = builder.header(CONTENT_ENCODING, content_encoding.token());
+    }
+    Ok(builder.body(body).send().await?)
+}
+
+fn header_str<'a>(response: &'a reqwest::Response, name: &str) -> Option<&'a str> {
+    response.headers().get(name).and_then(|v| v.to_str().ok())
+}
@@ -1,8 +1,10 @@
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
 use dimppl_shared::sync::{SyncPodcast, SyncPodcastEpisode};
 
 use crate::models::episode::EpisodeWithProgress;
+use crate::models::feed_auth::public_url;
 use crate::models::Podcast;
 
 #[derive(Serialize, Deserialize)]
@@ -22,6 +24,18 @@ pub struct CreateUserResponse {
     pub access_key: String,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct CreatePairingCodeResponse {
+    pub code: String,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RedeemPairingCodeRequest {
+    pub code: String,
+    pub device_name: String,
+}
+
 impl From<Podcast> for SyncPodcast {
     fn from(value: Podcast) -> Self {
         let Podcast {
@@ -32,7 +46,7 @@ impl From<Podcast> for SyncPodcast {
         } = value;
         Self {
             guid,
-            url: feed_url,
+            url: public_url(&feed_url),
             deleted_at: None,
             updated_at,
         }
@@ -43,7 +57,7 @@ impl From<EpisodeWithProgress> for SyncPodcastEpisode {
     fn from(value: EpisodeWithProgress) -> Self {
         Self {
             guid: value.episode.guid,
-            url: value.episode.content_url,
+            url: public_url(&value.episode.content_url),
             listened_seconds: value.progress.listened_seconds,
             completed: value.progress.completed,
             updated_at: value.progress.updated_at,
@@ -1,24 +1,38 @@
 use crate::backend::endpoints;
-use crate::backend::endpoints::sync_remote_podcasts;
-use crate::backend::models::CreateDeviceRequest;
+use crate::backend::endpoints::{sync_remote_podcasts, IncompatibleProtocolError};
+use crate::backend::models::{CreateDeviceRequest, RedeemPairingCodeRequest};
 use crate::config::{Config, ConfigWrapper};
 use crate::context_menus::ContextMenuType;
 use crate::database::db_connect;
+use crate::directory;
+use crate::directory::DirectoryPodcast;
+use crate::environment::API_URL;
 use crate::errors::AppResult;
 use crate::frontend_change_tracking::{AppHandleExt, EntityChange};
-use crate::models::episode::{EpisodeWithFileSize, EpisodeWithPodcast, EpisodeWithProgress};
+use crate::models::bookmark::{BookmarkWithEpisode, NewBookmarkRequest};
+use crate::models::episode::{EpisodeListOptions, EpisodeWithFileSize, EpisodeWithPodcast, EpisodeWithProgress};
 use crate::models::episode_downloads::EpisodeDownloads;
+use crate::models::episode_search::{EpisodeSearchHit, EpisodeSearchOptions};
 use crate::models::podcast::{
-    build_backend_sync_request, store_backend_sync_response, sync_single_podcast, UpdatePodcastRequest,
+    build_backend_sync_request, store_backend_sync_response, sync_single_podcast, ImportResult, UpdatePodcastRequest,
 };
-use crate::models::{episode, podcast, EpisodeProgress, PodcastStats};
-use crate::models::{Episode, Podcast};
+use crate::models::podcast_settings::UpdatePodcastSettingsRequest;
+use crate::models::transcript::TranscriptHit;
+use crate::models::{
+    bookmark, chapter, episode, episode_search, feed_health, feed_move, feed_warning, opml, podcast, podcast_merge,
+    podcast_settings, transcript, Chapter, EpisodeProgress, FeedHealth, FeedMove, FeedWarning, PodcastSettings,
+    PodcastStats, TranscriptSegment,
+};
+use crate::models::{Bookmark, Episode, Podcast};
 use crate::player::Player;
 use crate::show_file_in_folder::show_file_in_folder;
+use chrono::NaiveDateTime;
 use diesel::SqliteConnection;
+use serde::Serialize;
 use std::ops::Deref;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager, Window};
+use url::Url;
 use uuid::Uuid;
 
 #[tauri::command]
@@ -30,15 +44,26 @@ pub async fn list_all_podcasts() -> AppResult<Vec<Podcast>> {
 pub async fn sync_podcasts_inner(app: AppHandle, config: &Config) -> AppResult<()> {
     let mut connection = db_connect();
 
-    podcast::sync_podcasts(&mut connection, &app).await?;
-    sync_to_backend(&config, &mut connection).await?;
+    podcast::sync_podcasts(&mut connection, &app, config.refresh_concurrency).await?;
+    sync_to_backend(&app, config, &mut connection).await?;
     invalidate_all_caches(app.clone(), &mut connection).await?;
     Ok(())
 }
 
-pub async fn sync_to_backend(config: &Config, connection: &mut SqliteConnection) -> AppResult<()> {
+/// Tells the frontend when the server no longer accepts this version, whichever sync ran into it.
+pub async fn sync_to_backend(app: &AppHandle, config: &Config, connection: &mut SqliteConnection) -> AppResult<()> {
     let sync_state_request = build_backend_sync_request(connection)?;
-    let backend_sync_result = sync_remote_podcasts(&config.access_token, &sync_state_request).await?;
+    let backend_sync_result = match sync_remote_podcasts(&config.access_token, &sync_state_request).await {
+        Ok(result) => result,
+        Err(e) => {
+            if e.0.is::<IncompatibleProtocolError>() {
+                let _ = app.emit("backend-incompatible", e.to_string());
+            }
+            return Err(e);
+        }
+    };
+    let sent_merges: Vec<String> = sync_state_request.merged_podcasts.into_keys().collect();
+    podcast_merge::clear(&sent_merges, connection)?;
     store_backend_sync_response(connection, backend_sync_result).await?;
     Ok(())
 }
@@ -86,7 +111,9 @@ pub async fn update_podcast(
     tokio::spawn(async move {
         sync_single_podcast(app.clone(), podcast).await.unwrap();
         let mut connection = db_connect();
-        sync_to_backend(&config, &mut connection).await.unwrap();
+        if let Err(e) = sync_to_backend(&app, &config, &mut connection).await {
+            tracing::info!("Failed to sync updated podcast: {:?}", e);
+        }
         invalidate_all_caches(app.clone(), &mut connection).await.unwrap();
     });
     Ok(())
@@ -98,7 +125,9 @@ pub async fn delete_podcast(app: AppHandle, config_wrapper: tauri::State<'_, Con
     tokio::spawn(async move {
         let mut connection = db_connect();
         podcast::delete_podcast(&mut connection, id).unwrap();
-        sync_to_backend(&config, &mut connection).await.unwrap();
+        if let Err(e) = sync_to_backend(&app, &config, &mut connection).await {
+            tracing::info!("Failed to sync deleted podcast: {:?}", e);
+        }
         invalidate_all_caches(app, &mut connection).await.unwrap();
     });
     Ok(())
@@ -122,6 +151,12 @@ pub fn list_latest_episodes() -> AppResult<Vec<EpisodeWithPodcast>> {
     episode::list_latest_episodes(&mut connection)
 }
 
+#[tauri::command]
+pub fn search_episodes(query: String, options: EpisodeSearchOptions) -> AppResult<Vec<EpisodeSearchHit>> {
+    let mut conn = db_connect();
+    episode_search::search(&query, &options, &mut conn)
+}
+
 #[tauri::command]
 pub fn get_config(config_wrapper: tauri::State<ConfigWrapper>) -> Config {
     config_wrapper.0.lock().unwrap().clone()
@@ -164,13 +199,55 @@ pub async fn register_device(device_name: String, config_wrapper: tauri::State<'
     Ok(())
 }
 
-async fn do_import_podcast(url: String, app: AppHandle) -> AppResult<()> {
-    let mut conn = db_connect();
-    let podcast = podcast::import_podcast_from_url(url, &mut conn).await?;
-    app.send_invalidate_cache(EntityChange::Podcast(podcast.id))?;
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairingCode {
+    pub code: String,
+    pub expires_at: NaiveDateTime,
+    pub qr_payload: String,
+}
+
+#[tauri::command]
+pub async fn create_pairing_code(config_wrapper: tauri::State<'_, ConfigWrapper>) -> AppResult<PairingCode> {
+    let token = config_wrapper.0.lock().unwrap().access_token.clone();
+    let response = endpoints::create_pairing_code(&token).await?;
+    let qr_payload = Url::parse_with_params(
+        "dimppl://pair",
+        &[("code", response.code.as_str()), ("server", API_URL)],
+    )?;
+    Ok(PairingCode {
+        code: response.code,
+        expires_at: response.expires_at,
+        qr_payload: qr_payload.into(),
+    })
+}
+
+#[tauri::command]
+pub async fn redeem_pairing_code(
+    code: String,
+    device_name: String,
+    config_wrapper: tauri::State<'_, ConfigWrapper>,
+) -> AppResult<()> {
+    let mut config: Config = config_wrapper.0.lock().unwrap().clone();
+    config.device_name.clone_from(&device_name);
+    let request = RedeemPairingCodeRequest {
+        code: code.trim().to_string(),
+        device_name,
+    };
+    let response = endpoints::redeem_pairing_code(&request).await?;
+    config.access_token = response.access_token;
+    config_wrapper.update(config)?;
     Ok(())
 }
 
+async fn do_import_podcast(url: String, app: AppHandle) -> AppResult<ImportResult> {
+    let result = podcast::import_podcast_from_url(url).await?;
+    if let ImportResult::Imported(podcast) = &result {
+        app.send_invalidate_cache(EntityChange::Podcast(podcast.id))?;
+    }
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn import_podcast(url: String, app: AppHandle) -> AppResult<String> {
     let import_id = Uuid::new_v4().to_string();
@@ -178,9 +255,12 @@ pub async fn import_podcast(url: String, app: AppHandle) -> AppResult<String> {
     tokio::spawn(async move {
         let result = do_import_podcast(url, app.clone()).await;
         match result {
-            Ok(_) => {
+            Ok(ImportResult::Imported(_)) => {
                 let _ = app.emit("import-podcast-done", import_id_clone.clone());
             }
+            Ok(ImportResult::Candidates(candidates)) => {
+                let _ = app.emit("import-podcast-candidates", (import_id_clone.clone(), candidates));
+            }
             Err(e) => {
                 let _ = app.emit("import-podcast-error", (import_id_clone.clone(), e.to_string()));
             }
@@ -190,10 +270,95 @@ pub async fn import_podcast(url: String, app: AppHandle) -> AppResult<String> {
     Ok(import_id)
 }
 
+/// Starts importing every feed in an OPML file. Progress comes as `opml-import-progress` events
+/// tagged with the returned id.
 #[tauri::command]
-pub async fn list_podcast_episodes(id: i32) -> AppResult<Vec<EpisodeWithProgress>> {
+pub async fn import_opml(content: String, app: AppHandle) -> AppResult<String> {
+    let feeds = opml::parse(&content)?;
+    let import_id = Uuid::new_v4().to_string();
+    tokio::spawn(opml::import(import_id.clone(), feeds, app));
+    Ok(import_id)
+}
+
+#[tauri::command]
+pub fn export_opml(include_deleted: bool) -> AppResult<String> {
     let mut conn = db_connect();
-    episode::list_for_podcast(id, &mut conn)
+    opml::export(include_deleted, &mut conn)
+}
+
+#[tauri::command]
+pub async fn search_podcasts(
+    query: String,
+    config_wrapper: tauri::State<'_, ConfigWrapper>,
+) -> AppResult<Vec<DirectoryPodcast>> {
+    let config = config_wrapper.0.lock().unwrap().clone();
+    directory::search(&config, &query).await
+}
+
+#[tauri::command]
+pub async fn list_podcast_episodes(
+    id: i32,
+    options: Option<EpisodeListOptions>,
+) -> AppResult<Vec<EpisodeWithProgress>> {
+    let mut conn = db_connect();
+    let options = EpisodeListOptions {
+        sort_order: podcast_settings::find_for_podcast(id, &mut conn)?.sort_order(),
+        ..options.unwrap_or_default()
+    };
+    episode::list_for_podcast(id, &options, &mut conn)
+}
+
+#[tauri::command]
+pub fn list_podcast_seasons(id: i32) -> AppResult<Vec<i32>> {
+    let mut conn = db_connect();
+    episode::list_seasons(id, &mut conn)
+}
+
+#[tauri::command]
+pub fn get_podcast_settings(id: i32) -> AppResult<PodcastSettings> {
+    let mut conn = db_connect();
+    podcast_settings::find_for_podcast(id, &mut conn)
+}
+
+#[tauri::command]
+pub fn list_feed_warnings(id: i32) -> AppResult<Vec<FeedWarning>> {
+    let mut conn = db_connect();
+    feed_warning::list_for_podcast(id, &mut conn)
+}
+
+#[tauri::command]
+pub fn list_feed_health() -> AppResult<Vec<FeedHealth>> {
+    let mut conn = db_connect();
+    feed_health::list_all(&mut conn)
+}
+
+#[tauri::command]
+pub fn list_feed_moves(id: i32) -> AppResult<Vec<FeedMove>> {
+    let mut conn = db_connect();
+    feed_move::list_for_podcast(id, &mut conn)
+}
+
+#[tauri::command]
+pub async fn update_podcast_settings(
+    app: AppHandle,
+    config_wrapper: tauri::State<'_, ConfigWrapper>,
+    request: UpdatePodcastSettingsRequest,
+) -> AppResult<PodcastSettings> {
+    let id = request.podcast_id;
+    let settings = {
+        let mut connection = db_connect();
+        podcast_settings::update_settings(&mut connection, request)?
+    };
+    app.send_invalidate_cache(EntityChange::Podcast(id))?;
+    app.send_invalidate_cache(EntityChange::PodcastEpisodes(id))?;
+    let config = config_wrapper.0.lock().unwrap().clone();
+    tokio::spawn(async move {
+        let mut connection = db_connect();
+        if let Err(e) = sync_to_backend(&app, &config, &mut connection).await {
+            tracing::info!("Failed to sync podcast settings: {:?}", e);
+        }
+    });
+    Ok(settings)
 }
 
 #[tauri::command]
@@ -223,6 +388,13 @@ pub fn get_episode(id: i32) -> AppResult<Episode> {
     episode::find_one(id, &mut conn)
 }
 
+#[tauri::command]
+pub async fn list_episode_chapters(id: i32) -> AppResult<Vec<Chapter>> {
+    let mut conn = db_connect();
+    let episode = episode::find_one(id, &mut conn)?;
+    chapter::fetch_for_episode(&episode, &mut conn).await
+}
+
 #[tauri::command]
 pub fn get_episode_full(id: i32) -> AppResult<EpisodeWithPodcast> {
     let mut conn = db_connect();
@@ -230,16 +402,23 @@ pub fn get_episode_full(id: i32) -> AppResult<EpisodeWithPodcast> {
 }
 
 #[tauri::command]
-pub fn play_episode(id: i32, player: tauri::State<'_, Arc<Player>>) -> AppResult<()> {
+pub fn play_episode(
+    id: i32,
+    player: tauri::State<'_, Arc<Player>>,
+    config_wrapper: tauri::State<'_, ConfigWrapper>,
+) -> AppResult<()> {
     let player = player.deref().clone();
     let mut conn = db_connect();
     let episode = episode::find_one(id, &mut conn)?;
     let progress = episode::find_one_progress(id, &mut conn)?;
-    let start_seconds = if progress.completed {
-        0
+    let settings = podcast_settings::find_for_podcast(episode.podcast_id, &mut conn)?;
+    let start_seconds = if progress.completed || progress.listened_seconds == 0 {
+        settings.skip_intro_seconds.max(0) as u64
     } else {
         progress.listened_seconds as u64
     };
+    let global_speed = config_wrapper.0.lock().unwrap().playback_speed;
+    player.set_playback_speed(settings.playback_speed.unwrap_or(global_speed));
     std::thread::spawn(move || {
         let _ = player.play_episode(episode, start_seconds);
     });
@@ -255,6 +434,8 @@ pub fn player_action(action: String, player: tauri::State<'_, Arc<Player>>) -> A
             "pause" => player.pause(),
             "skip_forwards" => player.skip_forwards(),
             "skip_backwards" => player.skip_backwards(),
+            "next_chapter" => player.next_chapter(),
+            "previous_chapter" => player.previous_chapter(),
             _ => {}
         };
     });
@@ -366,3 +547,123 @@ pub fn list_podcast_stats() -> AppResult<Vec<PodcastStats>> {
     let stats = podcast::list_podcast_stats(&mut connection)?;
     Ok(stats)
 }
+
+fn spawn_sync_to_backend(app: AppHandle, config_wrapper: &ConfigWrapper) {
+    let config = config_wrapper.0.lock().unwrap().clone();
+    tokio::spawn(async move {
+        let mut connection = db_connect();
+        if let Err(e) = sync_to_backend(&app, &config, &mut connection).await {
+            tracing::info!("Failed to sync bookmarks: {:?}", e);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn add_bookmark(
+    app: AppHandle,
+    config_wrapper: tauri::State<'_, ConfigWrapper>,
+    request: NewBookmarkRequest,
+) -> AppResult<Bookmark> {
+    let bookmark = {
+        let mut connection = db_connect();
+        bookmark::create(&mut connection, request)?
+    };
+    app.send_invalidate_cache(EntityChange::AllBookmarks)?;
+    spawn_sync_to_backend(app, &config_wrapper);
+    Ok(bookmark)
+}
+
+#[tauri::command]
+pub fn list_bookmarks(episode_id: Option<i32>) -> AppResult<Vec<BookmarkWithEpisode>> {
+    let mut connection = db_connect();
+    bookmark::list(&mut connection, episode_id)
+}
+
+#[tauri::command]
+pub async fn delete_bookmark(
+    app: AppHandle,
+    config_wrapper: tauri::State<'_, ConfigWrapper>,
+    id: i32,
+) -> AppResult<()> {
+    {
+        let mut connection = db_connect();
+        bookmark::delete(&mut connection, id)?;
+    }
+    app.send_invalidate_cache(EntityChange::AllBookmarks)?;
+    spawn_sync_to_backend(app, &config_wrapper);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn play_bookmark(id: i32, player: tauri::State<'_, Arc<Player>>) -> AppResult<()> {
+    let mut conn = db_connect();
+    let bookmark = bookmark::find_one(id, &mut conn)?;
+    seek_or_play(
+        player.deref().clone(),
+        bookmark.episode_id,
+        bookmark.start_seconds as i64,
+        &mut conn,
+    )
+}
+
+#[tauri::command]
+pub async fn list_transcript_segments(id: i32) -> AppResult<Vec<TranscriptSegment>> {
+    let mut conn = db_connect();
+    let episode = episode::find_one(id, &mut conn)?;
+    transcript::fetch_for_episode(&episode, &mut conn).await
+}
+
+#[tauri::command]
+pub fn current_transcript_segment(player: tauri::State<'_, Arc<Player>>) -> AppResult<Option<TranscriptSegment>> {
+    let Some(status) = player.latest_status() else {
+        return Ok(None);
+    };
+    let Some(episode) = status.episode else {
+        return Ok(None);
+    };
+    let mut conn = db_connect();
+    transcript::segment_at(episode.id, status.elapsed as f64, &mut conn)
+}
+
+#[tauri::command]
+pub fn search_transcripts(query: String) -> AppResult<Vec<TranscriptHit>> {
+    let mut conn = db_connect();
+    transcript::search(&query, &mut conn)
+}
+
+/// Plays the episode from a seek point in its show notes.
+#[tauri::command]
+pub fn play_episode_at(id: i32, seconds: i64, player: tauri::State<'_, Arc<Player>>) -> AppResult<()> {
+    let mut conn = db_connect();
+    seek_or_play(player.deref().clone(), id, seconds, &mut conn)
+}
+
+#[tauri::command]
+pub fn play_transcript_segment(id: i32, player: tauri::State<'_, Arc<Player>>) -> AppResult<()> {
+    let mut conn = db_connect();
+    let segment = transcript::find_one(id, &mut conn)?;
+    seek_or_play(
+        player.deref().clone(),
+        segment.episode_id,
+        segment.start_seconds as i64,
+        &mut conn,
+    )
+}
+
+/// Seeks if the episode is the one loaded in the player, otherwise starts playing it from `seconds`.
+fn seek_or_play(player: Arc<Player>, episode_id: i32, seconds: i64, conn: &mut SqliteConnection) -> AppResult<()> {
+    let is_loaded = player
+        .latest_status()
+        .and_then(|status| status.episode)
+        .is_some_and(|episode| episode.id == episode_id);
+    if is_loaded {
+        player.seek_to(seconds);
+        player.play();
+        return Ok(());
+    }
+    let episode = episode::find_one(episode_id, conn)?;
+    std::thread::spawn(move || {
+        let _ = player.play_episode(episode, seconds.max(0) as u64);
+    });
+    Ok(())
+}
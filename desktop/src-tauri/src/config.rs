@@ -1,4 +1,6 @@
+use crate::database::POOL_SIZE;
 use crate::directories::project_dirs;
+use crate::directory::{itunes, podcast_index, DirectoryProvider};
 use crate::errors::AppResult;
 use gethostname::gethostname;
 use serde::{Deserialize, Serialize};
@@ -6,21 +8,36 @@ use std::fs;
 use std::path::PathBuf;
 use std::sync::Mutex;
 
+/// Each feed being refreshed holds a pooled connection, so refreshes get at most half of the pool.
+pub const MAX_REFRESH_CONCURRENCY: usize = POOL_SIZE as usize / 2;
+
 #[derive(Serialize, Deserialize, Clone)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Config {
     pub user_access_key: String,
     pub device_name: String,
     pub access_token: String,
     pub volume: f32,
     pub playback_speed: f32,
+    /// How many feeds are fetched at once when refreshing, from 1 to `MAX_REFRESH_CONCURRENCY`.
+    pub refresh_concurrency: usize,
+    /// The shortest time between background refreshes of a feed. Feeds that publish rarely wait longer.
+    pub refresh_interval_minutes: u32,
+    /// Stops background refreshes; refreshing by hand still works.
+    pub refresh_paused: bool,
+    pub directory_provider: DirectoryProvider,
+    /// Where the iTunes Search API is reached, overridable to point at a local stub.
+    pub itunes_search_url: String,
+    pub podcast_index_url: String,
+    pub podcast_index_key: String,
+    pub podcast_index_secret: String,
 }
 
 impl Config {
     pub fn load() -> AppResult<Self> {
         let contents = fs::read_to_string(config_path())?;
         let config: Self = toml::from_str(contents.as_str())?;
-        Ok(config)
+        Ok(config.within_limits())
     }
 
     pub fn load_or_save_default() -> AppResult<Self> {
@@ -32,6 +49,12 @@ impl Config {
         Ok(config)
     }
 
+    /// Brings settings edited by hand back into the range the app supports.
+    fn within_limits(mut self) -> Self {
+        self.refresh_concurrency = self.refresh_concurrency.clamp(1, MAX_REFRESH_CONCURRENCY);
+        self
+    }
+
     pub fn save(&self) -> AppResult<()> {
         let serialized = toml::to_string(self)?;
         fs::write(config_path(), serialized)?;
@@ -52,6 +75,14 @@ impl Default for Config {
             access_token: "".into(),
             volume: 1.0,
             playback_speed: 1.0,
+            refresh_concurrency: 4,
+            refresh_interval_minutes: 60,
+            refresh_paused: false,
+            directory_provider: DirectoryProvider::default(),
+            itunes_search_url: itunes::DEFAULT_URL.into(),
+            podcast_index_url: podcast_index::DEFAULT_URL.into(),
+            podcast_index_key: "".into(),
+            podcast_index_secret: "".into(),
         }
     }
 }
@@ -68,6 +99,7 @@ impl Default for ConfigWrapper {
 
 impl ConfigWrapper {
     pub fn update(&self, config: Config) -> AppResult<()> {
+        let config = config.within_limits();
         config.save()?;
         *self.0.lock().unwrap() = config;
         Ok(())
@@ -36,9 +36,13 @@ impl diesel::r2d2::CustomizeConnection<SqliteConnection, diesel::r2d2::Error> fo
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
+/// Connections kept by the pool. Feed refreshes use at most half of them, see `MAX_REFRESH_CONCURRENCY`.
+pub const POOL_SIZE: u32 = 10;
+
 static POOL: LazyLock<Pool<ConnectionManager<SqliteConnection>>> = LazyLock::new(|| {
     let manager = ConnectionManager::<SqliteConnection>::new(database_path());
     Pool::builder()
+        .max_size(POOL_SIZE)
         .test_on_check_out(true)
         .connection_customizer(Box::new(ConnectionOptions {
             enable_wal: true,
@@ -61,6 +65,16 @@ pub fn migrate_database(
     Ok(())
 }
 
+/// A migrated in-memory database for tests.
+#[cfg(test)]
+pub fn test_connection() -> SqliteConnection {
+    use diesel::Connection;
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    conn.batch_execute("PRAGMA foreign_keys = ON;").unwrap();
+    migrate_database(&mut conn).unwrap();
+    conn
+}
+
 pub fn prepare_database() {
     let mut conn = db_connect();
     let _ = migrate_database(&mut conn);
@@ -0,0 +1,58 @@
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::errors::AppResult;
+
+pub mod itunes;
+pub mod podcast_index;
+
+/// How many results a search asks the directory for.
+const SEARCH_LIMIT: usize = 30;
+
+/// A podcast listed in a directory. `feed_url` can be handed straight to `import_podcast`.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryPodcast {
+    pub title: String,
+    pub author: String,
+    pub artwork_url: String,
+    pub feed_url: String,
+}
+
+/// A searchable index of podcasts.
+pub trait PodcastDirectory {
+    fn search(&self, query: &str) -> impl Future<Output = AppResult<Vec<DirectoryPodcast>>> + Send;
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DirectoryProvider {
+    #[default]
+    Itunes,
+    PodcastIndex,
+}
+
+/// Searches whichever directory the user picked.
+pub async fn search(config: &Config, query: &str) -> AppResult<Vec<DirectoryPodcast>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut results = match config.directory_provider {
+        DirectoryProvider::Itunes => itunes::Itunes::new(&config.itunes_search_url).search(query).await?,
+        DirectoryProvider::PodcastIndex => {
+            podcast_index::PodcastIndex::new(
+                &config.podcast_index_url,
+                &config.podcast_index_key,
+                &config.podcast_index_secret,
+            )
+            .search(query)
+            .await?
+        }
+    };
+    // without a feed there's nothing to import
+    results.retain(|podcast| !podcast.feed_url.is_empty());
+    Ok(results)
+}
@@ -0,0 +1,65 @@
+use serde::Deserialize;
+
+use crate::directory::{DirectoryPodcast, PodcastDirectory, SEARCH_LIMIT};
+use crate::errors::AppResult;
+
+pub const DEFAULT_URL: &str = "https://itunes.apple.com";
+
+/// The iTunes Search API. It needs no key.
+pub struct Itunes {
+    base_url: String,
+}
+
+impl Itunes {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResponse {
+    results: Vec<SearchResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResult {
+    collection_name: Option<String>,
+    artist_name: Option<String>,
+    artwork_url600: Option<String>,
+    artwork_url100: Option<String>,
+    feed_url: Option<String>,
+}
+
+impl PodcastDirectory for Itunes {
+    async fn search(&self, query: &str) -> AppResult<Vec<DirectoryPodcast>> {
+        let limit = SEARCH_LIMIT.to_string();
+        let response = reqwest::Client::new()
+            .get(format!("{}/search", self.base_url))
+            .query(&[
+                ("media", "podcast"),
+                ("entity", "podcast"),
+                ("term", query),
+                ("limit", limit.as_str()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<SearchResponse>()
+            .await?;
+        let podcasts = response
+            .results
+            .into_iter()
+            .map(|result| DirectoryPodcast {
+                title: result.collection_name.unwrap_or_default(),
+                author: result.artist_name.unwrap_or_default(),
+                artwork_url: result.artwork_url600.or(result.artwork_url100).unwrap_or_default(),
+                feed_url: result.feed_url.unwrap_or_default(),
+            })
+            .collect();
+        Ok(podcasts)
+    }
+}
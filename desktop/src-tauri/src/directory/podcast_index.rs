@@ -0,0 +1,81 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
+use reqwest::header::{AUTHORIZATION, USER_AGENT};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+
+use crate::directory::{DirectoryPodcast, PodcastDirectory, SEARCH_LIMIT};
+use crate::errors::AppResult;
+
+pub const DEFAULT_URL: &str = "https://api.podcastindex.org/api/1.0";
+
+/// The Podcast Index API. Every request is signed with the key and secret from
+/// <https://api.podcastindex.org>.
+pub struct PodcastIndex {
+    base_url: String,
+    key: String,
+    secret: String,
+}
+
+impl PodcastIndex {
+    pub fn new(base_url: &str, key: &str, secret: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            key: key.to_string(),
+            secret: secret.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    feeds: Vec<SearchResult>,
+}
+
+#[derive(Deserialize)]
+struct SearchResult {
+    title: Option<String>,
+    author: Option<String>,
+    artwork: Option<String>,
+    image: Option<String>,
+    url: Option<String>,
+}
+
+impl PodcastDirectory for PodcastIndex {
+    async fn search(&self, query: &str) -> AppResult<Vec<DirectoryPodcast>> {
+        if self.key.is_empty() || self.secret.is_empty() {
+            return Err(anyhow!("Podcast Index API key and secret are not configured").into());
+        }
+        let date = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs().to_string();
+        let signature = format!("{:x}", Sha1::digest(format!("{}{}{date}", self.key, self.secret)));
+        let max = SEARCH_LIMIT.to_string();
+        let response = reqwest::Client::new()
+            .get(format!("{}/search/byterm", self.base_url))
+            .query(&[("q", query), ("max", max.as_str())])
+            .header(USER_AGENT, "dimppl")
+            .header("X-Auth-Key", &self.key)
+            .header("X-Auth-Date", &date)
+            .header(AUTHORIZATION, signature)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<SearchResponse>()
+            .await?;
+        let podcasts = response
+            .feeds
+            .into_iter()
+            .map(|result| DirectoryPodcast {
+                title: result.title.unwrap_or_default(),
+                author: result.author.unwrap_or_default(),
+                artwork_url: result
+                    .artwork
+                    .filter(|url| !url.is_empty())
+                    .or(result.image)
+                    .unwrap_or_default(),
+                feed_url: result.url.unwrap_or_default(),
+            })
+            .collect();
+        Ok(podcasts)
+    }
+}
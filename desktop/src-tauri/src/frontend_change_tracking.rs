@@ -10,6 +10,7 @@ pub enum EntityChange {
     EpisodeProgress(i32),
     AllDownloads,
     AllEpisodes,
+    AllBookmarks,
 }
 
 impl EntityChange {
@@ -36,6 +37,9 @@ impl EntityChange {
             EntityChange::AllEpisodes => {
                 vec![String::from("allEpisodes")]
             }
+            EntityChange::AllBookmarks => {
+                vec![String::from("allBookmarks")]
+            }
         }
     }
 }
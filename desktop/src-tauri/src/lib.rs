@@ -1,10 +1,6 @@
 extern crate core;
 
-use std::fs;
-
-use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::http::Response;
 
 use crate::config::ConfigWrapper;
 use crate::context_menus::{context_menu_event_handler, ContextMenuOption};
@@ -12,20 +8,22 @@ use crate::database::{database_path, db_connect, prepare_database};
 use crate::directories::ensure_data_dir;
 use crate::main_menu::{build_main_menu, main_menu_event_handler, MainMenuOption};
 use crate::models::episode_downloads::EpisodeDownloads;
-use crate::models::{episode, podcast};
 use crate::player::Player;
 use tauri::Manager;
 use tracing::Level;
 use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::EnvFilter;
 use crate::progress_updater::ProgressUpdater;
+use crate::refresh_scheduler::RefreshScheduler;
 
+mod artwork;
 mod backend;
 mod commands;
 mod config;
 mod context_menus;
 mod database;
 mod directories;
+mod directory;
 mod environment;
 mod errors;
 mod extensions;
@@ -37,6 +35,7 @@ mod player;
 mod schema;
 mod show_file_in_folder;
 mod progress_updater;
+mod refresh_scheduler;
 
 #[allow(deprecated)]
 pub async fn run() {
@@ -58,30 +57,21 @@ pub async fn run() {
     tracing::info!("db url: {db_url}");
     tauri::Builder::default()
         .plugin(tauri_plugin_os::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(ConfigWrapper::default())
-        .register_uri_scheme_protocol("localimages", move |_app, request| {
-            let mut conn = db_connect();
-            let uri_str = request.uri().to_string();
-            if uri_str.starts_with("localimages://podcast/") {
-                let podcast_id: i32 = uri_str.strip_prefix("localimages://podcast/").unwrap().parse().unwrap();
-                let podcast = podcast::find_one(podcast_id, &mut conn).unwrap();
-                let path = PathBuf::from(podcast.local_image_path);
-                if path.exists() {
-                    return Response::builder().status(200).body(fs::read(path).unwrap()).unwrap();
-                }
-            } else if uri_str.starts_with("localimages://episode/") {
-                let episode_id: i32 = uri_str.strip_prefix("localimages://episode/").unwrap().parse().unwrap();
-                let episode = episode::find_one(episode_id, &mut conn).unwrap();
-                let path = PathBuf::from(episode.image_local_path);
-                if path.exists() {
-                    return Response::builder().status(200).body(fs::read(path).unwrap()).unwrap();
-                }
-            }
-            Response::builder().status(404).body(Vec::new()).unwrap()
-        })
+        .register_uri_scheme_protocol("localimages", move |_app, request| artwork::serve(request.uri()))
         .setup(|app| {
             app.manage(EpisodeDownloads::new(app.handle().clone()));
             app.manage(ProgressUpdater::new(app.handle().clone()));
+            let refresh_scheduler = RefreshScheduler::new(app.handle().clone());
+            app.manage(refresh_scheduler.clone());
+            // artwork is tidied up first, as converting old images could overwrite what a refresh just saved
+            tauri::async_runtime::spawn_blocking(move || {
+                if let Err(e) = artwork::tidy_up(&mut db_connect()) {
+                    tracing::info!("Could not tidy up artwork: {:?}", e);
+                }
+                refresh_scheduler.start();
+            });
             let player = Arc::new(Player::new(app.handle().clone()));
             let config_wrapper = app.state::<ConfigWrapper>();
             let config = config_wrapper.0.lock().unwrap();
@@ -111,17 +101,39 @@ pub async fn run() {
             commands::find_last_played,
             commands::list_listen_history,
             commands::list_latest_episodes,
+            commands::search_episodes,
             commands::get_config,
             commands::set_config,
             commands::register_user,
             commands::set_access_key,
             commands::register_device,
+            commands::create_pairing_code,
+            commands::redeem_pairing_code,
             commands::import_podcast,
+            commands::search_podcasts,
+            commands::import_opml,
+            commands::export_opml,
             commands::list_podcast_episodes,
+            commands::list_podcast_seasons,
+            commands::get_podcast_settings,
+            commands::update_podcast_settings,
+            commands::list_feed_warnings,
+            commands::list_feed_moves,
+            commands::list_feed_health,
+            commands::add_bookmark,
+            commands::list_bookmarks,
+            commands::delete_bookmark,
+            commands::play_bookmark,
+            commands::list_transcript_segments,
+            commands::current_transcript_segment,
+            commands::search_transcripts,
+            commands::play_transcript_segment,
             commands::download_episode,
             commands::get_episode,
             commands::get_episode_full,
+            commands::list_episode_chapters,
             commands::play_episode,
+            commands::play_episode_at,
             commands::player_action,
             commands::find_progress_for_episode,
             commands::set_volume,
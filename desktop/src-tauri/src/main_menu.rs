@@ -18,6 +18,8 @@ pub enum MainMenuOption {
     FindEpisode,
     NavigateLatestEpisodes,
     ManageDownloads,
+    ManageBookmarks,
+    SearchTranscripts,
     PlayPause,
     SkipForward,
     SkipBackward,
@@ -156,6 +158,20 @@ pub fn build_main_menu(app_handle: &AppHandle) -> AppResult<Menu<Wry>> {
                         true,
                         None::<&str>,
                     )?,
+                    &MenuItem::with_id(
+                        app_handle,
+                        MainMenuOption::ManageBookmarks,
+                        "Marcadores",
+                        true,
+                        None::<&str>,
+                    )?,
+                    &MenuItem::with_id(
+                        app_handle,
+                        MainMenuOption::SearchTranscripts,
+                        "Buscar nas Transcrições",
+                        true,
+                        None::<&str>,
+                    )?,
                 ],
             )?,
             &Submenu::with_items(
@@ -303,11 +319,19 @@ fn event_handler_inner(app_handle: &AppHandle, option: MainMenuOption) -> AppRes
         MainMenuOption::ManageFeeds => {
             app_handle.navigate(AppRoute::Podcasts)?;
         }
-        MainMenuOption::FindEpisode => {}
+        MainMenuOption::FindEpisode => {
+            app_handle.navigate(AppRoute::EpisodeSearch)?;
+        }
         MainMenuOption::NavigateLatestEpisodes => {}
         MainMenuOption::ManageDownloads => {
             app_handle.navigate(AppRoute::Downloads)?;
         }
+        MainMenuOption::ManageBookmarks => {
+            app_handle.navigate(AppRoute::Bookmarks)?;
+        }
+        MainMenuOption::SearchTranscripts => {
+            app_handle.navigate(AppRoute::Transcripts)?;
+        }
         MainMenuOption::PlayPause => {}
         MainMenuOption::SkipForward => {}
         MainMenuOption::SkipBackward => {}
@@ -1,6 +1,19 @@
+pub mod bookmark;
+pub mod chapter;
+pub mod description;
 pub mod episode;
 pub mod episode_downloads;
+pub mod episode_search;
+pub mod feed_auth;
+pub mod feed_discovery;
+pub mod feed_health;
+pub mod feed_move;
+pub mod feed_warning;
+pub mod opml;
 pub mod podcast;
+pub mod podcast_merge;
+pub mod podcast_settings;
+pub mod transcript;
 
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
@@ -14,6 +27,7 @@ pub struct Podcast {
     pub id: i32,
     pub guid: String,
     pub author: String,
+    /// Hash of the downloaded artwork, as returned by `artwork::download`.
     pub local_image_path: String,
     pub image_url: String,
     pub feed_url: String,
@@ -22,6 +36,35 @@ pub struct Podcast {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub deleted_at: Option<NaiveDateTime>,
+    #[serde(skip)]
+    pub http_etag: Option<String>,
+    #[serde(skip)]
+    pub http_last_modified: Option<String>,
+    #[serde(skip)]
+    pub content_hash: Option<String>,
+    pub auth_username: Option<String>,
+    #[serde(skip)]
+    pub auth_password: Option<String>,
+    /// `episodic` or `serial`, from `itunes:type`.
+    pub podcast_type: String,
+    /// Plain-text version of `description`.
+    pub description_text: String,
+}
+
+#[derive(Queryable, Selectable, Serialize, Associations, Identifiable, Clone, Debug)]
+#[diesel(table_name = crate::schema::podcast_settings)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[diesel(belongs_to(Podcast))]
+#[serde(rename_all = "camelCase")]
+pub struct PodcastSettings {
+    pub id: i32,
+    pub podcast_id: i32,
+    pub playback_speed: Option<f32>,
+    pub skip_intro_seconds: i32,
+    pub auto_download: String,
+    pub sort_order: String,
+    pub notifications: bool,
+    pub updated_at: NaiveDateTime,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -45,12 +88,23 @@ pub struct Episode {
     pub content_local_path: String,
     pub content_url: String,
     pub description: String,
+    /// Hash of the downloaded artwork, like `Podcast::local_image_path`.
     pub image_local_path: String,
     pub image_url: String,
     pub length: i32,
     pub link: String,
     pub episode_date: NaiveDateTime,
     pub title: String,
+    pub chapters_url: String,
+    pub transcript_url: String,
+    pub transcript_type: String,
+    pub season: Option<i32>,
+    pub episode_number: Option<i32>,
+    /// `full`, `trailer` or `bonus`, from `itunes:episodeType`.
+    pub episode_type: String,
+    pub explicit: Option<bool>,
+    /// `description` without markup or seek links, for search and notifications.
+    pub description_text: String,
 }
 
 #[derive(Queryable, Selectable, Serialize, Associations, Identifiable, Clone, Debug)]
@@ -65,3 +119,96 @@ pub struct EpisodeProgress {
     pub listened_seconds: i32,
     pub updated_at: NaiveDateTime,
 }
+
+#[derive(Queryable, Selectable, Serialize, Associations, Identifiable, Clone, Debug)]
+#[diesel(table_name = crate::schema::bookmarks)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+#[diesel(belongs_to(Episode))]
+pub struct Bookmark {
+    pub id: i32,
+    pub guid: String,
+    pub episode_id: i32,
+    pub start_seconds: i32,
+    pub end_seconds: Option<i32>,
+    pub note: String,
+    pub created_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Serialize, Associations, Identifiable, Clone, Debug)]
+#[diesel(table_name = crate::schema::feed_health)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+#[diesel(belongs_to(Podcast))]
+pub struct FeedHealth {
+    pub id: i32,
+    pub podcast_id: i32,
+    pub last_attempt_at: NaiveDateTime,
+    pub last_success_at: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<NaiveDateTime>,
+    pub consecutive_failures: i32,
+    pub http_status: Option<i32>,
+    /// Until when refreshes skip this feed after failing.
+    pub next_attempt_at: Option<NaiveDateTime>,
+}
+
+#[derive(Queryable, Selectable, Serialize, Associations, Identifiable, Clone, Debug)]
+#[diesel(table_name = crate::schema::feed_moves)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+#[diesel(belongs_to(Podcast))]
+pub struct FeedMove {
+    pub id: i32,
+    pub podcast_id: i32,
+    pub old_url: String,
+    pub new_url: String,
+    pub reason: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Serialize, Associations, Identifiable, Clone, Debug)]
+#[diesel(table_name = crate::schema::feed_warnings)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+#[diesel(belongs_to(Podcast))]
+pub struct FeedWarning {
+    pub id: i32,
+    pub podcast_id: i32,
+    pub item_index: i32,
+    pub guid: Option<String>,
+    pub title: Option<String>,
+    pub message: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Serialize, Associations, Identifiable, Clone, Debug)]
+#[diesel(table_name = crate::schema::chapters)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+#[diesel(belongs_to(Episode))]
+pub struct Chapter {
+    pub id: i32,
+    pub episode_id: i32,
+    pub start_seconds: f64,
+    pub end_seconds: Option<f64>,
+    pub title: String,
+    pub image_url: String,
+    pub url: String,
+}
+
+#[derive(Queryable, Selectable, Serialize, Associations, Identifiable, Clone, Debug)]
+#[diesel(table_name = crate::schema::transcript_segments)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+#[diesel(belongs_to(Episode))]
+pub struct TranscriptSegment {
+    pub id: i32,
+    pub episode_id: i32,
+    pub start_seconds: f64,
+    pub end_seconds: Option<f64>,
+    pub speaker: String,
+    pub body: String,
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::{insert_into, update};
+use dimppl_shared::sync::SyncBookmark;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::AppResult;
+use crate::models::{Bookmark, Episode, Podcast};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookmarkWithEpisode {
+    pub bookmark: Bookmark,
+    pub episode: Episode,
+    pub podcast: Podcast,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewBookmarkRequest {
+    pub episode_id: i32,
+    pub start_seconds: i32,
+    pub end_seconds: Option<i32>,
+    pub note: String,
+}
+
+pub fn create(conn: &mut SqliteConnection, request: NewBookmarkRequest) -> AppResult<Bookmark> {
+    if request.start_seconds < 0 || request.end_seconds.is_some_and(|end| end <= request.start_seconds) {
+        return Err(anyhow!("invalid bookmark range").into());
+    }
+    let now = Utc::now().naive_utc();
+    use crate::schema::bookmarks::dsl::*;
+    let bookmark = insert_into(bookmarks)
+        .values((
+            guid.eq(Uuid::new_v4().to_string()),
+            episode_id.eq(request.episode_id),
+            start_seconds.eq(request.start_seconds),
+            end_seconds.eq(request.end_seconds),
+            note.eq(request.note),
+            created_at.eq(now),
+            updated_at.eq(now),
+        ))
+        .returning(Bookmark::as_returning())
+        .get_result(conn)?;
+    Ok(bookmark)
+}
+
+pub fn find_one(bookmark_id: i32, conn: &mut SqliteConnection) -> AppResult<Bookmark> {
+    use crate::schema::bookmarks::dsl::*;
+    let result = bookmarks
+        .filter(id.eq(bookmark_id).and(deleted_at.is_null()))
+        .select(Bookmark::as_select())
+        .first(conn)?;
+    Ok(result)
+}
+
+/// Bookmarks that weren't deleted, newest first, optionally only the ones for one episode.
+pub fn list(conn: &mut SqliteConnection, the_episode_id: Option<i32>) -> AppResult<Vec<BookmarkWithEpisode>> {
+    use crate::schema::{bookmarks, episodes, podcasts};
+    let mut query = bookmarks::table
+        .inner_join(episodes::table.inner_join(podcasts::table))
+        .filter(bookmarks::deleted_at.is_null())
+        .order_by(bookmarks::created_at.desc())
+        .select((Bookmark::as_select(), Episode::as_select(), Podcast::as_select()))
+        .into_boxed();
+    if let Some(the_episode_id) = the_episode_id {
+        query = query.filter(bookmarks::episode_id.eq(the_episode_id));
+    }
+    let results = query
+        .load::<(Bookmark, Episode, Podcast)>(conn)?
+        .into_iter()
+        .map(|(bookmark, episode, podcast)| BookmarkWithEpisode {
+            bookmark,
+            episode,
+            podcast,
+        })
+        .collect();
+    Ok(results)
+}
+
+pub fn delete(conn: &mut SqliteConnection, bookmark_id: i32) -> AppResult<()> {
+    use crate::schema::bookmarks::dsl::*;
+    let now = Utc::now().naive_utc();
+    update(bookmarks)
+        .set((deleted_at.eq(now), updated_at.eq(now)))
+        .filter(id.eq(bookmark_id))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Every bookmark including deleted ones, keyed by podcast guid, for the backend sync request.
+pub fn list_for_sync(conn: &mut SqliteConnection) -> AppResult<HashMap<String, Vec<SyncBookmark>>> {
+    use crate::schema::{bookmarks, episodes, podcasts};
+    let rows = bookmarks::table
+        .inner_join(episodes::table.inner_join(podcasts::table))
+        .filter(podcasts::deleted_at.is_null())
+        .select((podcasts::guid, episodes::guid, Bookmark::as_select()))
+        .load::<(String, String, Bookmark)>(conn)?;
+    let mut map: HashMap<String, Vec<SyncBookmark>> = HashMap::new();
+    for (podcast_guid, episode_guid, bookmark) in rows {
+        map.entry(podcast_guid).or_default().push(SyncBookmark {
+            guid: bookmark.guid,
+            episode_guid,
+            start_seconds: bookmark.start_seconds,
+            end_seconds: bookmark.end_seconds,
+            note: bookmark.note,
+            created_at: bookmark.created_at,
+            deleted_at: bookmark.deleted_at,
+            updated_at: bookmark.updated_at,
+        });
+    }
+    Ok(map)
+}
+
+/// Stores bookmarks that came from the server, unless the local copy is newer. Bookmarks for
+/// episodes that aren't in the local feed are skipped.
+pub fn store_from_sync(
+    the_podcast_id: i32,
+    sync_bookmarks: &[SyncBookmark],
+    conn: &mut SqliteConnection,
+) -> AppResult<()> {
+    for sync_bookmark in sync_bookmarks {
+        let given_episode_id: Option<i32> = {
+            use crate::schema::episodes::dsl;
+            dsl::episodes
+                .filter(
+                    dsl::podcast_id
+                        .eq(the_podcast_id)
+                        .and(dsl::guid.eq(&sync_bookmark.episode_guid)),
+                )
+                .select(dsl::id)
+                .first(conn)
+                .optional()?
+        };
+        let Some(given_episode_id) = given_episode_id else {
+            continue;
+        };
+        use crate::schema::bookmarks::dsl::*;
+        let existing: Option<Bookmark> = bookmarks
+            .filter(guid.eq(&sync_bookmark.guid))
+            .select(Bookmark::as_select())
+            .first(conn)
+            .optional()?;
+        match existing {
+            Some(existing) if existing.updated_at >= sync_bookmark.updated_at => {}
+            Some(existing) => {
+                update(bookmarks)
+                    .set((
+                        start_seconds.eq(sync_bookmark.start_seconds),
+                        end_seconds.eq(sync_bookmark.end_seconds),
+                        note.eq(&sync_bookmark.note),
+                        deleted_at.eq(sync_bookmark.deleted_at),
+                        updated_at.eq(sync_bookmark.updated_at),
+                    ))
+                    .filter(id.eq(existing.id))
+                    .execute(conn)?;
+            }
+            None => {
+                insert_into(bookmarks)
+                    .values((
+                        guid.eq(&sync_bookmark.guid),
+                        episode_id.eq(given_episode_id),
+                        start_seconds.eq(sync_bookmark.start_seconds),
+                        end_seconds.eq(sync_bookmark.end_seconds),
+                        note.eq(&sync_bookmark.note),
+                        created_at.eq(sync_bookmark.created_at),
+                        deleted_at.eq(sync_bookmark.deleted_at),
+                        updated_at.eq(sync_bookmark.updated_at),
+                    ))
+                    .execute(conn)?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use std::path::Path;
+
+use diesel::prelude::*;
+use diesel::{delete, insert_into};
+use id3::TagLike;
+use serde::Deserialize;
+
+use crate::errors::AppResult;
+use crate::models::{Chapter, Episode};
+
+/// A Podcasting 2.0 JSON chapters document, as linked by `podcast:chapters`.
+#[derive(Deserialize)]
+struct ChaptersDocument {
+    #[serde(default)]
+    chapters: Vec<ChapterEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChapterEntry {
+    start_time: f64,
+    end_time: Option<f64>,
+    title: Option<String>,
+    img: Option<String>,
+    url: Option<String>,
+    /// Chapters with `toc: false` only change artwork or links and aren't meant for navigation.
+    toc: Option<bool>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::chapters)]
+struct NewChapter {
+    episode_id: i32,
+    start_seconds: f64,
+    end_seconds: Option<f64>,
+    title: String,
+    image_url: String,
+    url: String,
+}
+
+pub fn list_for_episode(the_episode_id: i32, conn: &mut SqliteConnection) -> AppResult<Vec<Chapter>> {
+    use crate::schema::chapters::dsl::*;
+    let results = chapters
+        .filter(episode_id.eq(the_episode_id))
+        .order_by(start_seconds.asc())
+        .select(Chapter::as_select())
+        .load(conn)?;
+    Ok(results)
+}
+
+pub fn delete_for_episode(the_episode_id: i32, conn: &mut SqliteConnection) -> AppResult<()> {
+    use crate::schema::chapters::dsl::*;
+    delete(chapters).filter(episode_id.eq(the_episode_id)).execute(conn)?;
+    Ok(())
+}
+
+/// Chapters for the episode, downloading them from the feed's chapters URL the first time.
+pub async fn fetch_for_episode(episode: &Episode, conn: &mut SqliteConnection) -> AppResult<Vec<Chapter>> {
+    let stored = list_for_episode(episode.id, conn)?;
+    if !stored.is_empty() || episode.chapters_url.is_empty() {
+        return Ok(stored);
+    }
+    let document: ChaptersDocument = reqwest::get(&episode.chapters_url)
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let new_chapters = document
+        .chapters
+        .into_iter()
+        .filter(|entry| entry.toc.unwrap_or(true) && entry.start_time >= 0.0)
+        .map(|entry| NewChapter {
+            episode_id: episode.id,
+            start_seconds: entry.start_time,
+            end_seconds: entry.end_time,
+            title: entry.title.unwrap_or_default(),
+            image_url: entry.img.unwrap_or_default(),
+            url: entry.url.unwrap_or_default(),
+        })
+        .collect();
+    insert_all(episode.id, new_chapters, conn)
+}
+
+/// Reads the chapters embedded in the downloaded file and stores them, unless the episode already
+/// has chapters or the feed links a chapters document, which takes precedence.
+pub fn store_embedded(episode: &Episode, conn: &mut SqliteConnection) -> AppResult<Vec<Chapter>> {
+    let stored = list_for_episode(episode.id, conn)?;
+    if !stored.is_empty() || !episode.chapters_url.is_empty() || episode.content_local_path.is_empty() {
+        return Ok(stored);
+    }
+    let path = Path::new(&episode.content_local_path);
+    let mut new_chapters = read_id3_chapters(path, episode.id);
+    if new_chapters.is_empty() {
+        new_chapters = read_mp4_chapters(path, episode.id);
+    }
+    insert_all(episode.id, new_chapters, conn)
+}
+
+/// ID3v2 `CHAP` frames, as written to MP3 files. Titles and links come from the frames nested in
+/// each chapter.
+fn read_id3_chapters(path: &Path, the_episode_id: i32) -> Vec<NewChapter> {
+    let Ok(tag) = id3::Tag::read_from_path(path) else {
+        return Vec::new();
+    };
+    tag.chapters()
+        .map(|chapter| {
+            let frame_content = |frame_id: &str| {
+                chapter
+                    .frames
+                    .iter()
+                    .find(|frame| frame.id() == frame_id)
+                    .map(|frame| frame.content())
+            };
+            let start = chapter.start_time as f64 / 1000.0;
+            let end = chapter.end_time as f64 / 1000.0;
+            NewChapter {
+                episode_id: the_episode_id,
+                start_seconds: start,
+                end_seconds: Some(end).filter(|end| *end > start),
+                title: frame_content("TIT2")
+                    .and_then(|content| content.text())
+                    .unwrap_or_default()
+                    .to_string(),
+                image_url: "".into(),
+                url: frame_content("WXXX")
+                    .and_then(|content| content.extended_link())
+                    .map(|link| link.link.clone())
+                    .unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+/// Chapters of an MP4/M4A file, from either the Nero chapter list or a QuickTime chapter track.
+/// Those only carry start times, so each chapter ends where the next one starts.
+fn read_mp4_chapters(path: &Path, the_episode_id: i32) -> Vec<NewChapter> {
+    let Ok(tag) = mp4ameta::Tag::read_from_path(path) else {
+        return Vec::new();
+    };
+    let chapters = if tag.chapter_list().is_empty() {
+        tag.chapter_track()
+    } else {
+        tag.chapter_list()
+    };
+    let starts: Vec<f64> = chapters.iter().map(|chapter| chapter.start.as_secs_f64()).collect();
+    chapters
+        .iter()
+        .enumerate()
+        .map(|(index, chapter)| NewChapter {
+            episode_id: the_episode_id,
+            start_seconds: starts[index],
+            end_seconds: starts.get(index + 1).copied(),
+            title: chapter.title.clone(),
+            image_url: "".into(),
+            url: "".into(),
+        })
+        .collect()
+}
+
+/// Stores the chapters unless the episode got some in the meantime, as when they're fetched for it
+/// twice at once, and returns what it ends up with.
+fn insert_all(
+    the_episode_id: i32,
+    new_chapters: Vec<NewChapter>,
+    conn: &mut SqliteConnection,
+) -> AppResult<Vec<Chapter>> {
+    conn.immediate_transaction(|conn| {
+        let stored = list_for_episode(the_episode_id, conn)?;
+        if !stored.is_empty() || new_chapters.is_empty() {
+            return Ok(stored);
+        }
+        {
+            use crate::schema::chapters::dsl::*;
+            insert_into(chapters).values(new_chapters).execute(conn)?;
+        }
+        list_for_episode(the_episode_id, conn)
+    })
+}
+
+/// The chapter playing at `seconds`, given chapters sorted by start time.
+pub fn chapter_at(chapters: &[Chapter], seconds: f64) -> Option<&Chapter> {
+    chapters
+        .iter()
+        .rev()
+        .find(|chapter| chapter.start_seconds <= seconds && chapter.end_seconds.is_none_or(|end| seconds < end))
+}
@@ -0,0 +1,329 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// Tags kept in descriptions. Others are dropped but their text is kept, except for scripts and
+/// styles, which go entirely. Images aren't allowed, as most of them are tracking pixels.
+const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "b",
+    "blockquote",
+    "br",
+    "code",
+    "em",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "strong",
+    "u",
+    "ul",
+];
+
+/// Tags that end a line in the plain-text version.
+const BLOCK_TAGS: &[&str] = &[
+    "blockquote",
+    "br",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "ul",
+];
+
+/// A feed description made safe to render, and its text.
+pub struct Description {
+    pub html: String,
+    pub text: String,
+}
+
+impl Description {
+    /// Cleans a description straight from a feed.
+    pub fn from_feed(raw: &str) -> Self {
+        let html = sanitize(raw);
+        Self {
+            text: plain_text(&html),
+            html,
+        }
+    }
+
+    /// Like `from_feed`, also turning timestamps in the show notes into seek links that the frontend
+    /// plays the episode from: `<a class="seek-point" data-seconds="754" href="#">12:34</a>`.
+    pub fn from_show_notes(raw: &str) -> Self {
+        let description = Self::from_feed(raw);
+        Self {
+            html: link_timestamps(&description.html),
+            text: description.text,
+        }
+    }
+}
+
+/// Restricts the HTML to `ALLOWED_TAGS`, with links only to web and mail addresses. Descriptions
+/// without any markup are plain text, so their line breaks are kept as `<br>`s.
+pub fn sanitize(raw: &str) -> String {
+    let raw = raw.trim();
+    let html = if raw.contains('<') {
+        raw.to_string()
+    } else {
+        raw.replace("\r\n", "\n").replace('\n', "<br>")
+    };
+    ammonia::Builder::default()
+        .tags(ALLOWED_TAGS.iter().copied().collect())
+        .tag_attributes(HashMap::from([("a", HashSet::from(["href"]))]))
+        .generic_attributes(HashSet::new())
+        .url_schemes(HashSet::from(["http", "https", "mailto"]))
+        .link_rel(Some("noopener noreferrer nofollow"))
+        .clean(&html)
+        .to_string()
+}
+
+/// The text of sanitized HTML, with a line break after each block.
+pub fn plain_text(html: &str) -> String {
+    let mut text = String::new();
+    for part in split_tags(html) {
+        match part {
+            Part::Tag(tag) => {
+                if BLOCK_TAGS.contains(&tag_name(tag).as_str()) {
+                    text.push('\n');
+                }
+            }
+            Part::Text(content) => text.push_str(&decode_entities(content)),
+        }
+    }
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    let mut result = String::new();
+    let mut blank_lines = 0;
+    for line in lines {
+        if line.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if !result.is_empty() {
+            result.push_str(if blank_lines > 1 { "\n\n" } else { "\n" });
+        }
+        result.push_str(line);
+        blank_lines = 0;
+    }
+    result
+}
+
+/// Wraps timestamps found in text, outside of existing links, in seek links.
+fn link_timestamps(html: &str) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut link_depth = 0;
+    let mut at_line_start = true;
+    for part in split_tags(html) {
+        match part {
+            Part::Tag(tag) => {
+                let name = tag_name(tag);
+                match name.as_str() {
+                    "a" if tag.starts_with("</") => link_depth -= 1,
+                    "a" => link_depth += 1,
+                    _ => {}
+                }
+                at_line_start |= BLOCK_TAGS.contains(&name.as_str());
+                result.push_str(tag);
+            }
+            Part::Text(content) if link_depth > 0 => {
+                at_line_start = false;
+                result.push_str(content);
+            }
+            Part::Text(content) => {
+                let starts_line = at_line_start;
+                at_line_start &= is_line_lead(content);
+                let mut last = 0;
+                for (range, seconds) in find_timestamps(content, starts_line) {
+                    result.push_str(&content[last..range.start]);
+                    result.push_str(&format!(
+                        "<a class=\"seek-point\" data-seconds=\"{seconds}\" href=\"#\">{}</a>",
+                        &content[range.clone()]
+                    ));
+                    last = range.end;
+                }
+                result.push_str(&content[last..]);
+            }
+        }
+    }
+    result
+}
+
+/// Timestamps like `12:34` or `1:02:03` standing on their own in `text`, with their offset in seconds.
+/// As a bare `12:34` may as well be a time of day or a score, those only count at the start of a
+/// line, as in a list of chapters, or in brackets. `starts_line` tells whether `text` does.
+fn find_timestamps(text: &str, starts_line: bool) -> Vec<(Range<usize>, i64)> {
+    let bytes = text.as_bytes();
+    let is_part = |byte: u8| byte.is_ascii_digit() || byte == b':';
+    let mut found = Vec::new();
+    let mut start = 0;
+    while start < bytes.len() {
+        if !bytes[start].is_ascii_digit()
+            || (start > 0 && (is_part(bytes[start - 1]) || bytes[start - 1].is_ascii_alphabetic()))
+        {
+            start += 1;
+            continue;
+        }
+        let mut end = start;
+        while end < bytes.len() && is_part(bytes[end]) {
+            end += 1;
+        }
+        let candidate = &text[start..end];
+        let followed_by_word = end < bytes.len() && bytes[end].is_ascii_alphabetic();
+        let bracketed = start > 0
+            && matches!(bytes[start - 1], b'(' | b'[')
+            && end < bytes.len()
+            && matches!(bytes[end], b')' | b']');
+        let line_start = match text[..start].rfind('\n') {
+            Some(newline) => Some(newline + 1),
+            None => starts_line.then_some(0),
+        };
+        let leads_line = line_start.is_some_and(|line_start| is_line_lead(&text[line_start..start]));
+        let with_hours = candidate.matches(':').count() == 2;
+        if let Some(seconds) = parse_timestamp(candidate).filter(|_| !followed_by_word) {
+            if with_hours || bracketed || leads_line {
+                found.push((start..end, seconds));
+            }
+        }
+        start = end;
+    }
+    found
+}
+
+/// Whether text can come before a timestamp at the start of a line: spaces and list bullets.
+fn is_line_lead(text: &str) -> bool {
+    text.chars()
+        .all(|c| c.is_whitespace() || matches!(c, '-' | '*' | '•' | '–' | '—' | '#'))
+}
+
+fn parse_timestamp(candidate: &str) -> Option<i64> {
+    let parts: Vec<&str> = candidate.split(':').collect();
+    if !(2..=3).contains(&parts.len()) || parts[0].is_empty() || parts[0].len() > 2 {
+        return None;
+    }
+    if parts[1..].iter().any(|part| part.len() != 2) {
+        return None;
+    }
+    let numbers: Vec<i64> = parts.iter().map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    if numbers[1..].iter().any(|number| *number >= 60) {
+        return None;
+    }
+    Some(numbers.iter().fold(0, |total, number| total * 60 + number))
+}
+
+enum Part<'a> {
+    Tag(&'a str),
+    Text(&'a str),
+}
+
+/// Splits serialized HTML into tags and the text between them.
+fn split_tags(html: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = html;
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            parts.push(Part::Text(rest));
+            break;
+        };
+        if start > 0 {
+            parts.push(Part::Text(&rest[..start]));
+        }
+        let end = rest[start..].find('>').map_or(rest.len(), |end| start + end + 1);
+        parts.push(Part::Tag(&rest[start..end]));
+        rest = &rest[end..];
+    }
+    parts
+}
+
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches('<')
+        .trim_start_matches('/')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seek_points(html: &str) -> Vec<i64> {
+        html.split("data-seconds=\"")
+            .skip(1)
+            .map(|rest| rest[..rest.find('"').unwrap()].parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_sanitize_removes_scripts_and_images() {
+        let html = sanitize(
+            "<p>Show notes</p><script>alert('hi')</script><style>p { color: red }</style>\
+             <img src=\"https://tracker.example.com/pixel.gif\"><p onclick=\"steal()\">More</p>",
+        );
+        assert_eq!("<p>Show notes</p><p>More</p>", html);
+    }
+
+    #[test]
+    fn test_sanitize_drops_javascript_links() {
+        let html = sanitize("<a href=\"javascript:alert(1)\">click</a> <a href=\"https://example.com\">site</a>");
+        assert!(!html.contains("javascript"));
+        assert!(html.contains("<a rel=\"noopener noreferrer nofollow\">click</a>"));
+        assert!(html.contains("href=\"https://example.com\""));
+    }
+
+    #[test]
+    fn test_plain_text_keeps_line_breaks() {
+        let description = Description::from_feed("First line\r\nSecond line\n\nAfter a blank line");
+        assert_eq!("First line<br>Second line<br><br>After a blank line", description.html);
+        assert_eq!("First line\nSecond line\nAfter a blank line", description.text);
+    }
+
+    #[test]
+    fn test_timestamps_become_seek_points() {
+        let description =
+            Description::from_show_notes("<p>00:00 Intro</p><ul><li>- 12:34 News</li><li>1:02:03 Outro</li></ul>");
+        assert_eq!(vec![0, 754, 3723], seek_points(&description.html));
+
+        let plain = Description::from_show_notes("Topics:\n05:00 First\n10:30 Second [15:00] (and 20:00 elsewhere)");
+        assert_eq!(vec![300, 630, 900], seek_points(&plain.html));
+    }
+
+    #[test]
+    fn test_timestamps_inside_links_are_left_alone() {
+        let description = Description::from_show_notes("<p><a href=\"https://example.com/t=754\">12:34</a> News</p>");
+        assert!(seek_points(&description.html).is_empty());
+        assert!(description.html.contains(">12:34</a>"));
+    }
+
+    #[test]
+    fn test_times_of_day_and_scores_are_not_timestamps() {
+        let description = Description::from_show_notes(
+            "<p>We go live at 20:00 on Friday.</p><p>The home team won 3:21, see 12:34pm and v1:23.</p>",
+        );
+        assert!(seek_points(&description.html).is_empty());
+        assert!(find_timestamps("Doors open 19:30", true).is_empty());
+        assert!(find_timestamps("99:99", true).is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 use std::cmp::{min, Reverse};
+use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::io::Write;
@@ -6,24 +7,29 @@ use std::path::Path;
 use std::time::{Duration, Instant};
 
 use anyhow::Context;
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use diesel::associations::HasTable;
 use diesel::insert_into;
 use diesel::prelude::*;
+use dimppl_shared::sync::EpisodeSortOrder;
 use futures_util::StreamExt;
 use lofty::prelude::AudioFile;
 use mime2ext::mime2ext;
 use reqwest::header::CONTENT_TYPE;
 use reqwest::Response;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::probe::Hint;
+use symphonia::core::units::TimeBase;
 use url::Url;
 
 use crate::directories::podcast_downloads_dir;
 use crate::errors::AppResult;
 use crate::extensions::{ResponseExt, StrOptionExt};
 use crate::models::episode_downloads::{EpisodeDownloadProgress, EpisodeDownloads};
+use crate::models::feed_auth::FeedAuth;
 use crate::models::podcast::NewProgress;
-use crate::models::{Episode, EpisodeProgress, Podcast};
+use crate::models::{chapter, podcast, transcript, Episode, EpisodeProgress, Podcast};
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -55,12 +61,47 @@ impl EpisodeWithProgress {
     }
 }
 
-pub fn list_for_podcast(given_podcast_id: i32, conn: &mut SqliteConnection) -> AppResult<Vec<EpisodeWithProgress>> {
+/// Filters and ordering for a podcast's episode list.
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EpisodeListOptions {
+    /// Only the episodes of this season.
+    #[serde(default)]
+    pub season: Option<i32>,
+    #[serde(default)]
+    pub hide_trailers: bool,
+    /// Taken from the podcast's settings rather than the request.
+    #[serde(skip)]
+    pub sort_order: EpisodeSortOrder,
+}
+
+/// Newest first is by date. Oldest first follows the show's own order, season and episode number,
+/// which is what serial shows need: they often publish a whole season on the same day.
+pub fn list_for_podcast(
+    given_podcast_id: i32,
+    options: &EpisodeListOptions,
+    conn: &mut SqliteConnection,
+) -> AppResult<Vec<EpisodeWithProgress>> {
     fix_missing_progress_entries(given_podcast_id, conn)?;
-    let episodes_with_progress = EpisodeProgress::table()
+    use crate::schema::episodes::dsl::*;
+    let mut query = EpisodeProgress::table()
         .inner_join(Episode::table())
-        .filter(crate::schema::episodes::dsl::podcast_id.eq(given_podcast_id))
-        .order_by(crate::schema::episodes::dsl::episode_date.desc())
+        .filter(podcast_id.eq(given_podcast_id))
+        .into_boxed();
+    if let Some(given_season) = options.season {
+        query = query.filter(season.eq(given_season));
+    }
+    if options.hide_trailers {
+        query = query.filter(episode_type.ne("trailer"));
+    }
+    query = match options.sort_order {
+        EpisodeSortOrder::NewestFirst => query.order_by(episode_date.desc()),
+        EpisodeSortOrder::OldestFirst => query
+            .order_by(season.asc())
+            .then_order_by(episode_number.asc())
+            .then_order_by(episode_date.asc()),
+    };
+    let episodes_with_progress = query
         .select((EpisodeProgress::as_select(), Episode::as_select()))
         .load::<(EpisodeProgress, Episode)>(conn)?
         .iter()
@@ -69,6 +110,51 @@ pub fn list_for_podcast(given_podcast_id: i32, conn: &mut SqliteConnection) -> A
     Ok(episodes_with_progress)
 }
 
+/// Seasons the podcast's episodes belong to, in order.
+pub fn list_seasons(given_podcast_id: i32, conn: &mut SqliteConnection) -> AppResult<Vec<i32>> {
+    use crate::schema::episodes::dsl::*;
+    let results = episodes
+        .filter(podcast_id.eq(given_podcast_id))
+        .select(season)
+        .distinct()
+        .order_by(season.asc())
+        .load::<Option<i32>>(conn)?
+        .into_iter()
+        .flatten()
+        .collect();
+    Ok(results)
+}
+
+#[derive(QueryableByName)]
+struct RecentDate {
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    podcast_id: i32,
+    #[diesel(sql_type = diesel::sql_types::Timestamp)]
+    episode_date: NaiveDateTime,
+}
+
+/// Publication dates of each podcast's latest `per_podcast` episodes, newest first.
+pub fn list_recent_dates(
+    per_podcast: usize,
+    conn: &mut SqliteConnection,
+) -> AppResult<HashMap<i32, Vec<NaiveDateTime>>> {
+    let rows = diesel::sql_query(
+        "SELECT podcast_id, episode_date FROM (\
+         SELECT podcast_id, episode_date, \
+         ROW_NUMBER() OVER (PARTITION BY podcast_id ORDER BY episode_date DESC) AS position \
+         FROM episodes) \
+         WHERE position <= ? \
+         ORDER BY podcast_id, episode_date DESC",
+    )
+    .bind::<diesel::sql_types::Integer, _>(per_podcast as i32)
+    .load::<RecentDate>(conn)?;
+    let mut results: HashMap<i32, Vec<NaiveDateTime>> = HashMap::new();
+    for row in rows {
+        results.entry(row.podcast_id).or_default().push(row.episode_date);
+    }
+    Ok(results)
+}
+
 pub fn find_one(episode_id: i32, conn: &mut SqliteConnection) -> AppResult<Episode> {
     use crate::schema::episodes::dsl::*;
     let results = episodes.filter(id.eq(episode_id)).first(conn)?;
@@ -253,11 +339,12 @@ pub async fn start_download(
         .set_progress(&episode, EpisodeDownloadProgress::default())
         .await;
 
-    let response_result = reqwest::Client::builder()
+    let auth = FeedAuth::for_podcast(&podcast::find_one(episode.podcast_id, conn)?);
+    let request = reqwest::Client::builder()
         .timeout(Duration::from_secs(30))
         .build()?
-        .get(&episode.content_url)
-        .send().await;
+        .get(&episode.content_url);
+    let response_result = auth.apply(request, &episode.content_url).send().await;
     if let Err(response_result) = response_result {
         tracing::warn!("Failed to download episode: {:?}", response_result);
         progress_indicator.mark_done(episode_id).await;
@@ -302,23 +389,73 @@ pub async fn start_download(
         }
     }
     tracing::debug!("total chunks: {chunk_count}");
-    let tagged_file = lofty::read_from_path(path.clone())?;
-    let file_duration = tagged_file.properties().duration().as_secs();
+    let content_local_path = path.to_str().context("to_str")?.to_string();
+    diesel::update(Episode::table())
+        .filter(crate::schema::episodes::dsl::id.eq(episode_id))
+        .set(crate::schema::episodes::dsl::content_local_path.eq(&content_local_path))
+        .execute(conn)?;
+    let episode = Episode {
+        content_local_path,
+        ..episode
+    };
+    reconcile_duration(&episode, conn)?;
+    if let Err(e) = chapter::store_embedded(&episode, conn) {
+        tracing::info!("Could not read embedded chapters of episode {}: {:?}", episode.id, e);
+    }
+    if let Err(e) = transcript::fetch_for_episode(&episode, conn).await {
+        tracing::info!("Could not fetch transcript of episode {}: {:?}", episode.id, e);
+    }
+    progress_indicator.mark_done(episode_id).await;
+
+    Ok(())
+}
+
+/// Measures the downloaded file and stores its duration if it differs from the one the feed gave,
+/// which is often missing or rounded. Returns the best known duration in seconds.
+pub fn reconcile_duration(episode: &Episode, conn: &mut SqliteConnection) -> AppResult<i32> {
+    let measured = match measure_duration(Path::new(&episode.content_local_path)) {
+        Ok(seconds) => seconds as i32,
+        Err(e) => {
+            tracing::info!("Could not measure duration of episode {}: {:?}", episode.id, e);
+            return Ok(episode.length);
+        }
+    };
     tracing::debug!(
         "file duration: {} previously known duration: {}",
-        file_duration,
+        measured,
         episode.length
     );
+    if measured <= 0 || measured == episode.length {
+        return Ok(episode.length);
+    }
     diesel::update(Episode::table())
-        .filter(crate::schema::episodes::dsl::id.eq(episode_id))
-        .set((
-            crate::schema::episodes::dsl::content_local_path.eq(path.to_str().context("to_str")?.to_string()),
-            crate::schema::episodes::dsl::length.eq(file_duration as i32),
-        ))
+        .filter(crate::schema::episodes::dsl::id.eq(episode.id))
+        .set(crate::schema::episodes::dsl::length.eq(measured))
         .execute(conn)?;
-    progress_indicator.mark_done(episode_id).await;
+    Ok(measured)
+}
 
-    Ok(())
+/// Duration of an audio file in seconds, from its tags and headers through lofty or, when lofty
+/// can't read the file, from the frame count symphonia finds in the container.
+fn measure_duration(path: &Path) -> AppResult<u64> {
+    match lofty::read_from_path(path) {
+        Ok(tagged_file) => return Ok(tagged_file.properties().duration().as_secs()),
+        Err(e) => tracing::debug!("lofty could not read {:?}, trying symphonia: {:?}", path, e),
+    }
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+        hint.with_extension(extension);
+    }
+    let source = MediaSourceStream::new(Box::new(File::open(path)?), Default::default());
+    let probed = symphonia::default::get_probe().format(&hint, source, &Default::default(), &Default::default())?;
+    let track = probed.format.default_track().context("no default track")?;
+    let frames = track.codec_params.n_frames.context("unknown frame count")?;
+    let time_base = track
+        .codec_params
+        .time_base
+        .or(track.codec_params.sample_rate.map(|rate| TimeBase::new(1, rate)))
+        .context("unknown time base")?;
+    Ok(time_base.calc_time(frames).seconds)
 }
 
 fn extract_episode_filename_extension(episode: &Episode, response: &Response) -> String {
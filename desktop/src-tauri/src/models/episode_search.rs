@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use diesel::sql_types::{Integer, Text, Timestamp};
+use diesel::sqlite::Sqlite;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::AppResult;
+use crate::models::episode::EpisodeWithPodcast;
+use crate::models::{Episode, EpisodeProgress, Podcast};
+
+/// Private-use characters FTS5 wraps matches in, so they survive HTML escaping and become `<mark>`s.
+/// The query passes them as `char(57344)` and `char(57345)`.
+const MARK_START: char = '\u{E000}';
+const MARK_END: char = '\u{E001}';
+/// Column weights for `bm25`: title, description, podcast name.
+const RANKING: &str = "bm25(episode_search, 10.0, 1.0, 4.0)";
+const MAX_RESULTS: i32 = 200;
+
+/// Filters for an episode search. Dates are inclusive days.
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EpisodeSearchOptions {
+    pub podcast_id: Option<i32>,
+    /// Only episodes not marked as complete.
+    pub unplayed: bool,
+    pub downloaded: bool,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpisodeSearchHit {
+    #[serde(flatten)]
+    pub result: EpisodeWithPodcast,
+    /// The title as HTML, with the matched words in `<mark>`s.
+    pub title_html: String,
+    /// The part of the description around the matches, marked the same way.
+    pub snippet_html: String,
+}
+
+#[derive(QueryableByName)]
+struct SearchMatch {
+    #[diesel(sql_type = Integer)]
+    episode_id: i32,
+    #[diesel(sql_type = Text)]
+    title: String,
+    #[diesel(sql_type = Text)]
+    snippet: String,
+}
+
+/// Episodes whose title, description or podcast name contain every word of `query`, best matches
+/// first. The last word also matches longer words starting with it.
+pub fn search(
+    query: &str,
+    options: &EpisodeSearchOptions,
+    conn: &mut SqliteConnection,
+) -> AppResult<Vec<EpisodeSearchHit>> {
+    let Some(expression) = match_expression(query) else {
+        return Ok(Vec::new());
+    };
+    let mut sql_query = diesel::sql_query(
+        "SELECT episode_search.rowid AS episode_id, \
+         highlight(episode_search, 0, char(57344), char(57345)) AS title, \
+         snippet(episode_search, 1, char(57344), char(57345), '…', 24) AS snippet \
+         FROM episode_search \
+         INNER JOIN episodes ON episodes.id = episode_search.rowid \
+         INNER JOIN podcasts ON podcasts.id = episodes.podcast_id \
+         INNER JOIN episode_progresses ON episode_progresses.episode_id = episodes.id \
+         WHERE episode_search MATCH ? AND podcasts.deleted_at IS NULL",
+    )
+    .into_boxed::<Sqlite>()
+    .bind::<Text, _>(expression);
+    if let Some(podcast_id) = options.podcast_id {
+        sql_query = sql_query
+            .sql(" AND episodes.podcast_id = ?")
+            .bind::<Integer, _>(podcast_id);
+    }
+    if options.unplayed {
+        sql_query = sql_query.sql(" AND NOT episode_progresses.completed");
+    }
+    if options.downloaded {
+        sql_query = sql_query.sql(" AND episodes.content_local_path <> ''");
+    }
+    if let Some(from) = options.from {
+        sql_query = sql_query
+            .sql(" AND episodes.episode_date >= ?")
+            .bind::<Timestamp, _>(from.and_hms_opt(0, 0, 0).unwrap());
+    }
+    if let Some(to) = options.to.and_then(|to| to.succ_opt()) {
+        sql_query = sql_query
+            .sql(" AND episodes.episode_date < ?")
+            .bind::<Timestamp, _>(to.and_hms_opt(0, 0, 0).unwrap());
+    }
+    let matches = sql_query
+        .sql(format!(" ORDER BY {RANKING}, episodes.episode_date DESC LIMIT ?"))
+        .bind::<Integer, _>(MAX_RESULTS)
+        .load::<SearchMatch>(conn)?;
+
+    let mut results = load_episodes(matches.iter().map(|found| found.episode_id).collect(), conn)?;
+    let hits = matches
+        .into_iter()
+        .filter_map(|found| {
+            Some(EpisodeSearchHit {
+                result: results.remove(&found.episode_id)?,
+                title_html: marked_html(&found.title),
+                snippet_html: marked_html(&found.snippet),
+            })
+        })
+        .collect();
+    Ok(hits)
+}
+
+fn load_episodes(ids: Vec<i32>, conn: &mut SqliteConnection) -> AppResult<HashMap<i32, EpisodeWithPodcast>> {
+    use crate::schema::episode_progresses::dsl::episode_progresses;
+    use crate::schema::episodes::dsl::*;
+    use crate::schema::podcasts::dsl::podcasts;
+    let results = episodes
+        .inner_join(episode_progresses)
+        .inner_join(podcasts)
+        .filter(id.eq_any(ids))
+        .select((EpisodeProgress::as_select(), Episode::as_select(), Podcast::as_select()))
+        .load::<(EpisodeProgress, Episode, Podcast)>(conn)?
+        .into_iter()
+        .map(|(progress, episode, podcast)| {
+            (
+                episode.id,
+                EpisodeWithPodcast {
+                    episode,
+                    progress,
+                    podcast,
+                },
+            )
+        })
+        .collect();
+    Ok(results)
+}
+
+/// The typed words as an FTS5 query. Each is quoted so that punctuation and words like `OR` are taken
+/// literally; the last one is a prefix, as it may still be being typed.
+fn match_expression(query: &str) -> Option<String> {
+    let words: Vec<String> = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", words.join(" ")))
+}
+
+/// Escapes text from the index and turns the match markers into `<mark>` tags.
+fn marked_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace(MARK_START, "<mark>")
+        .replace(MARK_END, "</mark>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_connection;
+    use diesel::connection::SimpleConnection;
+
+    #[test]
+    fn test_match_expression() {
+        assert_eq!(Some("\"rust\" \"async\"*".into()), match_expression("  rust\tasync "));
+        assert_eq!(
+            Some("\"cats\" \"OR\" \"dogs\"*".into()),
+            match_expression("cats OR dogs")
+        );
+        assert_eq!(Some("\"say\" \"\"\"hi\"\"\"*".into()), match_expression("say \"hi\""));
+        assert_eq!(Some("\"c++\"*".into()), match_expression("c++"));
+        assert_eq!(None, match_expression(""));
+        assert_eq!(None, match_expression("   "));
+    }
+
+    #[test]
+    fn test_marked_html() {
+        assert_eq!(
+            "&lt;b&gt;R&amp;D&lt;/b&gt; <mark>a&lt;b</mark>",
+            marked_html("<b>R&D</b> \u{E000}a<b\u{E001}")
+        );
+        assert_eq!("no matches", marked_html("no matches"));
+    }
+
+    fn search_ids(query: &str, options: &EpisodeSearchOptions, conn: &mut SqliteConnection) -> Vec<i32> {
+        let mut ids: Vec<i32> = search(query, options, conn)
+            .unwrap()
+            .into_iter()
+            .map(|hit| hit.result.episode.id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_search() {
+        let conn = &mut test_connection();
+        conn.batch_execute(
+            "INSERT INTO podcasts (id, guid, author, local_image_path, image_url, feed_url, name, description, \
+             created_at, updated_at) VALUES \
+             (1, 'a', '', '', '', 'https://a.example/feed', 'Rustacean Station', '', '2024-01-01', '2024-01-01'), \
+             (2, 'b', '', '', '', 'https://b.example/feed', 'Cooking Hour', '', '2024-01-01', '2024-01-01');
+             INSERT INTO episodes (id, guid, podcast_id, content_local_path, content_url, description, \
+             image_local_path, image_url, length, link, episode_date, title, description_text) VALUES \
+             (1, '1', 1, '', '', '', '', '', 0, '', '2024-03-01 10:00:00', 'Async traits', 'Futures and pinning'), \
+             (2, '2', 1, '', '', '', '', '', 0, '', '2024-05-01 10:00:00', 'Error handling', 'About anyhow'), \
+             (3, '3', 2, '', '', '', '', '', 0, '', '2024-05-02 10:00:00', 'Rustic bread', 'Async baking');
+             INSERT INTO episode_progresses (episode_id, completed, listened_seconds, updated_at) VALUES \
+             (1, TRUE, 0, '2024-01-01'), (2, FALSE, 0, '2024-01-01'), (3, FALSE, 0, '2024-01-01');",
+        )
+        .unwrap();
+        let all = EpisodeSearchOptions::default();
+
+        assert_eq!(vec![1, 3], search_ids("async", &all, conn));
+        assert_eq!(vec![1, 2, 3], search_ids("rust", &all, conn));
+        assert_eq!(vec![2], search_ids("anyhow", &all, conn));
+        let hits = search("pinning", &all, conn).unwrap();
+        assert_eq!("Async traits", hits[0].title_html);
+        assert_eq!("Futures and <mark>pinning</mark>", hits[0].snippet_html);
+
+        let podcast = EpisodeSearchOptions {
+            podcast_id: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(vec![3], search_ids("async", &podcast, conn));
+        let unplayed = EpisodeSearchOptions {
+            unplayed: true,
+            ..Default::default()
+        };
+        assert_eq!(vec![3], search_ids("async", &unplayed, conn));
+        let dates = EpisodeSearchOptions {
+            from: NaiveDate::from_ymd_opt(2024, 4, 1),
+            to: NaiveDate::from_ymd_opt(2024, 5, 1),
+            ..Default::default()
+        };
+        assert_eq!(vec![2], search_ids("rust", &dates, conn));
+
+        conn.batch_execute(
+            "UPDATE episodes SET title = 'Sourdough starter' WHERE id = 3;
+             UPDATE podcasts SET name = 'Baking Hour' WHERE id = 2;
+             DELETE FROM episode_progresses WHERE episode_id = 1;
+             DELETE FROM episodes WHERE id = 1;",
+        )
+        .unwrap();
+        assert_eq!(vec![2], search_ids("rust", &all, conn));
+        assert_eq!(vec![3], search_ids("sourdough", &all, conn));
+        assert_eq!(vec![3], search_ids("baking hour", &all, conn));
+        assert!(search_ids("cooking", &all, conn).is_empty());
+        assert!(search_ids("pinning", &all, conn).is_empty());
+    }
+}
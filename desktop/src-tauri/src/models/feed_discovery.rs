@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::iter;
+
+use atom_syndication::Feed;
+use futures::future::join_all;
+use reqwest::Url;
+use rss::Channel;
+use serde::Serialize;
+
+use crate::models::feed_auth::FeedAuth;
+use crate::models::podcast::{fetch_feed, FeedValidators};
+
+/// A feed found through a website, with the title the feed itself gives.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedCandidate {
+    pub url: String,
+    pub title: String,
+}
+
+const FEED_TYPES: &[&str] = &["application/rss+xml", "application/atom+xml", "application/x-rss+xml"];
+
+/// Where podcast hosts and blog engines usually put the feed, tried when the page doesn't link one.
+const COMMON_FEED_PATHS: &[&str] = &[
+    "feed",
+    "rss",
+    "feed.xml",
+    "rss.xml",
+    "podcast.xml",
+    "feed/podcast",
+    "atom.xml",
+    "index.xml",
+];
+
+/// Whether a response is a web page rather than a feed, going by its first bytes.
+pub fn looks_like_html(content: &[u8]) -> bool {
+    let start = String::from_utf8_lossy(&content[..content.len().min(1024)]).to_ascii_lowercase();
+    !start.contains("<rss") && !start.contains("<feed") && (start.contains("<!doctype html") || start.contains("<html"))
+}
+
+/// Feeds for the website at `page_url`: the ones its `<link rel="alternate">` tags point at or,
+/// failing that, the ones found at common feed paths. Only URLs that really serve a feed are kept.
+pub async fn discover(page_url: &str, html: &[u8], auth: &FeedAuth) -> Vec<FeedCandidate> {
+    let Ok(page_url) = Url::parse(page_url) else {
+        return Vec::new();
+    };
+    let html = String::from_utf8_lossy(html);
+    let mut urls = linked_feeds(&page_url, &html);
+    if urls.is_empty() {
+        urls = common_feed_urls(&page_url);
+    }
+    let checks = urls.iter().map(|url| check_feed(url, auth));
+    let mut candidates: Vec<FeedCandidate> = Vec::new();
+    for candidate in join_all(checks).await.into_iter().flatten() {
+        if !candidates.iter().any(|existing| existing.url == candidate.url) {
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+fn linked_feeds(page_url: &Url, html: &str) -> Vec<String> {
+    let lowercase = html.to_ascii_lowercase();
+    let mut urls: Vec<String> = Vec::new();
+    let mut rest = 0;
+    while let Some(start) = lowercase[rest..].find("<link") {
+        let start = rest + start;
+        let Some(end) = lowercase[start..].find('>') else {
+            break;
+        };
+        let attributes = tag_attributes(&html[start + 5..start + end]);
+        rest = start + end;
+        let is_alternate = attributes.get("rel").is_some_and(|rel| {
+            rel.split_whitespace()
+                .any(|value| value.eq_ignore_ascii_case("alternate"))
+        });
+        let is_feed = attributes
+            .get("type")
+            .is_some_and(|mime| FEED_TYPES.contains(&mime.trim().to_ascii_lowercase().as_str()));
+        if !is_alternate || !is_feed {
+            continue;
+        }
+        let url = attributes.get("href").and_then(|href| page_url.join(href.trim()).ok());
+        if let Some(url) = url.map(String::from) {
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+    }
+    urls
+}
+
+/// The common paths both under the page and at the root of the site.
+fn common_feed_urls(page_url: &Url) -> Vec<String> {
+    let mut directory = page_url.clone();
+    if !directory.path().ends_with('/') {
+        let path = format!("{}/", directory.path());
+        directory.set_path(&path);
+    }
+    let mut urls: Vec<String> = Vec::new();
+    for base in [directory, page_url.join("/").unwrap_or(page_url.clone())] {
+        for path in COMMON_FEED_PATHS {
+            if let Ok(url) = base.join(path) {
+                let url = String::from(url);
+                if !urls.contains(&url) {
+                    urls.push(url);
+                }
+            }
+        }
+    }
+    urls
+}
+
+async fn check_feed(url: &str, auth: &FeedAuth) -> Option<FeedCandidate> {
+    let fetched = fetch_feed(url, &FeedValidators::default(), auth).await.ok()??;
+    if looks_like_html(&fetched.content) {
+        return None;
+    }
+    let title = match Channel::read_from(fetched.content.as_slice()) {
+        Ok(channel) => channel.title,
+        Err(_) => Feed::read_from(fetched.content.as_slice()).ok()?.title().value.clone(),
+    };
+    Some(FeedCandidate {
+        url: fetched.permanent_url.unwrap_or_else(|| url.to_string()),
+        title,
+    })
+}
+
+/// Attributes of an HTML tag, given what follows its name. Names are lowercased; values may be
+/// double-quoted, single-quoted or bare.
+fn tag_attributes(tag: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut chars = tag.trim_end_matches('/').chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let name: String = iter::from_fn(|| chars.next_if(|c| *c != '=' && !c.is_whitespace())).collect();
+        if name.is_empty() {
+            break;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next_if_eq(&'=').is_none() {
+            attributes.insert(name.to_ascii_lowercase(), String::new());
+            continue;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let value: String = match chars.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                let quote = *quote;
+                chars.next();
+                chars.by_ref().take_while(|c| *c != quote).collect()
+            }
+            _ => chars.by_ref().take_while(|c| !c.is_whitespace()).collect(),
+        };
+        attributes.insert(name.to_ascii_lowercase(), decode_entities(&value));
+    }
+    attributes
+}
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linked_feeds() {
+        let page_url = Url::parse("https://example.com/shows/daily/episode-1").unwrap();
+        let html = r#"<html><head>
+            <link rel="stylesheet" href="style.css">
+            <LINK REL="Alternate" TYPE="application/rss+xml" HREF="feed.xml" title="RSS">
+            <link rel='alternate' type='application/atom+xml' href='/atom.xml?a=1&amp;b=2'/>
+            <link rel=alternate type=application/rss+xml href=//cdn.example.net/feed.rss>
+            <link rel="alternate" type="application/rss+xml" href="./feed.xml">
+            <link rel="alternate" hreflang="de" href="/de/">
+            </head></html>"#;
+        assert_eq!(
+            vec![
+                "https://example.com/shows/daily/feed.xml",
+                "https://example.com/atom.xml?a=1&b=2",
+                "https://cdn.example.net/feed.rss",
+            ],
+            linked_feeds(&page_url, html)
+        );
+    }
+
+    #[test]
+    fn test_common_feed_urls() {
+        let urls = common_feed_urls(&Url::parse("https://example.com/show").unwrap());
+        assert_eq!(COMMON_FEED_PATHS.len() * 2, urls.len());
+        assert_eq!("https://example.com/show/feed", urls[0]);
+        assert_eq!("https://example.com/feed", urls[COMMON_FEED_PATHS.len()]);
+        let urls = common_feed_urls(&Url::parse("https://example.com/").unwrap());
+        assert_eq!(COMMON_FEED_PATHS.len(), urls.len());
+    }
+
+    #[test]
+    fn test_tag_attributes() {
+        let attributes = tag_attributes(r#" REL="alternate" data-empty href = 'a b' type=text/html"#);
+        assert_eq!(Some("alternate"), attributes.get("rel").map(String::as_str));
+        assert_eq!(Some(""), attributes.get("data-empty").map(String::as_str));
+        assert_eq!(Some("a b"), attributes.get("href").map(String::as_str));
+        assert_eq!(Some("text/html"), attributes.get("type").map(String::as_str));
+    }
+}
@@ -0,0 +1,96 @@
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use diesel::insert_into;
+use diesel::prelude::*;
+use reqwest::StatusCode;
+
+use crate::errors::{AppError, AppResult};
+use crate::models::FeedHealth;
+
+/// How long a feed is left alone after its first failure. Doubles with each failure after that.
+const BACKOFF_BASE_MINUTES: i64 = 15;
+const BACKOFF_MAX_HOURS: i64 = 24;
+
+pub fn list_all(conn: &mut SqliteConnection) -> AppResult<Vec<FeedHealth>> {
+    use crate::schema::feed_health::dsl::*;
+    let results = feed_health.select(FeedHealth::as_select()).load(conn)?;
+    Ok(results)
+}
+
+/// Whether a scheduled refresh should fetch the feed, or leave it alone until its backoff is over.
+pub fn is_due(health: &FeedHealth, now: NaiveDateTime) -> bool {
+    health.next_attempt_at.map_or(true, |next| next <= now)
+}
+
+pub fn record_success(the_podcast_id: i32, status: u16, conn: &mut SqliteConnection) -> AppResult<()> {
+    use crate::schema::feed_health::dsl::*;
+    let now = Utc::now().naive_utc();
+    let values = (
+        last_attempt_at.eq(now),
+        last_success_at.eq(Some(now)),
+        consecutive_failures.eq(0),
+        http_status.eq(Some(status as i32)),
+        next_attempt_at.eq(None::<NaiveDateTime>),
+    );
+    insert_into(feed_health)
+        .values((podcast_id.eq(the_podcast_id), values.clone()))
+        .on_conflict(podcast_id)
+        .do_update()
+        .set(values)
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Counts another failure and pushes the next attempt back accordingly.
+pub fn record_failure(the_podcast_id: i32, error: &AppError, conn: &mut SqliteConnection) -> AppResult<()> {
+    use crate::schema::feed_health::dsl::*;
+    let failures: i32 = feed_health
+        .filter(podcast_id.eq(the_podcast_id))
+        .select(consecutive_failures)
+        .first(conn)
+        .optional()?
+        .unwrap_or(0)
+        + 1;
+    let now = Utc::now().naive_utc();
+    let values = (
+        last_attempt_at.eq(now),
+        last_error.eq(Some(error.to_string())),
+        last_error_at.eq(Some(now)),
+        consecutive_failures.eq(failures),
+        http_status.eq(error_status(error).map(|status| status as i32)),
+        next_attempt_at.eq(Some(now + backoff(failures))),
+    );
+    insert_into(feed_health)
+        .values((podcast_id.eq(the_podcast_id), values.clone()))
+        .on_conflict(podcast_id)
+        .do_update()
+        .set(values)
+        .execute(conn)?;
+    Ok(())
+}
+
+fn backoff(failures: i32) -> TimeDelta {
+    let exponent = (failures - 1).clamp(0, 16) as u32;
+    let minutes = BACKOFF_BASE_MINUTES.saturating_mul(2i64.pow(exponent));
+    TimeDelta::minutes(minutes.min(BACKOFF_MAX_HOURS * 60))
+}
+
+/// The HTTP status the server answered with, when the error came from one.
+pub fn error_status(error: &AppError) -> Option<u16> {
+    error
+        .0
+        .downcast_ref::<reqwest::Error>()
+        .and_then(|e| e.status())
+        .map(|status| status.as_u16())
+}
+
+/// Errors worth trying again right away: timeouts, dropped connections, rate limits and server
+/// errors.
+pub fn is_transient(error: &AppError) -> bool {
+    let Some(e) = error.0.downcast_ref::<reqwest::Error>() else {
+        return false;
+    };
+    match e.status() {
+        Some(status) => status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+        None => e.is_timeout() || e.is_connect() || e.is_request() || e.is_body(),
+    }
+}
@@ -0,0 +1,90 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::{insert_into, update};
+
+use crate::errors::AppResult;
+use crate::models::FeedMove;
+
+/// Why a podcast's feed URL changed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MoveReason {
+    /// The old URL answered with a 301 or 308.
+    PermanentRedirect,
+    /// The feed declared an `itunes:new-feed-url`.
+    NewFeedUrl,
+    /// Another device followed the move first.
+    Sync,
+}
+
+impl MoveReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MoveReason::PermanentRedirect => "permanent_redirect",
+            MoveReason::NewFeedUrl => "new_feed_url",
+            MoveReason::Sync => "sync",
+        }
+    }
+}
+
+pub fn list_for_podcast(the_podcast_id: i32, conn: &mut SqliteConnection) -> AppResult<Vec<FeedMove>> {
+    use crate::schema::feed_moves::dsl::*;
+    let results = feed_moves
+        .filter(podcast_id.eq(the_podcast_id))
+        .order_by(created_at.desc())
+        .select(FeedMove::as_select())
+        .load(conn)?;
+    Ok(results)
+}
+
+/// Whether the podcast's feed has been moved away from `url` before.
+pub fn was_moved_from(the_podcast_id: i32, url: &str, conn: &mut SqliteConnection) -> AppResult<bool> {
+    use crate::schema::feed_moves::dsl::*;
+    let count: i64 = feed_moves
+        .filter(podcast_id.eq(the_podcast_id).and(old_url.eq(url)))
+        .count()
+        .get_result(conn)?;
+    Ok(count > 0)
+}
+
+/// Points the podcast at its new feed URL and keeps a record of where it was. Bumps `updated_at` so
+/// the new URL wins on the server and reaches the other devices; the feed refresh that follows the
+/// move, and syncs answered with an older URL, leave it as it is.
+pub fn move_feed(
+    the_podcast_id: i32,
+    old: &str,
+    new: &str,
+    move_reason: MoveReason,
+    conn: &mut SqliteConnection,
+) -> AppResult<()> {
+    tracing::info!("Podcast {the_podcast_id} moved from {old} to {new} ({move_reason:?})");
+    let now = Utc::now().naive_utc();
+    {
+        use crate::schema::podcasts::dsl::*;
+        update(podcasts)
+            .set((feed_url.eq(new), updated_at.eq(now)))
+            .filter(id.eq(the_podcast_id))
+            .execute(conn)?;
+    }
+    record(the_podcast_id, old, new, move_reason, conn)
+}
+
+/// Only records a move, for when the podcast already has the new URL.
+pub fn record(
+    the_podcast_id: i32,
+    old: &str,
+    new: &str,
+    move_reason: MoveReason,
+    conn: &mut SqliteConnection,
+) -> AppResult<()> {
+    use crate::schema::feed_moves::dsl::*;
+    insert_into(feed_moves)
+        .values((
+            podcast_id.eq(the_podcast_id),
+            old_url.eq(old),
+            new_url.eq(new),
+            reason.eq(move_reason.as_str()),
+            created_at.eq(Utc::now().naive_utc()),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::{delete, insert_into};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::AppResult;
+use crate::models::FeedWarning;
+
+/// A feed item that couldn't be read and was left out of the parsed podcast.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseWarning {
+    pub item_index: i32,
+    pub guid: Option<String>,
+    pub title: Option<String>,
+    pub message: String,
+}
+
+pub fn list_for_podcast(the_podcast_id: i32, conn: &mut SqliteConnection) -> AppResult<Vec<FeedWarning>> {
+    use crate::schema::feed_warnings::dsl::*;
+    let results = feed_warnings
+        .filter(podcast_id.eq(the_podcast_id))
+        .order_by(item_index.asc())
+        .select(FeedWarning::as_select())
+        .load(conn)?;
+    Ok(results)
+}
+
+/// Stores the warnings from the latest fetch of a feed, dropping the ones from earlier fetches.
+pub fn replace_for_podcast(
+    the_podcast_id: i32,
+    warnings: &[ParseWarning],
+    conn: &mut SqliteConnection,
+) -> AppResult<()> {
+    use crate::schema::feed_warnings::dsl::*;
+    let now = Utc::now().naive_utc();
+    delete(feed_warnings.filter(podcast_id.eq(the_podcast_id))).execute(conn)?;
+    for warning in warnings {
+        insert_into(feed_warnings)
+            .values((
+                podcast_id.eq(the_podcast_id),
+                item_index.eq(warning.item_index),
+                guid.eq(&warning.guid),
+                title.eq(&warning.title),
+                message.eq(&warning.message),
+                created_at.eq(now),
+            ))
+            .execute(conn)?;
+    }
+    Ok(())
+}
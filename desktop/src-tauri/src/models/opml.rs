@@ -0,0 +1,220 @@
+use anyhow::anyhow;
+use chrono::Utc;
+use diesel::prelude::*;
+use futures::StreamExt;
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::errors::AppResult;
+use crate::frontend_change_tracking::{AppHandleExt, EntityChange};
+use crate::models::podcast::{import_podcast_from_url, list_all, ImportResult};
+use crate::models::Podcast;
+
+/// How many feeds an OPML import fetches at once.
+const IMPORT_CONCURRENCY: usize = 4;
+
+/// A feed listed in an OPML file.
+#[derive(Clone, Debug)]
+pub struct OpmlFeed {
+    pub url: String,
+    pub title: String,
+}
+
+/// Sent after each feed of an OPML import is done with.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OpmlImportProgress {
+    pub import_id: String,
+    pub url: String,
+    pub title: String,
+    pub podcast_id: Option<i32>,
+    pub error: Option<String>,
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Every feed in the file, at any depth. Outlines without an `xmlUrl` are folders and only their
+/// children count. A file without any feeds is an error.
+pub fn parse(content: &str) -> AppResult<Vec<OpmlFeed>> {
+    let mut reader = Reader::from_str(content);
+    reader.config_mut().trim_text(true);
+    let mut feeds: Vec<OpmlFeed> = Vec::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) | Event::Empty(element)
+                if element.local_name().as_ref().eq_ignore_ascii_case(b"outline") =>
+            {
+                let mut url = None;
+                let mut text = None;
+                let mut title = None;
+                for attribute in element.attributes() {
+                    let attribute = attribute?;
+                    let value = attribute.unescape_value()?.trim().to_string();
+                    match attribute.key.local_name().as_ref().to_ascii_lowercase().as_slice() {
+                        b"xmlurl" => url = Some(value),
+                        b"text" => text = Some(value),
+                        b"title" => title = Some(value),
+                        _ => {}
+                    }
+                }
+                let Some(url) = url.filter(|url| !url.is_empty()) else {
+                    continue;
+                };
+                if !feeds.iter().any(|feed| feed.url == url) {
+                    feeds.push(OpmlFeed {
+                        title: title.or(text).unwrap_or_else(|| url.clone()),
+                        url,
+                    });
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if feeds.is_empty() {
+        return Err(anyhow!("no feeds found in the OPML file").into());
+    }
+    Ok(feeds)
+}
+
+/// Imports the feeds a few at a time, reporting each one as an `opml-import-progress` event.
+/// Feeds that fail are reported and skipped.
+pub async fn import(import_id: String, feeds: Vec<OpmlFeed>, app: AppHandle) {
+    let total = feeds.len();
+    let mut results = futures::stream::iter(feeds)
+        .map(|feed| async move {
+            let result = import_podcast_from_url(feed.url.clone()).await;
+            (feed, result)
+        })
+        .buffer_unordered(IMPORT_CONCURRENCY);
+    let mut completed = 0;
+    while let Some((feed, result)) = results.next().await {
+        completed += 1;
+        let (podcast_id, error) = match result {
+            Ok(ImportResult::Imported(podcast)) => {
+                let _ = app.send_invalidate_cache(EntityChange::Podcast(podcast.id));
+                (Some(podcast.id), None)
+            }
+            Ok(ImportResult::Candidates(_)) => (None, Some("not a podcast feed".to_string())),
+            Err(e) => {
+                tracing::info!("Could not import {} from OPML: {:?}", &feed.url, e);
+                (None, Some(e.to_string()))
+            }
+        };
+        let _ = app.emit(
+            "opml-import-progress",
+            OpmlImportProgress {
+                import_id: import_id.clone(),
+                url: feed.url,
+                title: feed.title,
+                podcast_id,
+                error,
+                completed,
+                total,
+            },
+        );
+    }
+}
+
+/// The subscriptions as an OPML 2.0 document. Deleted podcasts can be listed too, commented out so
+/// other apps don't subscribe to them.
+pub fn export(include_deleted: bool, conn: &mut SqliteConnection) -> AppResult<String> {
+    let podcasts = list_all(conn)?;
+    let mut opml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n");
+    opml.push_str("  <head>\n    <title>dimppl</title>\n");
+    opml.push_str(&format!("    <dateCreated>{}</dateCreated>\n", Utc::now().to_rfc2822()));
+    opml.push_str("  </head>\n  <body>\n");
+    for podcast in &podcasts {
+        opml.push_str(&format!("    {}\n", outline(podcast)));
+    }
+    if include_deleted {
+        for podcast in list_deleted(conn)? {
+            if podcasts.iter().any(|live| live.feed_url == podcast.feed_url) {
+                continue;
+            }
+            // "--" can't appear inside a comment
+            let outline = outline(&podcast).replace("--", "-&#45;");
+            opml.push_str(&format!("    <!-- {outline} -->\n"));
+        }
+    }
+    opml.push_str("  </body>\n</opml>\n");
+    Ok(opml)
+}
+
+fn outline(podcast: &Podcast) -> String {
+    format!(
+        "<outline type=\"rss\" text=\"{name}\" title=\"{name}\" xmlUrl=\"{url}\"/>",
+        name = escape(podcast.name.as_str()),
+        url = escape(podcast.feed_url.as_str()),
+    )
+}
+
+fn list_deleted(conn: &mut SqliteConnection) -> AppResult<Vec<Podcast>> {
+    use crate::schema::podcasts::dsl::*;
+    let results = podcasts
+        .filter(deleted_at.is_not_null())
+        .order_by(name.asc())
+        .select(Podcast::as_select())
+        .load(conn)?;
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nested_folders() {
+        let feeds = parse(
+            r#"<?xml version="1.0"?>
+            <opml version="2.0">
+              <body>
+                <outline text="Tech">
+                  <outline text="Inner" title="Inner Show" type="rss" xmlUrl="https://example.com/inner.xml"/>
+                  <outline text="Deeper">
+                    <outline text="Deep Show" xmlUrl="https://example.com/deep.xml"></outline>
+                  </outline>
+                </outline>
+                <outline xmlUrl=" https://example.com/top.xml "/>
+              </body>
+            </opml>"#,
+        )
+        .unwrap();
+        let found: Vec<(&str, &str)> = feeds
+            .iter()
+            .map(|feed| (feed.url.as_str(), feed.title.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("https://example.com/inner.xml", "Inner Show"),
+                ("https://example.com/deep.xml", "Deep Show"),
+                ("https://example.com/top.xml", "https://example.com/top.xml"),
+            ],
+            found
+        );
+    }
+
+    #[test]
+    fn test_parse_duplicate_feed() {
+        let feeds = parse(
+            r#"<opml version="2.0"><body>
+              <outline text="First" xmlUrl="https://example.com/feed.xml"/>
+              <outline text="Folder">
+                <outline text="Second" xmlUrl="https://example.com/feed.xml"/>
+              </outline>
+            </body></opml>"#,
+        )
+        .unwrap();
+        assert_eq!(1, feeds.len());
+        assert_eq!("First", feeds[0].title);
+    }
+
+    #[test]
+    fn test_parse_without_feeds() {
+        assert!(parse(r#"<opml version="2.0"><body><outline text="Empty folder"/></body></opml>"#).is_err());
+        assert!(parse("").is_err());
+    }
+}
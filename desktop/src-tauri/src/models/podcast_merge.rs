@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::{delete, insert_into, update};
+
+use crate::errors::{AppError, AppResult};
+use crate::models::{Episode, EpisodeProgress, Podcast, PodcastSettings};
+
+/// Remembers that the podcast known as `old_guid` is now `new_guid`, so the server can merge its
+/// copy on the next sync. Earlier merges into `old_guid` are pointed at `new_guid` too.
+pub fn record(old: &str, new: &str, conn: &mut SqliteConnection) -> AppResult<()> {
+    use crate::schema::podcast_merges::dsl::*;
+    update(podcast_merges)
+        .set(new_guid.eq(new))
+        .filter(new_guid.eq(old))
+        .execute(conn)?;
+    insert_into(podcast_merges)
+        .values((
+            old_guid.eq(old),
+            new_guid.eq(new),
+            created_at.eq(Utc::now().naive_utc()),
+        ))
+        .on_conflict(old_guid)
+        .do_update()
+        .set(new_guid.eq(new))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Pending merges keyed by old guid, for the backend sync request.
+pub fn list_for_sync(conn: &mut SqliteConnection) -> AppResult<HashMap<String, String>> {
+    use crate::schema::podcast_merges::dsl::*;
+    let rows = podcast_merges
+        .select((old_guid, new_guid))
+        .load::<(String, String)>(conn)?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Forgets merges the server has applied.
+pub fn clear(old_guids: &[String], conn: &mut SqliteConnection) -> AppResult<()> {
+    use crate::schema::podcast_merges::dsl::*;
+    delete(podcast_merges)
+        .filter(old_guid.eq_any(old_guids))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Folds a local duplicate into `into`. Episodes the target doesn't have are moved over; for the
+/// ones it has, the newest progress wins and bookmarks follow. Settings are kept from whichever
+/// side changed them last, then `from` is deleted. A failure halfway leaves both as they were.
+pub fn merge_local(from: &Podcast, into: &Podcast, conn: &mut SqliteConnection) -> AppResult<()> {
+    conn.transaction::<_, AppError, _>(|conn| {
+        let from_episodes: Vec<Episode> = Episode::belonging_to(from).select(Episode::as_select()).load(conn)?;
+        for from_episode in from_episodes {
+            let target: Option<Episode> = Episode::belonging_to(into)
+                .filter(crate::schema::episodes::guid.eq(&from_episode.guid))
+                .select(Episode::as_select())
+                .first(conn)
+                .optional()?;
+            let Some(target) = target else {
+                use crate::schema::episodes::dsl::*;
+                update(episodes)
+                    .set(podcast_id.eq(into.id))
+                    .filter(id.eq(from_episode.id))
+                    .execute(conn)?;
+                continue;
+            };
+            let progresses: Vec<EpisodeProgress> = {
+                use crate::schema::episode_progresses::dsl::*;
+                episode_progresses
+                    .filter(episode_id.eq_any([from_episode.id, target.id]))
+                    .select(EpisodeProgress::as_select())
+                    .load(conn)?
+            };
+            let from_progress = progresses.iter().find(|p| p.episode_id == from_episode.id);
+            let target_progress = progresses.iter().find(|p| p.episode_id == target.id);
+            if let (Some(from_progress), Some(target_progress)) = (from_progress, target_progress) {
+                if from_progress.updated_at > target_progress.updated_at {
+                    use crate::schema::episode_progresses::dsl::*;
+                    update(episode_progresses)
+                        .set((
+                            listened_seconds.eq(from_progress.listened_seconds),
+                            completed.eq(from_progress.completed),
+                            updated_at.eq(from_progress.updated_at),
+                        ))
+                        .filter(id.eq(target_progress.id))
+                        .execute(conn)?;
+                }
+            }
+            {
+                use crate::schema::bookmarks::dsl::*;
+                update(bookmarks)
+                    .set(episode_id.eq(target.id))
+                    .filter(episode_id.eq(from_episode.id))
+                    .execute(conn)?;
+            }
+            if target.content_local_path.is_empty() && !from_episode.content_local_path.is_empty() {
+                use crate::schema::episodes::dsl::*;
+                update(episodes)
+                    .set(content_local_path.eq(&from_episode.content_local_path))
+                    .filter(id.eq(target.id))
+                    .execute(conn)?;
+            }
+        }
+        merge_settings(from, into, conn)?;
+        let now = Utc::now().naive_utc();
+        use crate::schema::podcasts::dsl::*;
+        update(podcasts)
+            .set((deleted_at.eq(now), updated_at.eq(now)))
+            .filter(id.eq(from.id))
+            .execute(conn)?;
+        Ok(())
+    })
+}
+
+fn merge_settings(from: &Podcast, into: &Podcast, conn: &mut SqliteConnection) -> AppResult<()> {
+    use crate::schema::podcast_settings::dsl::*;
+    let rows: Vec<PodcastSettings> = podcast_settings
+        .filter(podcast_id.eq_any([from.id, into.id]))
+        .select(PodcastSettings::as_select())
+        .load(conn)?;
+    let Some(from_settings) = rows.iter().find(|s| s.podcast_id == from.id) else {
+        return Ok(());
+    };
+    if let Some(into_settings) = rows.iter().find(|s| s.podcast_id == into.id) {
+        if into_settings.updated_at >= from_settings.updated_at {
+            return Ok(());
+        }
+        delete(podcast_settings).filter(id.eq(into_settings.id)).execute(conn)?;
+    }
+    update(podcast_settings)
+        .set(podcast_id.eq(into.id))
+        .filter(id.eq(from_settings.id))
+        .execute(conn)?;
+    Ok(())
+}
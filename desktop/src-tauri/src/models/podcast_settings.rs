@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel::{insert_into, update};
+use dimppl_shared::sync::{AutoDownloadPolicy, EpisodeSortOrder, SyncPodcastSettings};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::AppResult;
+use crate::models::PodcastSettings;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePodcastSettingsRequest {
+    pub podcast_id: i32,
+    pub playback_speed: Option<f32>,
+    pub skip_intro_seconds: i32,
+    pub auto_download: AutoDownloadPolicy,
+    pub sort_order: EpisodeSortOrder,
+    pub notifications: bool,
+}
+
+#[derive(Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::podcast_settings)]
+#[diesel(treat_none_as_null = true)]
+struct NewPodcastSettings {
+    pub podcast_id: i32,
+    pub playback_speed: Option<f32>,
+    pub skip_intro_seconds: i32,
+    pub auto_download: String,
+    pub sort_order: String,
+    pub notifications: bool,
+    pub updated_at: NaiveDateTime,
+}
+
+impl NewPodcastSettings {
+    fn from_sync(podcast_id: i32, settings: &SyncPodcastSettings) -> Self {
+        Self {
+            podcast_id,
+            playback_speed: settings.playback_speed,
+            skip_intro_seconds: settings.skip_intro_seconds,
+            auto_download: settings.auto_download.as_str().into(),
+            sort_order: settings.sort_order.as_str().into(),
+            notifications: settings.notifications,
+            updated_at: settings.updated_at,
+        }
+    }
+}
+
+impl From<PodcastSettings> for SyncPodcastSettings {
+    fn from(value: PodcastSettings) -> Self {
+        Self {
+            playback_speed: value.playback_speed,
+            skip_intro_seconds: value.skip_intro_seconds,
+            auto_download: value.auto_download(),
+            sort_order: value.sort_order(),
+            notifications: value.notifications,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+impl PodcastSettings {
+    pub fn auto_download(&self) -> AutoDownloadPolicy {
+        AutoDownloadPolicy::from_str_or_default(&self.auto_download)
+    }
+
+    pub fn sort_order(&self) -> EpisodeSortOrder {
+        EpisodeSortOrder::from_str_or_default(&self.sort_order)
+    }
+}
+
+fn find_stored(the_podcast_id: i32, conn: &mut SqliteConnection) -> AppResult<Option<PodcastSettings>> {
+    use crate::schema::podcast_settings::dsl::*;
+    let result = podcast_settings
+        .filter(podcast_id.eq(the_podcast_id))
+        .select(PodcastSettings::as_select())
+        .first(conn)
+        .optional()?;
+    Ok(result)
+}
+
+/// Settings for a podcast, or the defaults if they were never changed. Defaults aren't stored so
+/// they never win over settings made on another device. Serial shows default to oldest first.
+pub fn find_for_podcast(the_podcast_id: i32, conn: &mut SqliteConnection) -> AppResult<PodcastSettings> {
+    if let Some(settings) = find_stored(the_podcast_id, conn)? {
+        return Ok(settings);
+    }
+    let the_podcast_type: String = {
+        use crate::schema::podcasts::dsl::*;
+        podcasts
+            .filter(id.eq(the_podcast_id))
+            .select(podcast_type)
+            .first(conn)
+            .optional()?
+            .unwrap_or_default()
+    };
+    let sort_order = if the_podcast_type == "serial" {
+        EpisodeSortOrder::OldestFirst
+    } else {
+        EpisodeSortOrder::default()
+    };
+    Ok(PodcastSettings {
+        id: 0,
+        podcast_id: the_podcast_id,
+        playback_speed: None,
+        skip_intro_seconds: 0,
+        auto_download: AutoDownloadPolicy::default().as_str().into(),
+        sort_order: sort_order.as_str().into(),
+        notifications: false,
+        updated_at: NaiveDateTime::default(),
+    })
+}
+
+pub fn update_settings(
+    conn: &mut SqliteConnection,
+    request: UpdatePodcastSettingsRequest,
+) -> AppResult<PodcastSettings> {
+    let settings = SyncPodcastSettings {
+        playback_speed: request.playback_speed,
+        skip_intro_seconds: request.skip_intro_seconds,
+        auto_download: request.auto_download,
+        sort_order: request.sort_order,
+        notifications: request.notifications,
+        updated_at: Utc::now().naive_utc(),
+    };
+    save(request.podcast_id, &settings, conn)?;
+    find_for_podcast(request.podcast_id, conn)
+}
+
+/// Stores settings that came from the server, unless the local ones are newer.
+pub fn store_from_sync(
+    the_podcast_id: i32,
+    settings: &SyncPodcastSettings,
+    conn: &mut SqliteConnection,
+) -> AppResult<()> {
+    if let Some(existing) = find_stored(the_podcast_id, conn)? {
+        if existing.updated_at >= settings.updated_at {
+            return Ok(());
+        }
+    }
+    save(the_podcast_id, settings, conn)
+}
+
+fn save(the_podcast_id: i32, settings: &SyncPodcastSettings, conn: &mut SqliteConnection) -> AppResult<()> {
+    use crate::schema::podcast_settings::dsl::*;
+    let values = NewPodcastSettings::from_sync(the_podcast_id, settings);
+    if find_stored(the_podcast_id, conn)?.is_some() {
+        update(podcast_settings)
+            .set(&values)
+            .filter(podcast_id.eq(the_podcast_id))
+            .execute(conn)?;
+    } else {
+        insert_into(podcast_settings).values(&values).execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Stored settings keyed by podcast guid, for the backend sync request.
+pub fn list_for_sync(conn: &mut SqliteConnection) -> AppResult<HashMap<String, SyncPodcastSettings>> {
+    use crate::schema::podcasts::dsl as podcasts_dsl;
+    let rows = crate::schema::podcast_settings::table
+        .inner_join(podcasts_dsl::podcasts)
+        .filter(podcasts_dsl::deleted_at.is_null())
+        .select((podcasts_dsl::guid, PodcastSettings::as_select()))
+        .load::<(String, PodcastSettings)>(conn)?;
+    Ok(rows
+        .into_iter()
+        .map(|(podcast_guid, settings)| (podcast_guid, settings.into()))
+        .collect())
+}
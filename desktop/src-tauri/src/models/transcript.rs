@@ -0,0 +1,443 @@
+use anyhow::anyhow;
+use chrono::Utc;
+use diesel::dsl::{exists, not};
+use diesel::prelude::*;
+use diesel::{delete, insert_into};
+use serde::{Deserialize, Serialize};
+
+use crate::database::db_connect;
+use crate::errors::AppResult;
+use crate::models::{feed_health, Episode, Podcast, TranscriptSegment};
+
+/// How many transcripts `fetch_pending` downloads at a time.
+const PENDING_BATCH: i64 = 20;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptHit {
+    pub segment: TranscriptSegment,
+    pub episode: Episode,
+    pub podcast: Podcast,
+}
+
+/// Transcript formats we can read, best first: the timed ones let us seek to a hit.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum TranscriptFormat {
+    Json,
+    WebVtt,
+    Srt,
+    Html,
+}
+
+impl TranscriptFormat {
+    fn from_mime(mime: &str) -> Option<Self> {
+        match mime.split(';').next().unwrap_or_default().trim() {
+            "application/json" => Some(Self::Json),
+            "text/vtt" => Some(Self::WebVtt),
+            "application/srt" | "application/x-subrip" | "text/srt" => Some(Self::Srt),
+            "text/html" => Some(Self::Html),
+            _ => None,
+        }
+    }
+}
+
+/// Picks the transcript to keep out of the `podcast:transcript` elements of an item, given as
+/// `(url, type)` pairs.
+pub fn preferred<'a>(candidates: impl Iterator<Item = (&'a str, &'a str)>) -> Option<(String, String)> {
+    candidates
+        .filter_map(|(url, mime)| Some((TranscriptFormat::from_mime(mime)?, url, mime)))
+        .min_by_key(|(format, _, _)| *format)
+        .map(|(_, url, mime)| (url.to_string(), mime.to_string()))
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::transcript_segments)]
+struct NewSegment {
+    episode_id: i32,
+    start_seconds: f64,
+    end_seconds: Option<f64>,
+    speaker: String,
+    body: String,
+}
+
+#[derive(Deserialize)]
+struct JsonTranscript {
+    #[serde(default)]
+    segments: Vec<JsonSegment>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonSegment {
+    start_time: f64,
+    end_time: Option<f64>,
+    speaker: Option<String>,
+    body: String,
+}
+
+pub fn list_for_episode(the_episode_id: i32, conn: &mut SqliteConnection) -> AppResult<Vec<TranscriptSegment>> {
+    use crate::schema::transcript_segments::dsl::*;
+    let results = transcript_segments
+        .filter(episode_id.eq(the_episode_id))
+        .order_by(start_seconds.asc())
+        .select(TranscriptSegment::as_select())
+        .load(conn)?;
+    Ok(results)
+}
+
+pub fn delete_for_episode(the_episode_id: i32, conn: &mut SqliteConnection) -> AppResult<()> {
+    {
+        use crate::schema::transcript_segments::dsl::*;
+        delete(transcript_segments)
+            .filter(episode_id.eq(the_episode_id))
+            .execute(conn)?;
+    }
+    use crate::schema::transcript_fetches::dsl::*;
+    delete(transcript_fetches)
+        .filter(episode_id.eq(the_episode_id))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// The transcript of the episode, downloading and storing it the first time.
+pub async fn fetch_for_episode(episode: &Episode, conn: &mut SqliteConnection) -> AppResult<Vec<TranscriptSegment>> {
+    let stored = list_for_episode(episode.id, conn)?;
+    if !stored.is_empty() || episode.transcript_url.is_empty() {
+        return Ok(stored);
+    }
+    let downloaded = download(episode).await;
+    store(episode.id, downloaded, conn)
+}
+
+/// Downloads the transcripts of a few episodes that have one and weren't tried yet, newest first, so
+/// transcript search also covers episodes that were never downloaded or opened.
+pub async fn fetch_pending() -> AppResult<()> {
+    let pending = list_pending(&mut db_connect())?;
+    for episode in pending {
+        let downloaded = download(&episode).await;
+        if let Err(e) = store(episode.id, downloaded, &mut db_connect()) {
+            tracing::info!("Could not fetch transcript of episode {}: {:?}", episode.id, e);
+        }
+    }
+    Ok(())
+}
+
+fn list_pending(conn: &mut SqliteConnection) -> AppResult<Vec<Episode>> {
+    use crate::schema::{episodes, podcasts, transcript_fetches, transcript_segments};
+    let results = episodes::table
+        .inner_join(podcasts::table)
+        .left_join(transcript_fetches::table)
+        .filter(episodes::transcript_url.ne(""))
+        .filter(transcript_fetches::episode_id.is_null())
+        .filter(not(exists(
+            transcript_segments::table.filter(transcript_segments::episode_id.eq(episodes::id)),
+        )))
+        .filter(podcasts::deleted_at.is_null())
+        .order_by(episodes::episode_date.desc())
+        .limit(PENDING_BATCH)
+        .select(Episode::as_select())
+        .load(conn)?;
+    Ok(results)
+}
+
+async fn download(episode: &Episode) -> AppResult<Vec<NewSegment>> {
+    let format = TranscriptFormat::from_mime(&episode.transcript_type)
+        .ok_or_else(|| anyhow!("unsupported transcript type {}", episode.transcript_type))?;
+    let content = reqwest::get(&episode.transcript_url)
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let segments = match format {
+        TranscriptFormat::Json => parse_json(&content)?,
+        TranscriptFormat::WebVtt | TranscriptFormat::Srt => parse_cues(&content),
+        TranscriptFormat::Html => parse_html(&content),
+    };
+    let new_segments = segments
+        .into_iter()
+        .filter(|segment| !segment.body.is_empty())
+        .map(|segment| NewSegment {
+            episode_id: episode.id,
+            ..segment
+        })
+        .collect();
+    Ok(new_segments)
+}
+
+/// Notes that the episode's transcript was tried, unless that failed for a reason that may go away,
+/// and stores the segments if the episode has none yet, as when it's fetched twice at once.
+fn store(
+    the_episode_id: i32,
+    downloaded: AppResult<Vec<NewSegment>>,
+    conn: &mut SqliteConnection,
+) -> AppResult<Vec<TranscriptSegment>> {
+    if !downloaded.as_ref().is_err_and(feed_health::is_transient) {
+        use crate::schema::transcript_fetches::dsl::*;
+        let now = Utc::now().naive_utc();
+        insert_into(transcript_fetches)
+            .values((episode_id.eq(the_episode_id), fetched_at.eq(now)))
+            .on_conflict(episode_id)
+            .do_update()
+            .set(fetched_at.eq(now))
+            .execute(conn)?;
+    }
+    let new_segments = downloaded?;
+    conn.immediate_transaction(|conn| {
+        let stored = list_for_episode(the_episode_id, conn)?;
+        if !stored.is_empty() || new_segments.is_empty() {
+            return Ok(stored);
+        }
+        {
+            use crate::schema::transcript_segments::dsl::*;
+            insert_into(transcript_segments).values(new_segments).execute(conn)?;
+        }
+        list_for_episode(the_episode_id, conn)
+    })
+}
+
+/// The segment being spoken at `seconds`, if the episode has a transcript.
+pub fn segment_at(
+    the_episode_id: i32,
+    seconds: f64,
+    conn: &mut SqliteConnection,
+) -> AppResult<Option<TranscriptSegment>> {
+    use crate::schema::transcript_segments::dsl::*;
+    let result = transcript_segments
+        .filter(episode_id.eq(the_episode_id).and(start_seconds.le(seconds)))
+        .order_by(start_seconds.desc())
+        .select(TranscriptSegment::as_select())
+        .first(conn)
+        .optional()?;
+    Ok(result.filter(|segment| segment.end_seconds.is_none_or(|end| seconds < end)))
+}
+
+pub fn find_one(segment_id: i32, conn: &mut SqliteConnection) -> AppResult<TranscriptSegment> {
+    use crate::schema::transcript_segments::dsl::*;
+    let result = transcript_segments
+        .filter(id.eq(segment_id))
+        .select(TranscriptSegment::as_select())
+        .first(conn)?;
+    Ok(result)
+}
+
+/// Segments of every stored transcript containing `query`, newest episodes first.
+pub fn search(query: &str, conn: &mut SqliteConnection) -> AppResult<Vec<TranscriptHit>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let pattern = format!(
+        "%{}%",
+        query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+    );
+    use crate::schema::{episodes, podcasts, transcript_segments};
+    let results = transcript_segments::table
+        .inner_join(episodes::table.inner_join(podcasts::table))
+        .filter(transcript_segments::body.like(pattern).escape('\\'))
+        .filter(podcasts::deleted_at.is_null())
+        .order_by((episodes::episode_date.desc(), transcript_segments::start_seconds.asc()))
+        .limit(200)
+        .select((
+            TranscriptSegment::as_select(),
+            Episode::as_select(),
+            Podcast::as_select(),
+        ))
+        .load::<(TranscriptSegment, Episode, Podcast)>(conn)?
+        .into_iter()
+        .map(|(segment, episode, podcast)| TranscriptHit {
+            segment,
+            episode,
+            podcast,
+        })
+        .collect();
+    Ok(results)
+}
+
+/// Podcast Index JSON transcripts are often split per word or phrase; consecutive segments by the
+/// same speaker are joined into chunks of up to 15 seconds so searches can match whole sentences.
+fn parse_json(content: &str) -> AppResult<Vec<NewSegment>> {
+    let transcript: JsonTranscript = serde_json::from_str(content)?;
+    let mut segments: Vec<NewSegment> = Vec::new();
+    for segment in transcript.segments {
+        let speaker = segment.speaker.unwrap_or_default();
+        let body = segment.body.trim();
+        if let Some(last) = segments.last_mut() {
+            if last.speaker == speaker && segment.start_time - last.start_seconds < 15.0 {
+                last.body.push(' ');
+                last.body.push_str(body);
+                last.end_seconds = segment.end_time.or(last.end_seconds);
+                continue;
+            }
+        }
+        segments.push(NewSegment {
+            episode_id: 0,
+            start_seconds: segment.start_time,
+            end_seconds: segment.end_time,
+            speaker,
+            body: body.to_string(),
+        });
+    }
+    Ok(segments)
+}
+
+/// SRT and WebVTT: blocks separated by blank lines, each with a `start --> end` line followed by
+/// the text. WebVTT voice tags (`<v Speaker>`) give the speaker.
+fn parse_cues(content: &str) -> Vec<NewSegment> {
+    let content = content.replace("\r\n", "\n");
+    let mut segments = Vec::new();
+    for block in content.split("\n\n") {
+        let mut lines = block.lines().skip_while(|line| !line.contains("-->"));
+        let Some(timing) = lines.next() else {
+            continue;
+        };
+        let mut times = timing.split("-->");
+        let start = times.next().and_then(timestamp_to_seconds);
+        // WebVTT allows cue settings after the end timestamp
+        let end = times
+            .next()
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(timestamp_to_seconds);
+        let Some(start) = start else {
+            continue;
+        };
+        let text = lines.collect::<Vec<_>>().join(" ");
+        let speaker = text
+            .find("<v ")
+            .and_then(|index| {
+                let rest = &text[index + 3..];
+                rest.find('>').map(|end| rest[..end].trim().to_string())
+            })
+            .unwrap_or_default();
+        segments.push(NewSegment {
+            episode_id: 0,
+            start_seconds: start,
+            end_seconds: end,
+            speaker,
+            body: strip_tags(&text),
+        });
+    }
+    segments
+}
+
+/// HTML transcripts follow the Podcasting 2.0 convention of `<cite>Speaker:</cite>`,
+/// `<time>00:00:00</time>` and a paragraph of text. A document without `<time>` elements becomes a
+/// single segment at the start of the episode.
+fn parse_html(content: &str) -> Vec<NewSegment> {
+    let mut segments = Vec::new();
+    let mut speaker = String::new();
+    let mut rest = content;
+    while let Some(time_index) = rest.find("<time>") {
+        if let Some(cite_index) = rest[..time_index].rfind("<cite>") {
+            let cite = &rest[cite_index + 6..time_index];
+            speaker = strip_tags(cite).trim_end_matches(':').trim().to_string();
+        }
+        let after_time = &rest[time_index + 6..];
+        let Some(time_end) = after_time.find("</time>") else {
+            break;
+        };
+        let start = timestamp_to_seconds(&after_time[..time_end]);
+        let text_start = &after_time[time_end + 7..];
+        let text_end = [text_start.find("<cite>"), text_start.find("<time>")]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or(text_start.len());
+        if let Some(start) = start {
+            segments.push(NewSegment {
+                episode_id: 0,
+                start_seconds: start,
+                end_seconds: None,
+                speaker: speaker.clone(),
+                body: strip_tags(&text_start[..text_end]),
+            });
+        }
+        rest = &text_start[text_end..];
+    }
+    if segments.is_empty() {
+        segments.push(NewSegment {
+            episode_id: 0,
+            start_seconds: 0.0,
+            end_seconds: None,
+            speaker: "".into(),
+            body: strip_tags(content),
+        });
+    }
+    segments
+}
+
+/// `HH:MM:SS.mmm`, `MM:SS.mmm` or SRT's `HH:MM:SS,mmm`.
+fn timestamp_to_seconds(string: &str) -> Option<f64> {
+    string
+        .trim()
+        .replace(',', ".")
+        .split(':')
+        .map(|part| part.parse::<f64>().ok())
+        .try_fold(0.0, |total, part| Some(total * 60.0 + part?))
+}
+
+/// Drops markup and collapses whitespace, decoding the handful of entities transcripts use.
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_to_seconds() {
+        assert_eq!(Some(3723.5), timestamp_to_seconds("01:02:03.500"));
+        assert_eq!(Some(3723.5), timestamp_to_seconds("01:02:03,500"));
+        assert_eq!(Some(62.25), timestamp_to_seconds(" 01:02.250 "));
+        assert_eq!(None, timestamp_to_seconds("01:xx:03"));
+        assert_eq!(None, timestamp_to_seconds(""));
+    }
+
+    #[test]
+    fn test_parse_webvtt() {
+        let content = "WEBVTT\r\n\r\nintro\r\n00:00:01.500 --> 00:00:04.000 align:start\r\n<v Alice>Hello \
+                       there</v>\r\n\r\n00:00:04.000 --> 00:00:06.250\r\nNo speaker\r\n";
+        let segments = parse_cues(content);
+        assert_eq!(2, segments.len());
+        assert_eq!(1.5, segments[0].start_seconds);
+        assert_eq!(Some(4.0), segments[0].end_seconds);
+        assert_eq!("Alice", segments[0].speaker);
+        assert_eq!("Hello there", segments[0].body);
+        assert_eq!(Some(6.25), segments[1].end_seconds);
+        assert_eq!("", segments[1].speaker);
+    }
+
+    #[test]
+    fn test_parse_srt() {
+        let content = "1\n00:01:02,250 --> 00:01:05,000\nFirst line\nwraps\n\n2\nbroken --> 00:01:06,000\nSkipped\n\n\
+                       3\n01:00:00,000 --> 01:00:01,000\nLast";
+        let segments = parse_cues(content);
+        assert_eq!(2, segments.len());
+        assert_eq!(62.25, segments[0].start_seconds);
+        assert_eq!(Some(65.0), segments[0].end_seconds);
+        assert_eq!("First line wraps", segments[0].body);
+        assert_eq!(3600.0, segments[1].start_seconds);
+        assert_eq!("Last", segments[1].body);
+    }
+}
@@ -11,6 +11,9 @@ pub enum AppRoute {
     Settings,
     Podcasts,
     Downloads,
+    Bookmarks,
+    Transcripts,
+    EpisodeSearch,
 }
 
 pub trait NavigationExt {
@@ -5,7 +5,7 @@ use serde::Serialize;
 use tauri::AppHandle;
 
 use crate::errors::AppResult;
-use crate::models::{Episode, Podcast};
+use crate::models::{Chapter, Episode, Podcast};
 use crate::player::new_player::NewPlayer;
 
 mod new_player;
@@ -51,6 +51,14 @@ impl Player {
         self.new_player.skip_backwards();
     }
 
+    pub fn next_chapter(&self) {
+        self.new_player.next_chapter();
+    }
+
+    pub fn previous_chapter(&self) {
+        self.new_player.previous_chapter();
+    }
+
     pub fn seek_to(&self, seconds: i64) {
         self.new_player.seek_to(seconds);
     }
@@ -76,5 +84,6 @@ pub struct PlayerStatus {
     pub podcast: Option<Podcast>,
     pub elapsed: i64,
     pub duration: i64,
+    pub chapter: Option<Chapter>,
     pub loading: bool,
 }
@@ -13,7 +13,6 @@ use anyhow::anyhow;
 use chrono::Utc;
 use diesel::associations::HasTable;
 use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper};
-use lofty::prelude::AudioFile;
 use souvlaki::{
     MediaControlEvent, MediaControls, MediaMetadata, MediaPlayback, MediaPosition, PlatformConfig, SeekDirection,
 };
@@ -31,7 +30,7 @@ use dimppl_shared::progress::ProgressUpdateRequest;
 use crate::database::db_connect;
 use crate::errors::{AppError, AppResult};
 use crate::frontend_change_tracking::{AppHandleExt, EntityChange};
-use crate::models::{podcast, Episode, EpisodeProgress, Podcast};
+use crate::models::{chapter, episode, podcast, Chapter, Episode, EpisodeProgress, Podcast};
 use crate::player::{output, PlayerStatus};
 use crate::progress_updater::ProgressUpdater;
 
@@ -41,13 +40,14 @@ pub struct NewPlayer {
     sender_channel: Arc<Mutex<Option<Sender<PlayerCommand>>>>,
     thread_handle: Arc<Mutex<Option<JoinHandle<AppResult<()>>>>>,
     playing_episode: Arc<RwLock<Option<(Episode, Podcast)>>>,
+    chapters: Arc<RwLock<Vec<Chapter>>>,
     played_millis: Arc<AtomicI64>,
     episode_length: Arc<RwLock<i64>>,
     is_paused: Arc<RwLock<bool>>,
     volume: Arc<RwLock<f32>>,
     playback_speed: Arc<RwLock<f32>>,
     media_controls: Arc<RwLock<Option<MediaControls>>>,
-    last_artwork_episode_id: Arc<RwLock<i32>>,
+    last_metadata: Arc<RwLock<(i32, Option<i32>)>>,
     latest_status: Arc<Mutex<Option<PlayerStatus>>>,
 }
 
@@ -65,13 +65,14 @@ impl NewPlayer {
             sender_channel: Arc::new(Mutex::new(None)),
             thread_handle: Arc::new(Mutex::new(None)),
             playing_episode: Arc::new(RwLock::new(None)),
+            chapters: Arc::new(RwLock::new(Vec::new())),
             played_millis: Arc::new(AtomicI64::new(0)),
             episode_length: Arc::new(RwLock::new(0)),
             is_paused: Arc::new(RwLock::new(false)),
             volume: Arc::new(RwLock::new(1.0)),
             playback_speed: Arc::new(RwLock::new(1.0)),
             media_controls: Arc::new(RwLock::new(None)),
-            last_artwork_episode_id: Arc::new(RwLock::new(0)),
+            last_metadata: Arc::new(RwLock::new((0, None))),
             latest_status: Default::default(),
         }
     }
@@ -108,10 +109,18 @@ impl NewPlayer {
                     }
                     MediaControlEvent::Next => {
                         // TODO: queue?
-                        cloned_self.skip_forwards();
+                        if cloned_self.chapters.read().unwrap().is_empty() {
+                            cloned_self.skip_forwards();
+                        } else {
+                            cloned_self.next_chapter();
+                        }
                     }
                     MediaControlEvent::Previous => {
-                        cloned_self.skip_backwards();
+                        if cloned_self.chapters.read().unwrap().is_empty() {
+                            cloned_self.skip_backwards();
+                        } else {
+                            cloned_self.previous_chapter();
+                        }
                     }
                     MediaControlEvent::Stop => {
                         // TODO
@@ -158,19 +167,27 @@ impl NewPlayer {
         *self.media_controls.write().unwrap() = Some(controls);
     }
 
-    pub fn play_episode(&self, episode: Episode, starting_at: i32) -> AppResult<()> {
+    pub fn play_episode(&self, mut episode: Episode, starting_at: i32) -> AppResult<()> {
         if episode.content_local_path.is_empty() {
             return Err(anyhow!("no content_local_path").into());
         }
         {
             let mut conn = db_connect();
+            episode.length = episode::reconcile_duration(&episode, &mut conn)?;
             let podcast = podcast::find_one(episode.podcast_id, &mut conn)?;
+            // files downloaded before embedded chapters were read get them on first play
+            *self.chapters.write().unwrap() = if episode.chapters_url.is_empty() {
+                chapter::store_embedded(&episode, &mut conn).unwrap_or_default()
+            } else {
+                chapter::list_for_episode(episode.id, &mut conn)?
+            };
             let mut playing_episode = self.playing_episode.write().unwrap();
             *playing_episode = Some((episode.clone(), podcast));
         }
-        let tagged_file = lofty::read_from_path(episode.content_local_path.as_str())?;
-        let file_duration = tagged_file.properties().duration().as_secs();
-        *self.episode_length.write().unwrap() = file_duration as i64;
+        if self.chapters.read().unwrap().is_empty() && !episode.chapters_url.is_empty() {
+            self.load_chapters(episode.clone());
+        }
+        *self.episode_length.write().unwrap() = episode.length as i64;
         self.played_millis.store((starting_at as i64) * 1000, Ordering::Relaxed);
         *self.is_paused.write().unwrap() = false;
         self.broadcast_status_self(true);
@@ -221,6 +238,61 @@ impl NewPlayer {
         self.seek_to(self.played_millis.load(Ordering::Relaxed) / 1000 - 15);
     }
 
+    pub fn next_chapter(&self) {
+        let elapsed = self.played_millis.load(Ordering::Relaxed) as f64 / 1000.0;
+        let next_start = self
+            .chapters
+            .read()
+            .unwrap()
+            .iter()
+            .find(|chapter| chapter.start_seconds > elapsed + 1.0)
+            .map(|chapter| chapter.start_seconds);
+        if let Some(start) = next_start {
+            self.seek_to(start.ceil() as i64);
+        }
+    }
+
+    /// Goes back to the start of the current chapter, or to the previous one when the current chapter
+    /// has only just started.
+    pub fn previous_chapter(&self) {
+        let elapsed = self.played_millis.load(Ordering::Relaxed) as f64 / 1000.0;
+        let previous_start = {
+            let chapters = self.chapters.read().unwrap();
+            let current = chapters.iter().rposition(|chapter| chapter.start_seconds <= elapsed);
+            match current {
+                Some(index) if elapsed - chapters[index].start_seconds > 3.0 => chapters[index].start_seconds,
+                Some(index) if index > 0 => chapters[index - 1].start_seconds,
+                _ => 0.0,
+            }
+        };
+        self.seek_to(previous_start.ceil() as i64);
+    }
+
+    /// Downloads the episode's chapters in the background, keeping them if it's still playing.
+    fn load_chapters(&self, episode: Episode) {
+        let cloned_self = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut conn = db_connect();
+            let chapters = match chapter::fetch_for_episode(&episode, &mut conn).await {
+                Ok(chapters) => chapters,
+                Err(e) => {
+                    tracing::info!("Could not fetch chapters for episode {}: {:?}", episode.id, e);
+                    return;
+                }
+            };
+            let still_playing = cloned_self
+                .playing_episode
+                .read()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|(playing, _)| playing.id == episode.id);
+            if still_playing {
+                *cloned_self.chapters.write().unwrap() = chapters;
+                cloned_self.broadcast_status_self(false);
+            }
+        });
+    }
+
     pub fn seek_to(&self, seconds: i64) {
         if self.playing_episode.read().unwrap().is_none() || seconds < 0 {
             return;
@@ -247,10 +319,11 @@ impl NewPlayer {
             *self.is_paused.read().unwrap(),
             elapsed,
             *self.episode_length.read().unwrap(),
+            &self.chapters.read().unwrap(),
             false,
             save_progress,
             &mut self.media_controls.write().unwrap(),
-            &mut self.last_artwork_episode_id.write().unwrap(),
+            &mut self.last_metadata.write().unwrap(),
         );
         *self.latest_status.lock().unwrap() = Some(status);
     }
@@ -262,16 +335,23 @@ impl NewPlayer {
         paused: bool,
         elapsed: i64,
         duration: i64,
+        chapters: &[Chapter],
         loading: bool,
         save_progress: bool,
         maybe_controls: &mut Option<MediaControls>,
-        last_seen_episode_id: &mut i32,
+        last_metadata: &mut (i32, Option<i32>),
     ) -> PlayerStatus {
         if save_progress && episode_container.is_some() {
             use crate::schema::episode_progresses::dsl::*;
             let (episode, podcast) = episode_container.clone().unwrap();
             let elapsed_seconds = elapsed / 1000;
-            let completed_listening = (episode.length as i64) - elapsed_seconds < 300; // 5 minutes
+            let known_length = if episode.length > 0 {
+                episode.length as i64
+            } else {
+                duration
+            };
+            // an unknown length can't tell us anything
+            let completed_listening = known_length > 0 && known_length - elapsed_seconds < 300; // 5 minutes
             let mut conn = db_connect();
             tracing::trace!(
                 "{} seconds elapsed, saving progress for episode id {}",
@@ -308,18 +388,21 @@ impl NewPlayer {
             podcast: episode_container.as_ref().map(|(_, podcast)| podcast.clone()),
             elapsed: elapsed / 1000,
             duration,
+            chapter: chapter::chapter_at(chapters, elapsed as f64 / 1000.0).cloned(),
             loading,
         };
         let _ = app_handle.emit("player-status", status.clone());
         if save_progress {
             if let Some(controls) = maybe_controls {
                 if let Some((episode, podcast)) = episode_container {
-                    if *last_seen_episode_id != episode.id {
-                        *last_seen_episode_id = episode.id;
+                    let chapter = status.chapter.as_ref().filter(|chapter| !chapter.title.is_empty());
+                    let metadata_key = (episode.id, chapter.map(|chapter| chapter.id));
+                    if *last_metadata != metadata_key {
+                        *last_metadata = metadata_key;
                         let cover_url = Some(podcast.image_url.as_str());
                         let _ = controls.set_metadata(MediaMetadata {
-                            title: Some(episode.title.as_str()),
-                            album: None,
+                            title: Some(chapter.map_or(episode.title.as_str(), |chapter| chapter.title.as_str())),
+                            album: chapter.map(|_| episode.title.as_str()),
                             artist: Some(podcast.name.as_str()),
                             cover_url,
                             duration: Some(Duration::from_secs(episode.length.unsigned_abs() as u64)),
@@ -497,6 +580,7 @@ impl NewPlayer {
             // TODO: mark episode as done
             cloned_self.broadcast_status_self(true);
             *cloned_self.playing_episode.write().unwrap() = None;
+            cloned_self.chapters.write().unwrap().clear();
             cloned_self.played_millis.store(0, Ordering::Relaxed);
             *cloned_self.episode_length.write().unwrap() = 0;
             cloned_self.broadcast_status_self(true);
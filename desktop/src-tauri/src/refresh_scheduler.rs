@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use reqwest::Url;
+use tauri::{AppHandle, Manager};
+use tokio::net::TcpStream;
+use tokio::time::MissedTickBehavior;
+
+use crate::config::{Config, ConfigWrapper};
+use crate::database::db_connect;
+use crate::errors::AppResult;
+use crate::frontend_change_tracking::{AppHandleExt, EntityChange};
+use crate::models::{episode, feed_health, podcast, transcript, FeedHealth, Podcast};
+
+/// How often the scheduler looks for feeds that are due.
+const TICK: Duration = Duration::from_secs(60);
+/// The wall clock getting this far ahead of the monotonic one between ticks means the computer slept.
+const SLEEP_THRESHOLD: Duration = Duration::from_secs(120);
+/// Feeds that rarely publish are still checked at least this often.
+const MAX_FEED_INTERVAL: TimeDelta = TimeDelta::hours(24);
+/// After a wake or reconnect every feed is refreshed, except the ones fetched this recently.
+const MIN_CATCH_UP_AGE: TimeDelta = TimeDelta::minutes(5);
+/// How many recent episodes a feed's publishing frequency is worked out from.
+const FREQUENCY_SAMPLE: usize = 10;
+/// Feeds are checked this many times per usual gap between their episodes.
+const CHECKS_PER_EPISODE: i32 = 4;
+/// How many feed servers are tried to tell whether the computer is online.
+const PROBE_HOSTS: usize = 3;
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Refreshes feeds in the background. A feed is due once its own interval, worked out from how often
+/// it publishes, has passed since it was last fetched; all feeds are caught up after the computer
+/// wakes up or gets back online. Transcripts not fetched yet are downloaded after each refresh.
+#[derive(Clone)]
+pub struct RefreshScheduler {
+    app_handle: AppHandle,
+}
+
+impl RefreshScheduler {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+
+    pub fn start(&self) {
+        tauri::async_runtime::spawn(self.clone().run());
+    }
+
+    async fn run(self) {
+        let mut ticker = tokio::time::interval(TICK);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut last_tick = (Instant::now(), Utc::now());
+        let mut online = true;
+        loop {
+            ticker.tick().await;
+            let now = (Instant::now(), Utc::now());
+            let slept = has_slept(last_tick, now);
+            last_tick = now;
+            let config = self.app_handle.state::<ConfigWrapper>().0.lock().unwrap().clone();
+            if config.refresh_paused {
+                continue;
+            }
+            let podcasts = match due_podcasts(&config, slept || !online) {
+                Ok(podcasts) => podcasts,
+                Err(e) => {
+                    tracing::info!("Could not work out which feeds are due: {:?}", e);
+                    continue;
+                }
+            };
+            if podcasts.is_empty() {
+                continue;
+            }
+            if !is_online(&podcasts).await {
+                if online {
+                    tracing::info!("Offline, postponing the refresh of {} feeds", podcasts.len());
+                }
+                online = false;
+                continue;
+            }
+            if slept {
+                tracing::info!("Woke up from sleep, catching up on feeds");
+            } else if !online {
+                tracing::info!("Back online, catching up on feeds");
+            }
+            online = true;
+            self.refresh(podcasts, &config).await;
+        }
+    }
+
+    async fn refresh(&self, podcasts: Vec<Podcast>, config: &Config) {
+        tracing::info!("Scheduled refresh of {} feeds", podcasts.len());
+        let ids: Vec<i32> = podcasts.iter().map(|podcast| podcast.id).collect();
+        podcast::refresh_podcasts(podcasts, &self.app_handle, config.refresh_concurrency).await;
+        for id in ids {
+            let _ = self.app_handle.send_invalidate_cache(EntityChange::Podcast(id));
+            let _ = self.app_handle.send_invalidate_cache(EntityChange::PodcastEpisodes(id));
+        }
+        let _ = self.app_handle.send_invalidate_cache(EntityChange::AllEpisodes);
+        if let Err(e) = transcript::fetch_pending().await {
+            tracing::info!("Could not fetch pending transcripts: {:?}", e);
+        }
+    }
+}
+
+fn has_slept(last: (Instant, DateTime<Utc>), now: (Instant, DateTime<Utc>)) -> bool {
+    let monotonic = now.0.duration_since(last.0);
+    let wall = (now.1 - last.1).to_std().unwrap_or_default();
+    wall.saturating_sub(monotonic) > SLEEP_THRESHOLD
+}
+
+/// Podcasts to refresh now. Feeds still backing off from failures are left alone either way.
+fn due_podcasts(config: &Config, catching_up: bool) -> AppResult<Vec<Podcast>> {
+    let mut conn = db_connect();
+    let now = Utc::now().naive_utc();
+    let base = TimeDelta::minutes(config.refresh_interval_minutes.max(1) as i64);
+    let health: HashMap<i32, FeedHealth> = feed_health::list_all(&mut conn)?
+        .into_iter()
+        .map(|health| (health.podcast_id, health))
+        .collect();
+    let intervals = feed_intervals(base, &episode::list_recent_dates(FREQUENCY_SAMPLE, &mut conn)?);
+    let podcasts = podcast::list_all(&mut conn)?
+        .into_iter()
+        .filter(|podcast| {
+            let Some(health) = health.get(&podcast.id) else {
+                return true;
+            };
+            let interval = if catching_up {
+                MIN_CATCH_UP_AGE
+            } else {
+                intervals.get(&podcast.id).copied().unwrap_or(base)
+            };
+            feed_health::is_due(health, now) && now - health.last_attempt_at >= interval
+        })
+        .collect();
+    Ok(podcasts)
+}
+
+/// How often each podcast is checked: a few times per usual gap between its episodes, but never more
+/// often than the configured interval nor less often than once a day.
+fn feed_intervals(base: TimeDelta, dates: &HashMap<i32, Vec<NaiveDateTime>>) -> HashMap<i32, TimeDelta> {
+    let longest = MAX_FEED_INTERVAL.max(base);
+    dates
+        .iter()
+        .filter_map(|(podcast_id, dates)| {
+            let mut gaps: Vec<TimeDelta> = dates.windows(2).map(|pair| pair[0] - pair[1]).collect();
+            if gaps.is_empty() {
+                return None;
+            }
+            gaps.sort();
+            let median = gaps[gaps.len() / 2];
+            Some((*podcast_id, (median / CHECKS_PER_EPISODE).clamp(base, longest)))
+        })
+        .collect()
+}
+
+/// Whether the servers of a few of the feeds can be reached, as a stand-in for having a working
+/// connection. The sync server isn't asked, as feeds can be fetched without it.
+async fn is_online(podcasts: &[Podcast]) -> bool {
+    let mut hosts: Vec<(String, u16)> = Vec::new();
+    for podcast in podcasts {
+        let Some(host) = Url::parse(&podcast.feed_url)
+            .ok()
+            .and_then(|url| Some((url.host_str()?.to_string(), url.port_or_known_default()?)))
+        else {
+            continue;
+        };
+        if !hosts.contains(&host) {
+            hosts.push(host);
+        }
+        if hosts.len() == PROBE_HOSTS {
+            break;
+        }
+    }
+    if hosts.is_empty() {
+        return true;
+    }
+    let probes = hosts.iter().map(|(host, port)| async move {
+        let connect = TcpStream::connect((host.as_str(), *port));
+        matches!(tokio::time::timeout(PROBE_TIMEOUT, connect).await, Ok(Ok(_)))
+    });
+    futures::future::join_all(probes)
+        .await
+        .into_iter()
+        .any(|reachable| reachable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn days_ago(days: &[i64]) -> Vec<NaiveDateTime> {
+        let now = Utc::now().naive_utc();
+        days.iter().map(|days| now - TimeDelta::days(*days)).collect()
+    }
+
+    #[test]
+    fn test_feed_intervals() {
+        let base = TimeDelta::minutes(60);
+        let dates = HashMap::from([
+            // weekly
+            (1, days_ago(&[0, 7, 14, 21])),
+            // daily, checked at the configured interval at most
+            (2, days_ago(&[0, 1, 2, 3])),
+            // yearly, still checked once a day
+            (3, days_ago(&[0, 365, 730])),
+            // the median ignores an odd gap
+            (4, days_ago(&[0, 1, 2, 30])),
+            // a single episode says nothing about how often the feed publishes
+            (5, days_ago(&[0])),
+        ]);
+        let intervals = feed_intervals(base, &dates);
+        assert_eq!(Some(&MAX_FEED_INTERVAL), intervals.get(&1));
+        assert_eq!(Some(&TimeDelta::hours(6)), intervals.get(&2));
+        assert_eq!(Some(&MAX_FEED_INTERVAL), intervals.get(&3));
+        assert_eq!(Some(&TimeDelta::hours(6)), intervals.get(&4));
+        assert_eq!(None, intervals.get(&5));
+
+        let hourly = HashMap::from([(1, days_ago(&[0, 0, 0]))]);
+        assert_eq!(Some(&base), feed_intervals(base, &hourly).get(&1));
+    }
+
+    #[test]
+    fn test_has_slept() {
+        let last = (Instant::now(), Utc::now());
+        let tick = (last.0 + TICK, last.1 + TimeDelta::from_std(TICK).unwrap());
+        assert!(!has_slept(last, tick));
+        let late_tick = (
+            last.0 + TICK,
+            last.1 + TimeDelta::from_std(TICK).unwrap() + TimeDelta::seconds(30),
+        );
+        assert!(!has_slept(last, late_tick));
+        let woke = (last.0 + TICK, last.1 + TimeDelta::hours(8));
+        assert!(has_slept(last, woke));
+        // the wall clock going back, as when it's corrected, isn't sleep
+        let corrected = (last.0 + TICK, last.1 - TimeDelta::hours(1));
+        assert!(!has_slept(last, corrected));
+    }
+}
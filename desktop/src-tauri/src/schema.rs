@@ -1,5 +1,31 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    bookmarks (id) {
+        id -> Integer,
+        guid -> Text,
+        episode_id -> Integer,
+        start_seconds -> Integer,
+        end_seconds -> Nullable<Integer>,
+        note -> Text,
+        created_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    chapters (id) {
+        id -> Integer,
+        episode_id -> Integer,
+        start_seconds -> Double,
+        end_seconds -> Nullable<Double>,
+        title -> Text,
+        image_url -> Text,
+        url -> Text,
+    }
+}
+
 diesel::table! {
     episode_progresses (id) {
         id -> Integer,
@@ -24,6 +50,73 @@ diesel::table! {
         link -> Text,
         episode_date -> Timestamp,
         title -> Text,
+        chapters_url -> Text,
+        transcript_url -> Text,
+        transcript_type -> Text,
+        season -> Nullable<Integer>,
+        episode_number -> Nullable<Integer>,
+        episode_type -> Text,
+        explicit -> Nullable<Bool>,
+        description_text -> Text,
+    }
+}
+
+diesel::table! {
+    feed_health (id) {
+        id -> Integer,
+        podcast_id -> Integer,
+        last_attempt_at -> Timestamp,
+        last_success_at -> Nullable<Timestamp>,
+        last_error -> Nullable<Text>,
+        last_error_at -> Nullable<Timestamp>,
+        consecutive_failures -> Integer,
+        http_status -> Nullable<Integer>,
+        next_attempt_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    feed_moves (id) {
+        id -> Integer,
+        podcast_id -> Integer,
+        old_url -> Text,
+        new_url -> Text,
+        reason -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    feed_warnings (id) {
+        id -> Integer,
+        podcast_id -> Integer,
+        item_index -> Integer,
+        guid -> Nullable<Text>,
+        title -> Nullable<Text>,
+        message -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    podcast_merges (id) {
+        id -> Integer,
+        old_guid -> Text,
+        new_guid -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    podcast_settings (id) {
+        id -> Integer,
+        podcast_id -> Integer,
+        playback_speed -> Nullable<Float>,
+        skip_intro_seconds -> Integer,
+        auto_download -> Text,
+        sort_order -> Text,
+        notifications -> Bool,
+        updated_at -> Timestamp,
     }
 }
 
@@ -40,10 +133,56 @@ diesel::table! {
         created_at -> Timestamp,
         updated_at -> Timestamp,
         deleted_at -> Nullable<Timestamp>,
+        http_etag -> Nullable<Text>,
+        http_last_modified -> Nullable<Text>,
+        content_hash -> Nullable<Text>,
+        auth_username -> Nullable<Text>,
+        auth_password -> Nullable<Text>,
+        podcast_type -> Text,
+        description_text -> Text,
+    }
+}
+
+diesel::table! {
+    transcript_fetches (episode_id) {
+        episode_id -> Integer,
+        fetched_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    transcript_segments (id) {
+        id -> Integer,
+        episode_id -> Integer,
+        start_seconds -> Double,
+        end_seconds -> Nullable<Double>,
+        speaker -> Text,
+        body -> Text,
     }
 }
 
+diesel::joinable!(bookmarks -> episodes (episode_id));
+diesel::joinable!(chapters -> episodes (episode_id));
 diesel::joinable!(episode_progresses -> episodes (episode_id));
 diesel::joinable!(episodes -> podcasts (podcast_id));
+diesel::joinable!(feed_health -> podcasts (podcast_id));
+diesel::joinable!(feed_moves -> podcasts (podcast_id));
+diesel::joinable!(feed_warnings -> podcasts (podcast_id));
+diesel::joinable!(podcast_settings -> podcasts (podcast_id));
+diesel::joinable!(transcript_fetches -> episodes (episode_id));
+diesel::joinable!(transcript_segments -> episodes (episode_id));
 
-diesel::allow_tables_to_appear_in_same_query!(episode_progresses, episodes, podcasts,);
+diesel::allow_tables_to_appear_in_same_query!(
+    bookmarks,
+    chapters,
+    episode_progresses,
+    episodes,
+    feed_health,
+    feed_moves,
+    feed_warnings,
+    podcast_merges,
+    podcast_settings,
+    podcasts,
+    transcript_fetches,
+    transcript_segments,
+);
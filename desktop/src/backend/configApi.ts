@@ -6,6 +6,20 @@ export interface Config {
   accessToken: string
   volume: number
   playbackSpeed: number
+  refreshConcurrency: number
+  refreshIntervalMinutes: number
+  refreshPaused: boolean
+  directoryProvider: 'itunes' | 'podcast_index'
+  itunesSearchUrl: string
+  podcastIndexUrl: string
+  podcastIndexKey: string
+  podcastIndexSecret: string
+}
+
+export interface PairingCode {
+  code: string
+  expiresAt: string
+  qrPayload: string
 }
 
 export const configApi = {
@@ -24,6 +38,13 @@ export const configApi = {
     await invoke<void>('register_device', { deviceName })
     return await configApi.load()
   },
+  createPairingCode: async (): Promise<PairingCode> => {
+    return invoke<PairingCode>('create_pairing_code')
+  },
+  redeemPairingCode: async (code: string, deviceName: string): Promise<Config> => {
+    await invoke<void>('redeem_pairing_code', { code, deviceName })
+    return await configApi.load()
+  },
   setVolume: async (volume: number): Promise<void> => {
     await invoke<void>('set_volume', { volume })
   }
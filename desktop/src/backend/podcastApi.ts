@@ -11,6 +11,9 @@ export interface Podcast {
   description: string
   createdAt: string
   updatedAt: string
+  authUsername: string | null
+  podcastType: 'episodic' | 'serial'
+  descriptionText: string
 }
 
 export interface EpisodeWithProgress {
@@ -31,6 +34,29 @@ export interface Episode {
   link: string
   episodeDate: string
   title: string
+  chaptersUrl: string
+  transcriptUrl: string
+  transcriptType: string
+  season: number | null
+  episodeNumber: number | null
+  episodeType: 'full' | 'trailer' | 'bonus'
+  explicit: boolean | null
+  descriptionText: string
+}
+
+export interface EpisodeListOptions {
+  season?: number
+  hideTrailers?: boolean
+}
+
+export interface Chapter {
+  id: number
+  episodeId: number
+  startSeconds: number
+  endSeconds: number | null
+  title: string
+  imageUrl: string
+  url: string
 }
 
 export interface EpisodeProgress {
@@ -47,6 +73,20 @@ export interface EpisodeWithPodcast {
   progress: EpisodeProgress
 }
 
+export interface EpisodeSearchOptions {
+  podcastId: number | null
+  unplayed: boolean
+  downloaded: boolean
+  // yyyy-mm-dd, inclusive
+  from: string | null
+  to: string | null
+}
+
+export interface EpisodeSearchHit extends EpisodeWithPodcast {
+  titleHtml: string
+  snippetHtml: string
+}
+
 export interface EpisodeWithFileSize extends EpisodeWithPodcast {
   fileSize: number
 }
@@ -61,11 +101,135 @@ export interface PodcastWithStats {
 export interface PodcastUpdateRequest {
   id: number
   url: string
+  // omitted to keep the stored credentials, empty to remove them
+  username?: string
+  password?: string
+}
+
+export type AutoDownloadPolicy = 'off' | 'latest' | 'all'
+
+export type EpisodeSortOrder = 'newest_first' | 'oldest_first'
+
+export interface PodcastSettings {
+  id: number
+  podcastId: number
+  playbackSpeed: number | null
+  skipIntroSeconds: number
+  autoDownload: AutoDownloadPolicy
+  sortOrder: EpisodeSortOrder
+  notifications: boolean
+  updatedAt: string
+}
+
+export interface PodcastSettingsUpdateRequest {
+  podcastId: number
+  playbackSpeed: number | null
+  skipIntroSeconds: number
+  autoDownload: AutoDownloadPolicy
+  sortOrder: EpisodeSortOrder
+  notifications: boolean
+}
+
+export interface ParseWarning {
+  itemIndex: number
+  guid: string | null
+  title: string | null
+  message: string
+}
+
+export interface FeedWarning extends ParseWarning {
+  id: number
+  podcastId: number
+  createdAt: string
+}
+
+export interface FeedMove {
+  id: number
+  podcastId: number
+  oldUrl: string
+  newUrl: string
+  reason: 'permanent_redirect' | 'new_feed_url' | 'sync'
+  createdAt: string
+}
+
+export interface DirectoryPodcast {
+  title: string
+  author: string
+  artworkUrl: string
+  feedUrl: string
+}
+
+export interface OpmlImportProgress {
+  importId: string
+  url: string
+  title: string
+  podcastId: number | null
+  error: string | null
+  completed: number
+  total: number
+}
+
+export interface FeedCandidate {
+  url: string
+  title: string
+}
+
+export interface FeedHealth {
+  id: number
+  podcastId: number
+  lastAttemptAt: string
+  lastSuccessAt: string | null
+  lastError: string | null
+  lastErrorAt: string | null
+  consecutiveFailures: number
+  httpStatus: number | null
+  nextAttemptAt: string | null
 }
 
 export interface PodcastSyncError {
   id: number
   error: string
+  warnings: ParseWarning[]
+}
+
+export interface Bookmark {
+  id: number
+  guid: string
+  episodeId: number
+  startSeconds: number
+  endSeconds: number | null
+  note: string
+  createdAt: string
+  deletedAt: string | null
+  updatedAt: string
+}
+
+export interface TranscriptSegment {
+  id: number
+  episodeId: number
+  startSeconds: number
+  endSeconds: number | null
+  speaker: string
+  body: string
+}
+
+export interface TranscriptHit {
+  segment: TranscriptSegment
+  episode: Episode
+  podcast: Podcast
+}
+
+export interface BookmarkWithEpisode {
+  bookmark: Bookmark
+  episode: Episode
+  podcast: Podcast
+}
+
+export interface NewBookmarkRequest {
+  episodeId: number
+  startSeconds: number
+  endSeconds: number | null
+  note: string
 }
 
 export const podcastApi = {
@@ -84,11 +248,26 @@ export const podcastApi = {
   listLatestEpisodes: async (): Promise<EpisodeWithPodcast[]> => {
     return await invoke<EpisodeWithPodcast[]>('list_latest_episodes')
   },
+  searchEpisodes: async (query: string, options: EpisodeSearchOptions): Promise<EpisodeSearchHit[]> => {
+    return await invoke<EpisodeSearchHit[]>('search_episodes', { query, options })
+  },
   importPodcast: async (url: string): Promise<string> => {
     return await invoke<string>('import_podcast', { url })
   },
-  listEpisodes: async (podcastId: number): Promise<EpisodeWithProgress[]> => {
-    return await invoke<EpisodeWithProgress[]>('list_podcast_episodes', { id: podcastId })
+  importOpml: async (content: string): Promise<string> => {
+    return await invoke<string>('import_opml', { content })
+  },
+  exportOpml: async (includeDeleted: boolean): Promise<string> => {
+    return await invoke<string>('export_opml', { includeDeleted })
+  },
+  searchPodcasts: async (query: string): Promise<DirectoryPodcast[]> => {
+    return await invoke<DirectoryPodcast[]>('search_podcasts', { query })
+  },
+  listEpisodes: async (podcastId: number, options?: EpisodeListOptions): Promise<EpisodeWithProgress[]> => {
+    return await invoke<EpisodeWithProgress[]>('list_podcast_episodes', { id: podcastId, options })
+  },
+  listPodcastSeasons: async (podcastId: number): Promise<number[]> => {
+    return await invoke<number[]>('list_podcast_seasons', { id: podcastId })
   },
   getEpisode: async (id: number): Promise<Episode> => {
     return await invoke<Episode>('get_episode', { id })
@@ -96,13 +275,18 @@ export const podcastApi = {
   getEpisodeFull: async (id: number): Promise<EpisodeWithPodcast> => {
     return await invoke<EpisodeWithPodcast>('get_episode_full', { id })
   },
+  listEpisodeChapters: async (id: number): Promise<Chapter[]> => {
+    return await invoke<Chapter[]>('list_episode_chapters', { id })
+  },
   downloadEpisode: async (id: number): Promise<void> => {
     return await invoke<void>('download_episode', { id })
   },
   playEpisode: async (id: number): Promise<void> => {
     return await invoke<void>('play_episode', { id })
   },
-  playerAction: async (action: 'play' | 'pause' | 'skip_forwards' | 'skip_backwards'): Promise<void> => {
+  playerAction: async (
+    action: 'play' | 'pause' | 'skip_forwards' | 'skip_backwards' | 'next_chapter' | 'previous_chapter'
+  ): Promise<void> => {
     return await invoke<void>('player_action', { action })
   },
   seek: async (to: number): Promise<void> => {
@@ -125,5 +309,47 @@ export const podcastApi = {
   },
   deletePodcast: async (id: number): Promise<void> => {
     return await invoke<void>('delete_podcast', { id })
+  },
+  getPodcastSettings: async (id: number): Promise<PodcastSettings> => {
+    return await invoke<PodcastSettings>('get_podcast_settings', { id })
+  },
+  listFeedWarnings: async (id: number): Promise<FeedWarning[]> => {
+    return await invoke<FeedWarning[]>('list_feed_warnings', { id })
+  },
+  listFeedHealth: async (): Promise<FeedHealth[]> => {
+    return await invoke<FeedHealth[]>('list_feed_health')
+  },
+  listFeedMoves: async (id: number): Promise<FeedMove[]> => {
+    return await invoke<FeedMove[]>('list_feed_moves', { id })
+  },
+  updatePodcastSettings: async (request: PodcastSettingsUpdateRequest): Promise<PodcastSettings> => {
+    return await invoke<PodcastSettings>('update_podcast_settings', { request })
+  },
+  addBookmark: async (request: NewBookmarkRequest): Promise<Bookmark> => {
+    return await invoke<Bookmark>('add_bookmark', { request })
+  },
+  listBookmarks: async (episodeId: number | null = null): Promise<BookmarkWithEpisode[]> => {
+    return await invoke<BookmarkWithEpisode[]>('list_bookmarks', { episodeId })
+  },
+  deleteBookmark: async (id: number): Promise<void> => {
+    return await invoke<void>('delete_bookmark', { id })
+  },
+  playBookmark: async (id: number): Promise<void> => {
+    return await invoke<void>('play_bookmark', { id })
+  },
+  listTranscriptSegments: async (id: number): Promise<TranscriptSegment[]> => {
+    return await invoke<TranscriptSegment[]>('list_transcript_segments', { id })
+  },
+  currentTranscriptSegment: async (): Promise<TranscriptSegment | null> => {
+    return await invoke<TranscriptSegment | null>('current_transcript_segment')
+  },
+  searchTranscripts: async (query: string): Promise<TranscriptHit[]> => {
+    return await invoke<TranscriptHit[]>('search_transcripts', { query })
+  },
+  playTranscriptSegment: async (id: number): Promise<void> => {
+    return await invoke<void>('play_transcript_segment', { id })
+  },
+  playEpisodeAt: async (id: number, seconds: number): Promise<void> => {
+    return await invoke<void>('play_episode_at', { id, seconds })
   }
 }
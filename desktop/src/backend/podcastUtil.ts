@@ -1,19 +1,37 @@
-import { Episode, Podcast } from './podcastApi.ts'
+import React from 'react'
+import { Episode, Podcast, podcastApi } from './podcastApi.ts'
+
+// `size` is in CSS pixels; the local image goes in the URL so a new one isn't hidden by the cache
+const localImageUrl = (path: string, localImage: string, size?: number): string => {
+  const params = new URLSearchParams({ v: localImage })
+  if (size !== undefined) {
+    params.set('size', Math.ceil(size * window.devicePixelRatio).toString())
+  }
+  return `localimages://${path}?${params}`
+}
 
 export const podcastUtil = {
-  imageUrl: (podcast: Podcast): string => {
+  imageUrl: (podcast: Podcast, size?: number): string => {
     if (podcast.localImagePath.length !== 0) {
-      return `localimages://podcast/${podcast.id}`
+      return localImageUrl(`podcast/${podcast.id}`, podcast.localImagePath, size)
     }
     return podcast.imageUrl
   },
-  episodeImage: (episode: Episode, podcast: Podcast): string => {
+  episodeImage: (episode: Episode, podcast: Podcast, size?: number): string => {
     if (episode.imageLocalPath.length !== 0) {
-      return `localimages://episode/${episode.id}`
+      return localImageUrl(`episode/${episode.id}`, episode.imageLocalPath, size)
     }
     if (episode.imageUrl.length !== 0) {
       return episode.imageUrl
     }
-    return podcastUtil.imageUrl(podcast)
+    return podcastUtil.imageUrl(podcast, size)
+  },
+  // show notes mark timestamps as <a class="seek-point" data-seconds="...">
+  seekPointClickHandler: (episode: Episode) => (e: React.MouseEvent<HTMLElement>) => {
+    const seekPoint = (e.target as HTMLElement).closest<HTMLElement>('a.seek-point')
+    if (seekPoint === null) return
+    e.preventDefault()
+    e.stopPropagation()
+    podcastApi.playEpisodeAt(episode.id, Number(seekPoint.dataset.seconds))
   }
 }
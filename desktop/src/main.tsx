@@ -19,6 +19,10 @@ listen<string>('invalidate-cache', event => {
   queryClient.invalidateQueries({ queryKey: [event.payload] })
 })
 
+listen<string>('backend-incompatible', event => {
+  alert(event.payload)
+})
+
 invoke<void>('set_up_media_controls')
 
 ReactDOM.createRoot(document.getElementById('root')!).render(
@@ -5,12 +5,15 @@ import { OnboardingDeviceNameRoute } from './routes/onboarding/OnboardingDeviceN
 import { AppRoute } from './routes/app/AppRoute.tsx'
 import { HomeRoute } from './routes/app/home/HomeRoute.tsx'
 import { PodcastRoute } from './routes/app/podcast/PodcastRoute.tsx'
-import { EpisodeWithFileSize, EpisodeWithPodcast, podcastApi } from './backend/podcastApi.ts'
+import { BookmarkWithEpisode, EpisodeWithFileSize, EpisodeWithPodcast, podcastApi } from './backend/podcastApi.ts'
 import { EpisodeRoute } from './routes/app/episode/EpisodeRoute.tsx'
 import { SettingsRoute } from './routes/app/manage/SettingsRoute.tsx'
 import { Config, configApi } from './backend/configApi.ts'
 import { PodcastsRoute } from './routes/app/manage/PodcastsRoute.tsx'
 import { DownloadsRoute } from './routes/app/manage/DownloadsRoute.tsx'
+import { BookmarksRoute } from './routes/app/manage/BookmarksRoute.tsx'
+import { TranscriptsRoute } from './routes/app/manage/TranscriptsRoute.tsx'
+import { EpisodeSearchRoute } from './routes/app/manage/EpisodeSearchRoute.tsx'
 
 export const rootRoute = createRootRoute({
   component: RootRouteComponent
@@ -93,10 +96,39 @@ export const downloadsRoute = createRoute({
   loader: async (): Promise<EpisodeWithFileSize[]> => podcastApi.listAllDownloads()
 })
 
+export const bookmarksRoute = createRoute({
+  getParentRoute: () => appRoute,
+  path: 'bookmarks',
+  component: BookmarksRoute,
+  loader: async (): Promise<BookmarkWithEpisode[]> => podcastApi.listBookmarks()
+})
+
+export const transcriptsRoute = createRoute({
+  getParentRoute: () => appRoute,
+  path: 'transcripts',
+  component: TranscriptsRoute
+})
+
+export const episodeSearchRoute = createRoute({
+  getParentRoute: () => appRoute,
+  path: 'search',
+  component: EpisodeSearchRoute
+})
+
 const routeTree = rootRoute.addChildren([
   onboardingUserAccountRoute,
   onboardingDeviceNameRoute,
-  appRoute.addChildren([settingsRoute, podcastsRoute, downloadsRoute, appHomeRoute, podcastRoute, episodeRoute])
+  appRoute.addChildren([
+    settingsRoute,
+    podcastsRoute,
+    downloadsRoute,
+    bookmarksRoute,
+    transcriptsRoute,
+    episodeSearchRoute,
+    appHomeRoute,
+    podcastRoute,
+    episodeRoute
+  ])
 ])
 
 export const router = createRouter({ routeTree })
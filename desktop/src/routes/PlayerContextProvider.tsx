@@ -1,4 +1,4 @@
-import { Episode, Podcast } from '../backend/podcastApi.ts'
+import { Chapter, Episode, Podcast } from '../backend/podcastApi.ts'
 import React, { createContext, PropsWithChildren, useEffect, useState } from 'react'
 import { listen } from '@tauri-apps/api/event'
 
@@ -8,6 +8,7 @@ export interface PlayerStatus {
   podcast?: Podcast
   elapsed: number
   duration: number
+  chapter?: Chapter
   loading: boolean
 }
 
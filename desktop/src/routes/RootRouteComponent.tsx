@@ -8,7 +8,7 @@ export const RootRouteComponent: React.FC = () => {
   useEffect(() => {
     configApi.load().then(configData => {
       console.log(configData)
-      if (configData.accessToken.length !== 0 && configData.userAccessKey.length !== 0) {
+      if (configData.accessToken.length !== 0) {
         return navigate({ to: appHomeRoute.to })
       } else {
         return navigate({ to: onboardingUserAccountRoute.to })
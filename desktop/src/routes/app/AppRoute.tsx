@@ -4,17 +4,20 @@ import { Sidebar } from './sidebar/Sidebar.tsx'
 import { PlayerControlsTopBar } from './controls/PlayerControlsTopBar.tsx'
 import {
   appHomeRoute,
+  bookmarksRoute,
   downloadsRoute,
   episodeRoute,
+  episodeSearchRoute,
   podcastRoute,
   podcastsRoute,
-  settingsRoute
+  settingsRoute,
+  transcriptsRoute
 } from '../../routeDefinitions.ts'
 import { RootDiv } from '../../components/RootDiv.tsx'
 import { listen } from '@tauri-apps/api/event'
 
 export interface NavigationEvent {
-  type: 'Home' | 'Podcast' | 'Episode' | 'Settings' | 'Podcasts' | 'Downloads',
+  type: 'Home' | 'Podcast' | 'Episode' | 'Settings' | 'Podcasts' | 'Downloads' | 'Bookmarks' | 'Transcripts' | 'EpisodeSearch',
   id?: number
 }
 
@@ -46,6 +49,15 @@ export const AppRoute: React.FC = () => {
         case 'Downloads':
           navigate({ to: downloadsRoute.to })
           break
+        case 'Bookmarks':
+          navigate({ to: bookmarksRoute.to })
+          break
+        case 'Transcripts':
+          navigate({ to: transcriptsRoute.to })
+          break
+        case 'EpisodeSearch':
+          navigate({ to: episodeSearchRoute.to })
+          break
       }
     })
   }, [])
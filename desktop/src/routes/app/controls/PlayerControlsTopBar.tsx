@@ -116,6 +116,10 @@ export const PlayerControlsTopBar: React.FC = () => {
   return (
     <TopBar data-tauri-drag-region={true}>
       <ContentAligner data-tauri-drag-region={true}>
+        {playerStatus.chapter && (
+          <ToolbarIconButton icon="skip_previous"
+                             onClick={() => podcastApi.playerAction('previous_chapter')}/>
+        )}
         <ToolbarIconButton icon="fast_rewind" disabled={playerStatus.episode === undefined}
                            onClick={() => podcastApi.playerAction('skip_backwards')}/>
         <ToolbarIconButton icon={playerStatus.isPaused ? 'play_arrow' : 'pause'}
@@ -123,18 +127,26 @@ export const PlayerControlsTopBar: React.FC = () => {
                            onClick={() => podcastApi.playerAction(playerStatus.isPaused ? 'play' : 'pause')}/>
         <ToolbarIconButton icon="fast_forward" disabled={playerStatus.episode === undefined}
                            onClick={() => podcastApi.playerAction('skip_forwards')}/>
+        {playerStatus.chapter && (
+          <ToolbarIconButton icon="skip_next"
+                             onClick={() => podcastApi.playerAction('next_chapter')}/>
+        )}
       </ContentAligner>
       {(playerStatus.episode === null || playerStatus.episode === undefined) ? <DisplayIsland/> : (
         <DisplayIsland>
-          <ImageBox url={podcastUtil.episodeImage(playerStatus.episode!, playerStatus.podcast!)}/>
+          <ImageBox url={podcastUtil.episodeImage(playerStatus.episode!, playerStatus.podcast!, 40)}/>
           <RightSide>
             <TextBox>
               <p
                 title={playerStatus.episode.title}>{playerStatus.loading ? 'Carregando...' : playerStatus.episode.title}</p>
               <p style={{ color: 'var(--gray50)' }}>
-                {playerStatus.podcast?.name}
-                {' — '}
-                {formatDate(playerStatus.episode.episodeDate)}
+                {playerStatus.chapter ? playerStatus.chapter.title : (
+                  <>
+                    {playerStatus.podcast?.name}
+                    {' — '}
+                    {formatDate(playerStatus.episode.episodeDate)}
+                  </>
+                )}
               </p>
               <p className="left">{formatHms(playerStatus.elapsed)}</p>
               <p className="right">{formatHms(playerStatus.duration)}</p>
@@ -76,7 +76,7 @@ export const EpisodeRoute: React.FC = () => {
       <WrapperDiv>
         <BackButton/>
         <div style={{ display: 'flex', gap: 24 }}>
-          <BigImage url={podcastUtil.imageUrl(podcast)}/>
+          <BigImage url={podcastUtil.imageUrl(podcast, 200)}/>
           <DescriptionWrapper>
             <Title>{episode.title}</Title>
             <Subtitle>{podcast.name}</Subtitle>
@@ -111,7 +111,8 @@ export const EpisodeRoute: React.FC = () => {
         </div>
       </WrapperDiv>
       <hr style={{ margin: 8 }}/>
-      <div style={{ padding: 8 }} dangerouslySetInnerHTML={{ __html: episode.description }}></div>
+      <div style={{ padding: 8 }} onClick={podcastUtil.seekPointClickHandler(episode)}
+           dangerouslySetInnerHTML={{ __html: episode.description }}></div>
     </div>
   )
 }
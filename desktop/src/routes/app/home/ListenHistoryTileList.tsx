@@ -60,7 +60,7 @@ const EpisodeTile: React.FC<{ data: EpisodeWithPodcast }> = ({ data }) => {
   const { episode, podcast, progress } = data
   return (
     <TileWrapper>
-      <BigImage url={podcastUtil.episodeImage(episode, podcast)}/>
+      <BigImage url={podcastUtil.episodeImage(episode, podcast, 125)}/>
       <TileLink title={episode.title} to={`episode/${episode.id}`} search={{}} params={{}}>{episode.title}</TileLink>
       <DateDisplay>
         {
@@ -0,0 +1,56 @@
+import React from 'react'
+import { CoolTable, NoScrollContainer, SettingsToolbar, TableContainer } from './shared.tsx'
+import { useQuery } from '@tanstack/react-query'
+import { podcastApi } from '../../../backend/podcastApi.ts'
+import { bookmarksRoute } from '../../../routeDefinitions.ts'
+import { formatDate, formatHms } from '../../../timeUtil.ts'
+import { IconButton } from '../IconButton.tsx'
+
+export const BookmarksRoute: React.FC = () => {
+  const query = useQuery({
+    queryKey: ['allBookmarks'],
+    queryFn: () => podcastApi.listBookmarks(),
+    initialData: bookmarksRoute.useLoaderData()
+  })
+  return (
+    <NoScrollContainer>
+      <SettingsToolbar/>
+      <TableContainer>
+        <CoolTable>
+          <thead>
+          <tr>
+            <th>Episódio</th>
+            <th>Trecho</th>
+            <th>Nota</th>
+            <th>Criado em</th>
+            <th></th>
+          </tr>
+          </thead>
+          <tbody>
+          {query.data?.map(row => (
+            <tr key={row.bookmark.id} onDoubleClick={() => podcastApi.playBookmark(row.bookmark.id)}>
+              <td>
+                <strong>{row.episode.title}</strong>
+                <br/>
+                {row.podcast.name}
+              </td>
+              <td className="tiny">
+                {formatHms(row.bookmark.startSeconds)}
+                {row.bookmark.endSeconds !== null ? ` - ${formatHms(row.bookmark.endSeconds)}` : ''}
+              </td>
+              <td>{row.bookmark.note}</td>
+              <td className="tiny">{formatDate(row.bookmark.createdAt)}</td>
+              <td>
+                <div style={{ display: 'flex' }}>
+                  <IconButton icon="play_circle" title="Tocar" onClick={() => podcastApi.playBookmark(row.bookmark.id)}/>
+                  <IconButton icon="delete" title="Excluir" onClick={() => podcastApi.deleteBookmark(row.bookmark.id)}/>
+                </div>
+              </td>
+            </tr>
+          ))}
+          </tbody>
+        </CoolTable>
+      </TableContainer>
+    </NoScrollContainer>
+  )
+}
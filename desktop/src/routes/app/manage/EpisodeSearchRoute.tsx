@@ -0,0 +1,142 @@
+import React, { useState } from 'react'
+import styled from 'styled-components'
+import { Link } from '@tanstack/react-router'
+import { CoolTable, NoScrollContainer, SettingsToolbar, TableContainer } from './shared.tsx'
+import { useQuery } from '@tanstack/react-query'
+import { EpisodeSearchOptions, podcastApi } from '../../../backend/podcastApi.ts'
+import { episodeRoute } from '../../../routeDefinitions.ts'
+import { formatDate } from '../../../timeUtil.ts'
+import { IconButton } from '../IconButton.tsx'
+
+const SearchForm = styled.form`
+  padding: 8px 8px 0;
+  display: flex;
+  flex-direction: column;
+  gap: 6px;
+
+  & input[type=search] {
+    width: 100%;
+    padding: 4px;
+  }
+`
+
+const Filters = styled.div`
+  display: flex;
+  flex-wrap: wrap;
+  align-items: center;
+  gap: 12px;
+  font-size: 90%;
+
+  & label {
+    display: flex;
+    align-items: center;
+    gap: 4px;
+  }
+`
+
+const Highlighted = styled.span`
+  & mark {
+    background-color: var(--murrey);
+    color: inherit;
+  }
+`
+
+export const EpisodeSearchRoute: React.FC = () => {
+  const [text, setText] = useState('')
+  const [searchText, setSearchText] = useState('')
+  const [options, setOptions] = useState<EpisodeSearchOptions>({
+    podcastId: null,
+    unplayed: false,
+    downloaded: false,
+    from: null,
+    to: null
+  })
+  const podcasts = useQuery({
+    queryKey: ['allPodcasts'],
+    queryFn: () => podcastApi.listAll(),
+    initialData: []
+  })
+  const query = useQuery({
+    queryKey: ['episodeSearch', searchText, options],
+    queryFn: () => podcastApi.searchEpisodes(searchText, options)
+  })
+  const setOption = <K extends keyof EpisodeSearchOptions>(key: K, value: EpisodeSearchOptions[K]) => {
+    setOptions(current => ({ ...current, [key]: value }))
+  }
+  return (
+    <NoScrollContainer>
+      <SettingsToolbar/>
+      <SearchForm onSubmit={e => {
+        e.preventDefault()
+        setSearchText(text.trim())
+      }}>
+        <input type="search" placeholder="Buscar episódios por título, descrição ou podcast" value={text}
+               onChange={e => setText(e.currentTarget.value)}/>
+        <Filters>
+          <label>
+            <span>Podcast</span>
+            <select
+              value={options.podcastId ?? ''}
+              onChange={e => setOption('podcastId', e.currentTarget.value === '' ? null : Number(e.currentTarget.value))}
+            >
+              <option value="">Todos</option>
+              {podcasts.data.map(it => <option key={it.id} value={it.id}>{it.name}</option>)}
+            </select>
+          </label>
+          <label>
+            <input type="checkbox" checked={options.unplayed}
+                   onChange={e => setOption('unplayed', e.currentTarget.checked)}/>
+            <span>Não ouvidos</span>
+          </label>
+          <label>
+            <input type="checkbox" checked={options.downloaded}
+                   onChange={e => setOption('downloaded', e.currentTarget.checked)}/>
+            <span>Baixados</span>
+          </label>
+          <label>
+            <span>De</span>
+            <input type="date" value={options.from ?? ''}
+                   onChange={e => setOption('from', e.currentTarget.value === '' ? null : e.currentTarget.value)}/>
+          </label>
+          <label>
+            <span>Até</span>
+            <input type="date" value={options.to ?? ''}
+                   onChange={e => setOption('to', e.currentTarget.value === '' ? null : e.currentTarget.value)}/>
+          </label>
+        </Filters>
+      </SearchForm>
+      <TableContainer>
+        <CoolTable>
+          <thead>
+          <tr>
+            <th>Episódio</th>
+            <th>Trecho</th>
+            <th>Data</th>
+            <th></th>
+          </tr>
+          </thead>
+          <tbody>
+          {query.data?.map(hit => (
+            <tr key={hit.episode.id} onDoubleClick={() => podcastApi.playEpisode(hit.episode.id)}>
+              <td>
+                <Link to={episodeRoute.to} params={{ episodeId: hit.episode.id.toString() }}>
+                  <Highlighted as="strong" dangerouslySetInnerHTML={{ __html: hit.titleHtml }}/>
+                </Link>
+                <br/>
+                {hit.podcast.name}
+              </td>
+              <td className="selectable">
+                <Highlighted dangerouslySetInnerHTML={{ __html: hit.snippetHtml }}/>
+              </td>
+              <td className="tiny">{formatDate(hit.episode.episodeDate)}</td>
+              <td>
+                <IconButton icon="play_circle" title="Tocar" onClick={() => podcastApi.playEpisode(hit.episode.id)}/>
+              </td>
+            </tr>
+          ))}
+          </tbody>
+        </CoolTable>
+      </TableContainer>
+    </NoScrollContainer>
+  )
+}
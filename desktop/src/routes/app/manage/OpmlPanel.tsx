@@ -0,0 +1,101 @@
+import React, { useCallback, useEffect, useState } from 'react'
+import { useQueryClient } from '@tanstack/react-query'
+import styled from 'styled-components'
+import { listen } from '@tauri-apps/api/event'
+import { OpmlImportProgress, podcastApi } from '../../../backend/podcastApi.ts'
+import { PrettyButton } from '../../../components/PrettyButton.tsx'
+
+const PanelContainer = styled.div`
+  padding: 8px;
+  border-bottom: 2px solid var(--gray12);
+  display: flex;
+  flex-direction: column;
+  gap: 8px;
+
+  .button-container {
+    display: flex;
+    align-items: center;
+    gap: 8px;
+  }
+`
+
+export const OpmlPanel: React.FC = () => {
+  const queryClient = useQueryClient()
+  const [importId, setImportId] = useState<string | null>(null)
+  const [progress, setProgress] = useState<OpmlImportProgress | null>(null)
+  const [failures, setFailures] = useState<OpmlImportProgress[]>([])
+  const [errorMsg, setErrorMsg] = useState('')
+  const [includeDeleted, setIncludeDeleted] = useState(false)
+  useEffect(() => {
+    if (importId === null) return
+    const listenProgress = listen<OpmlImportProgress>('opml-import-progress', event => {
+      if (event.payload.importId !== importId) return
+      setProgress(event.payload)
+      if (event.payload.error !== null) {
+        setFailures(current => [...current, event.payload])
+      }
+      if (event.payload.completed === event.payload.total) {
+        setImportId(null)
+        queryClient.invalidateQueries({ queryKey: ['allPodcasts'] })
+        queryClient.invalidateQueries({ queryKey: ['podcastStats'] })
+      }
+    })
+    return () => {
+      listenProgress.then(unlisten => unlisten())
+    }
+  }, [importId, queryClient])
+  const handleFile = useCallback(async (e: React.ChangeEvent<HTMLInputElement>) => {
+    const file = e.currentTarget.files?.[0]
+    e.currentTarget.value = ''
+    if (file === undefined) return
+    setErrorMsg('')
+    setProgress(null)
+    setFailures([])
+    try {
+      setImportId(await podcastApi.importOpml(await file.text()))
+    } catch (error) {
+      setErrorMsg((error as any).toString())
+    }
+  }, [])
+  const handleExport = useCallback(async () => {
+    const content = await podcastApi.exportOpml(includeDeleted)
+    const link = document.createElement('a')
+    link.href = URL.createObjectURL(new Blob([content], { type: 'text/x-opml' }))
+    link.download = 'dimppl.opml'
+    link.click()
+    URL.revokeObjectURL(link.href)
+  }, [includeDeleted])
+  return (
+    <PanelContainer>
+      <div className="button-container">
+        <label>
+          <span>Importar OPML </span>
+          <input type="file" accept=".opml,.xml" disabled={importId !== null} onChange={handleFile}/>
+        </label>
+      </div>
+      <div className="button-container">
+        <PrettyButton type="button" onClick={handleExport}>Exportar OPML</PrettyButton>
+        <label>
+          <input type="checkbox" checked={includeDeleted} onChange={e => setIncludeDeleted(e.currentTarget.checked)}/>
+          <span> Incluir podcasts excluídos (comentados)</span>
+        </label>
+      </div>
+      {errorMsg !== '' && <p>{errorMsg}</p>}
+      {progress !== null && (
+        <p>
+          {progress.completed < progress.total ? 'Importando' : 'Importados'} {progress.completed} de {progress.total} feeds
+        </p>
+      )}
+      {failures.length > 0 && (
+        <details>
+          <summary>{failures.length} feeds não puderam ser importados</summary>
+          <ul>
+            {failures.map(it => (
+              <li key={it.url}>{it.title} ({it.url}): {it.error}</li>
+            ))}
+          </ul>
+        </details>
+      )}
+    </PanelContainer>
+  )
+}
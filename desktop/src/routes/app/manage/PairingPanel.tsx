@@ -0,0 +1,62 @@
+import React, { useCallback, useState } from 'react'
+import styled from 'styled-components'
+import { configApi, PairingCode } from '../../../backend/configApi.ts'
+import { PrettyButton } from '../../../components/PrettyButton.tsx'
+
+const PanelContainer = styled.div`
+  padding: 8px;
+  border-bottom: 2px solid var(--gray12);
+  display: flex;
+  flex-direction: column;
+  gap: 8px;
+
+  .code {
+    font-size: 200%;
+    font-family: monospace;
+    letter-spacing: 4px;
+    -webkit-user-select: text;
+  }
+
+  .payload {
+    font-size: 80%;
+    -webkit-user-select: text;
+  }
+`
+
+// the server sends UTC times without a zone
+const expiryTime = (pairingCode: PairingCode) =>
+  new Date(`${pairingCode.expiresAt}Z`).toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' })
+
+export const PairingPanel: React.FC = () => {
+  const [pairingCode, setPairingCode] = useState<PairingCode | null>(null)
+  const [loading, setLoading] = useState(false)
+  const [errorMsg, setErrorMsg] = useState('')
+  const create = useCallback(async () => {
+    setLoading(true)
+    setErrorMsg('')
+    try {
+      setPairingCode(await configApi.createPairingCode())
+    } catch (error) {
+      setErrorMsg((error as any).toString())
+    }
+    setLoading(false)
+  }, [])
+  return (
+    <PanelContainer>
+      <div>
+        <PrettyButton type="button" disabled={loading} onClick={create}>Parear novo dispositivo</PrettyButton>
+      </div>
+      {errorMsg !== '' && <p>{errorMsg}</p>}
+      {pairingCode !== null && (
+        <>
+          <span>
+            Digite este código no novo dispositivo, na opção de parear com outro dispositivo. Ele vale até
+            às {expiryTime(pairingCode)}.
+          </span>
+          <span className="code">{pairingCode.code.slice(0, 3)} {pairingCode.code.slice(3)}</span>
+          <span className="payload">{pairingCode.qrPayload}</span>
+        </>
+      )}
+    </PanelContainer>
+  )
+}
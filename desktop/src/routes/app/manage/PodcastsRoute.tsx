@@ -1,11 +1,13 @@
 import React, { useCallback, useEffect, useRef, useState } from 'react'
 import { NoScrollContainer, SettingsToolbar } from './shared.tsx'
 import { useQuery } from '@tanstack/react-query'
-import { podcastApi, PodcastSyncError, PodcastWithStats } from '../../../backend/podcastApi.ts'
+import { FeedMove, podcastApi, PodcastSyncError, PodcastWithStats } from '../../../backend/podcastApi.ts'
 import styled from 'styled-components'
 import { formatDate } from '../../../timeUtil.ts'
 import { PrettyButton } from '../../../components/PrettyButton.tsx'
 import { listen } from '@tauri-apps/api/event'
+import { OpmlPanel } from './OpmlPanel.tsx'
+import { RefreshSettingsPanel } from './RefreshSettingsPanel.tsx'
 
 const ListContainer = styled.div`
   padding-top: 16px;
@@ -20,6 +22,12 @@ const ListContainer = styled.div`
   margin-bottom: 48px;
 `
 
+const moveReasons: Record<FeedMove['reason'], string> = {
+  permanent_redirect: 'redirecionamento permanente',
+  new_feed_url: 'novo endereço indicado pelo feed',
+  sync: 'sincronizado de outro dispositivo'
+}
+
 const PodcastContainer = styled.div`
   padding: 0 8px 8px;
   border-bottom: 2px solid var(--gray12);
@@ -53,21 +61,28 @@ const PodcastStatsContainer: React.FC<{ podcastWithStats: PodcastWithStats }> =
   const detailsRef = useRef<HTMLDetailsElement>(null)
   const formRef = useRef<HTMLFormElement>(null)
   const [url, setUrl] = useState(item.podcast.feedUrl)
+  const [username, setUsername] = useState(item.podcast.authUsername ?? '')
+  const [password, setPassword] = useState('')
   const [loading, setLoading] = useState(false)
   const handleClose = useCallback(() => {
     if (!detailsRef.current?.open) {
       setUrl(item.podcast.feedUrl)
+      setUsername(item.podcast.authUsername ?? '')
+      setPassword('')
     }
-  }, [item.podcast.feedUrl])
+  }, [item.podcast.feedUrl, item.podcast.authUsername])
   const handleSubmit = useCallback(async () => {
     // TODO: actually only sync this podcast, listen for sync errors, and report to user
     const request = {
       id: item.podcast.id,
-      url
+      url,
+      username,
+      // an empty password field keeps the stored one
+      password: password !== '' ? password : undefined
     }
     setLoading(true)
     return podcastApi.updatePodcast(request)
-  }, [url, item])
+  }, [url, username, password, item])
   useEffect(() => {
     const listenSyncStop = listen('sync-podcast-stop', event => {
       const id = event.payload as number
@@ -83,7 +98,8 @@ const PodcastStatsContainer: React.FC<{ podcastWithStats: PodcastWithStats }> =
     const listenSyncStop = listen('sync-podcast-error', event => {
       const err = event.payload as PodcastSyncError
       if (err.id === item.podcast.id) {
-        alert(`Erro ao sincronizar: ${err.error}`)
+        const details = err.warnings.map(it => `\n- ${it.title ?? it.guid ?? `#${it.itemIndex}`}: ${it.message}`)
+        alert(`Erro ao sincronizar: ${err.error}${details.join('')}`)
         setLoading(false)
       }
     })
@@ -91,6 +107,19 @@ const PodcastStatsContainer: React.FC<{ podcastWithStats: PodcastWithStats }> =
       listenSyncStop.then(unlisten => unlisten())
     }
   }, [item.podcast.id])
+  const warnings = useQuery({
+    queryKey: [`podcast-${item.podcast.id}`, 'feedWarnings'],
+    queryFn: () => podcastApi.listFeedWarnings(item.podcast.id)
+  })
+  const health = useQuery({
+    queryKey: ['podcastStats', 'feedHealth'],
+    queryFn: podcastApi.listFeedHealth,
+    select: data => data.find(it => it.podcastId === item.podcast.id)
+  })
+  const moves = useQuery({
+    queryKey: [`podcast-${item.podcast.id}`, 'feedMoves'],
+    queryFn: () => podcastApi.listFeedMoves(item.podcast.id)
+  })
   const deletePodcast = useCallback(async () => {
     setLoading(true)
     return await podcastApi.deletePodcast(item.podcast.id)
@@ -100,6 +129,37 @@ const PodcastStatsContainer: React.FC<{ podcastWithStats: PodcastWithStats }> =
     <h2>{item.podcast.name}</h2>
     <p>{item.totalEpisodes} episódios &bull; Atualizado em {formatDate(item.latestEpDate)}</p>
     {item.lastListenedAt !== null && (<p>Ouvido em {formatDate(item.lastListenedAt)}</p>)}
+    {health.data !== undefined && health.data.consecutiveFailures > 0 && (
+      <details>
+        <summary>
+          {health.data.consecutiveFailures} {health.data.consecutiveFailures === 1 ? 'falha' : 'falhas'} seguidas ao atualizar
+          {health.data.httpStatus !== null && ` (HTTP ${health.data.httpStatus})`}
+        </summary>
+        <p>{health.data.lastError}</p>
+        {health.data.lastSuccessAt !== null && (<p>Última atualização bem-sucedida em {formatDate(health.data.lastSuccessAt)}</p>)}
+        {health.data.nextAttemptAt !== null && (<p>Próxima tentativa automática em {formatDate(health.data.nextAttemptAt)}</p>)}
+      </details>
+    )}
+    {(warnings.data?.length ?? 0) > 0 && (
+      <details>
+        <summary>{warnings.data!.length} itens do feed ignorados</summary>
+        <ul>
+          {warnings.data!.map(it => (
+            <li key={it.id}>{it.title ?? it.guid ?? `#${it.itemIndex}`}: {it.message}</li>
+          ))}
+        </ul>
+      </details>
+    )}
+    {(moves.data?.length ?? 0) > 0 && (
+      <details>
+        <summary>Feed mudou de endereço {moves.data!.length} {moves.data!.length === 1 ? 'vez' : 'vezes'}</summary>
+        <ul>
+          {moves.data!.map(it => (
+            <li key={it.id}>{formatDate(it.createdAt)}: {it.oldUrl} &rarr; {it.newUrl} ({moveReasons[it.reason]})</li>
+          ))}
+        </ul>
+      </details>
+    )}
     <details ref={detailsRef} onToggle={handleClose}>
       <summary>Editar</summary>
       <form onSubmit={handleSubmit} ref={formRef}>
@@ -107,6 +167,14 @@ const PodcastStatsContainer: React.FC<{ podcastWithStats: PodcastWithStats }> =
           <span>URL ({url})</span>
           <input disabled={loading} type="url" value={url} onChange={e => setUrl(e.currentTarget.value)} required />
         </label>
+        <label>
+          <span>Usuário (feeds privados)</span>
+          <input disabled={loading} type="text" autoComplete="off" value={username} onChange={e => setUsername(e.currentTarget.value)} />
+        </label>
+        <label>
+          <span>Senha {item.podcast.authUsername !== null && '(deixe em branco para manter a atual)'}</span>
+          <input disabled={loading || username === ''} type="password" autoComplete="off" value={password} onChange={e => setPassword(e.currentTarget.value)} />
+        </label>
         <div className="button-container">
           <PrettyButton type="submit" disabled={!url.trim() || loading}>
             Salvar
@@ -128,6 +196,8 @@ export const PodcastsRoute: React.FC = () => {
   return (
     <NoScrollContainer>
       <SettingsToolbar/>
+      <RefreshSettingsPanel/>
+      <OpmlPanel/>
       <ListContainer>
         {queryItems.map(item => <PodcastStatsContainer key={item.podcast.id} podcastWithStats={item} />)}
       </ListContainer>
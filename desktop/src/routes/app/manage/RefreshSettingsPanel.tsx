@@ -0,0 +1,61 @@
+import React, { useCallback } from 'react'
+import { useQuery, useQueryClient } from '@tanstack/react-query'
+import styled from 'styled-components'
+import { Config, configApi } from '../../../backend/configApi.ts'
+
+const PanelContainer = styled.div`
+  padding: 8px;
+  border-bottom: 2px solid var(--gray12);
+  display: flex;
+  align-items: center;
+  gap: 16px;
+
+  input[type=number] {
+    width: 60px;
+  }
+`
+
+// the backend caps it at the same value, MAX_REFRESH_CONCURRENCY in config.rs
+const MAX_REFRESH_CONCURRENCY = 5
+
+export const RefreshSettingsPanel: React.FC = () => {
+  const queryClient = useQueryClient()
+  const config = useQuery({ queryKey: ['config'], queryFn: configApi.load })
+  const save = useCallback(async (changes: Partial<Config>) => {
+    if (config.data === undefined) return
+    await configApi.save({ ...config.data, ...changes })
+    await queryClient.invalidateQueries({ queryKey: ['config'] })
+  }, [config.data, queryClient])
+  if (config.data === undefined) return null
+  return (
+    <PanelContainer>
+      <label>
+        <input type="checkbox" checked={config.data.refreshPaused}
+               onChange={e => save({ refreshPaused: e.currentTarget.checked })}/>
+        <span> Pausar atualizações automáticas</span>
+      </label>
+      <label>
+        <span>Atualizar no máximo a cada </span>
+        <input type="number" min={1} disabled={config.data.refreshPaused}
+               defaultValue={config.data.refreshIntervalMinutes}
+               onBlur={e => {
+                 const minutes = parseInt(e.currentTarget.value)
+                 if (minutes > 0) save({ refreshIntervalMinutes: minutes })
+               }}/>
+        <span> minutos</span>
+      </label>
+      <label>
+        <span>Atualizar até </span>
+        <input type="number" min={1} max={MAX_REFRESH_CONCURRENCY}
+               defaultValue={config.data.refreshConcurrency}
+               onBlur={e => {
+                 const feeds = Math.min(parseInt(e.currentTarget.value), MAX_REFRESH_CONCURRENCY)
+                 if (!(feeds > 0)) return
+                 e.currentTarget.value = feeds.toString()
+                 save({ refreshConcurrency: feeds })
+               }}/>
+        <span> feeds ao mesmo tempo</span>
+      </label>
+    </PanelContainer>
+  )
+}
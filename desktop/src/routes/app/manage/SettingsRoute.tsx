@@ -3,12 +3,14 @@ import { onboardingUserAccountRoute, settingsRoute } from '../../../routeDefinit
 import { Link } from '@tanstack/react-router'
 import { CoolTable, NoScrollContainer, SettingsToolbar, TableContainer } from './shared.tsx'
 import { Config } from '../../../backend/configApi.ts'
+import { PairingPanel } from './PairingPanel.tsx'
 
 export const SettingsRoute: React.FC = () => {
   const config = settingsRoute.useLoaderData()
   return (
     <NoScrollContainer>
       <SettingsToolbar/>
+      <PairingPanel/>
       <TableContainer>
         <CoolTable>
           <thead>
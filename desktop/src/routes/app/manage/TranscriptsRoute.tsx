@@ -0,0 +1,69 @@
+import React, { useState } from 'react'
+import styled from 'styled-components'
+import { CoolTable, NoScrollContainer, SettingsToolbar, TableContainer } from './shared.tsx'
+import { useQuery } from '@tanstack/react-query'
+import { podcastApi } from '../../../backend/podcastApi.ts'
+import { formatDate, formatHms } from '../../../timeUtil.ts'
+import { IconButton } from '../IconButton.tsx'
+
+const SearchForm = styled.form`
+  padding: 8px 8px 0;
+
+  & input {
+    width: 100%;
+    padding: 4px;
+  }
+`
+
+export const TranscriptsRoute: React.FC = () => {
+  const [text, setText] = useState('')
+  const [searchText, setSearchText] = useState('')
+  const query = useQuery({
+    queryKey: ['transcriptSearch', searchText],
+    queryFn: () => podcastApi.searchTranscripts(searchText)
+  })
+  return (
+    <NoScrollContainer>
+      <SettingsToolbar/>
+      <SearchForm onSubmit={e => {
+        e.preventDefault()
+        setSearchText(text.trim())
+      }}>
+        <input type="search" placeholder="Buscar nas transcrições baixadas" value={text}
+               onChange={e => setText(e.currentTarget.value)}/>
+      </SearchForm>
+      <TableContainer>
+        <CoolTable>
+          <thead>
+          <tr>
+            <th>Episódio</th>
+            <th>Trecho</th>
+            <th>Texto</th>
+            <th></th>
+          </tr>
+          </thead>
+          <tbody>
+          {query.data?.map(hit => (
+            <tr key={hit.segment.id} onDoubleClick={() => podcastApi.playTranscriptSegment(hit.segment.id)}>
+              <td>
+                <strong>{hit.episode.title}</strong>
+                <br/>
+                {hit.podcast.name} &bull; {formatDate(hit.episode.episodeDate)}
+              </td>
+              <td className="tiny">{formatHms(Math.floor(hit.segment.startSeconds))}</td>
+              <td className="selectable">
+                {hit.segment.speaker !== '' && <strong>{hit.segment.speaker}: </strong>}
+                {hit.segment.body}
+              </td>
+              <td>
+                <IconButton icon="play_circle" title="Tocar"
+                            onClick={() => podcastApi.playTranscriptSegment(hit.segment.id)}/>
+              </td>
+            </tr>
+          ))}
+          </tbody>
+        </CoolTable>
+      </TableContainer>
+    </NoScrollContainer>
+  )
+}
@@ -86,6 +86,43 @@ const EpisodeControls = styled.div`
   justify-content: space-between;
 `
 
+const EpisodeBadges = styled.div`
+  display: flex;
+  gap: 4px;
+  font-size: 10px;
+  flex-shrink: 0;
+
+  span {
+    border: 1px solid var(--gray12);
+    border-radius: 3px;
+    padding: 0 4px;
+  }
+`
+
+const episodeTypeLabels: Record<Episode['episodeType'], string | null> = {
+  full: null,
+  trailer: 'Trailer',
+  bonus: 'Bônus'
+}
+
+const EpisodeNumbering: React.FC<{ episode: Episode }> = ({ episode }) => {
+  const numbering = [
+    episode.season !== null ? `T${episode.season}` : null,
+    episode.episodeNumber !== null ? `E${episode.episodeNumber}` : null
+  ].filter(it => it !== null).join(' ')
+  const typeLabel = episodeTypeLabels[episode.episodeType]
+  if (numbering === '' && typeLabel === null && episode.explicit !== true) {
+    return null
+  }
+  return (
+    <EpisodeBadges>
+      {numbering !== '' && <span>{numbering}</span>}
+      {typeLabel !== null && <span>{typeLabel}</span>}
+      {episode.explicit === true && <span title="Conteúdo explícito">E</span>}
+    </EpisodeBadges>
+  )
+}
+
 const DateDisplay = styled.div`
   font-size: 11px;
   line-height: 1.4;
@@ -122,12 +159,13 @@ export const EpisodeListItem: React.FC<{
       contextMenu.podcastEpisode(episode.id)
       e.preventDefault()
     }}>
-      <EpisodeImageBox url={podcastUtil.episodeImage(episode, podcast)}/>
+      <EpisodeImageBox url={podcastUtil.episodeImage(episode, podcast, 150)}/>
       <EpisodeInfoBox>
         <EpisodeLink to={episodeRoute.to} search={{}} params={{ episodeId: episode.id.toString() }}
                      title={episode.title}>
           {episode.title}
         </EpisodeLink>
+        <EpisodeNumbering episode={episode}/>
         {showPodcastName && (
           <EpisodeLink className="notbold" to={podcastRoute.to} params={{ podcastId: podcast.id.toString() }}
                        title={podcast.name}>
@@ -136,6 +174,7 @@ export const EpisodeListItem: React.FC<{
         )}
         <div style={{ marginTop: 10, display: 'flex', height: showPodcastName ? 100 : 120 }}>
           <EpisodeDescription className={showPodcastName ? 'small' : ''}
+                              onClick={podcastUtil.seekPointClickHandler(episode)}
                               dangerouslySetInnerHTML={{ __html: episode.description }}/>
           <EpisodeControls>
             <div style={{ display: 'flex', alignItems: 'center', justifyContent: 'space-between' }}>
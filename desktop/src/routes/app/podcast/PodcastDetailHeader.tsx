@@ -39,7 +39,7 @@ export const PodcastDetailHeader: React.FC<{ podcast: Podcast }> = ({ podcast })
     <WrapperDiv>
       <BackButton/>
       <div style={{ display: 'flex', gap: 24 }}>
-        <BigImage url={podcastUtil.imageUrl(podcast)} />
+        <BigImage url={podcastUtil.imageUrl(podcast, 200)} />
         <DescriptionWrapper>
           <Title>{podcast.name}</Title>
           <Subtitle>{podcast.author}</Subtitle>
@@ -1,28 +1,70 @@
-import React from 'react'
+import React, { useState } from 'react'
 import { Podcast, podcastApi } from '../../../backend/podcastApi.ts'
 import { useQuery } from '@tanstack/react-query'
 import { EpisodeListItem } from './EpisodeListItem.tsx'
 import { Virtuoso } from 'react-virtuoso'
+import styled from 'styled-components'
+
+const FilterBar = styled.div`
+  display: flex;
+  gap: 16px;
+  align-items: center;
+  padding: 0 16px;
+  font-size: 90%;
+
+  label {
+    display: flex;
+    gap: 4px;
+    align-items: center;
+  }
+`
 
 export const PodcastEpisodesList: React.FC<{ podcast: Podcast }> = ({ podcast }) => {
+  const [season, setSeason] = useState<number | undefined>(undefined)
+  const [hideTrailers, setHideTrailers] = useState(false)
+  const seasons = useQuery({
+    queryKey: [`podcastEpisodes-${podcast.id}`, 'seasons'],
+    queryFn: () => podcastApi.listPodcastSeasons(podcast.id),
+    initialData: []
+  })
   const query = useQuery({
-    queryKey: [`podcastEpisodes-${podcast.id}`],
-    queryFn: () => podcastApi.listEpisodes(podcast.id),
+    queryKey: [`podcastEpisodes-${podcast.id}`, season, hideTrailers],
+    queryFn: () => podcastApi.listEpisodes(podcast.id, { season, hideTrailers }),
     initialData: []
   })
   return (
-    <div style={{ flex: '1', overflow: 'auto', position: 'relative' }}>
-      <Virtuoso
-        totalCount={query.data.length}
-        itemContent={(index) => (
-          <EpisodeListItem
-            episode={query.data[index].episode}
-            podcast={podcast}
-            progress={query.data[index].progress}
-            showPodcastName={false}
-          />
+    <>
+      <FilterBar>
+        {seasons.data.length > 0 && (
+          <label>
+            <span>Temporada</span>
+            <select
+              value={season ?? ''}
+              onChange={e => setSeason(e.currentTarget.value === '' ? undefined : Number(e.currentTarget.value))}
+            >
+              <option value="">Todas</option>
+              {seasons.data.map(it => <option key={it} value={it}>{it}</option>)}
+            </select>
+          </label>
         )}
-      />
-    </div>
+        <label>
+          <input type="checkbox" checked={hideTrailers} onChange={e => setHideTrailers(e.currentTarget.checked)}/>
+          <span>Ocultar trailers</span>
+        </label>
+      </FilterBar>
+      <div style={{ flex: '1', overflow: 'auto', position: 'relative' }}>
+        <Virtuoso
+          totalCount={query.data.length}
+          itemContent={(index) => (
+            <EpisodeListItem
+              episode={query.data[index].episode}
+              podcast={podcast}
+              progress={query.data[index].progress}
+              showPodcastName={false}
+            />
+          )}
+        />
+      </div>
+    </>
   )
 }
@@ -0,0 +1,111 @@
+import React, { useCallback } from 'react'
+import { useQuery, useQueryClient } from '@tanstack/react-query'
+import styled from 'styled-components'
+import {
+  AutoDownloadPolicy,
+  EpisodeSortOrder,
+  Podcast,
+  podcastApi,
+  PodcastSettingsUpdateRequest
+} from '../../../backend/podcastApi.ts'
+
+const PanelContainer = styled.details`
+  padding: 0 16px;
+  font-size: 90%;
+
+  .settings {
+    display: flex;
+    flex-wrap: wrap;
+    gap: 16px;
+    align-items: center;
+    padding-top: 8px;
+  }
+
+  label {
+    display: flex;
+    gap: 4px;
+    align-items: center;
+  }
+
+  input[type=number] {
+    width: 60px;
+  }
+`
+
+const PLAYBACK_SPEEDS = [0.75, 1, 1.25, 1.5, 1.75, 2]
+
+export const PodcastSettingsPanel: React.FC<{ podcast: Podcast }> = ({ podcast }) => {
+  const queryClient = useQueryClient()
+  const settings = useQuery({
+    queryKey: [`podcast-${podcast.id}`, 'settings'],
+    queryFn: () => podcastApi.getPodcastSettings(podcast.id)
+  })
+  const save = useCallback(async (changes: Partial<PodcastSettingsUpdateRequest>) => {
+    if (settings.data === undefined) return
+    const { playbackSpeed, skipIntroSeconds, autoDownload, sortOrder, notifications } = settings.data
+    await podcastApi.updatePodcastSettings({
+      podcastId: podcast.id,
+      playbackSpeed,
+      skipIntroSeconds,
+      autoDownload,
+      sortOrder,
+      notifications,
+      ...changes
+    })
+    await queryClient.invalidateQueries({ queryKey: [`podcast-${podcast.id}`] })
+  }, [podcast.id, settings.data, queryClient])
+  if (settings.data === undefined) return null
+  return (
+    <PanelContainer>
+      <summary>Ajustes deste podcast</summary>
+      <div className="settings">
+        <label>
+          <span>Velocidade</span>
+          <select
+            value={settings.data.playbackSpeed ?? ''}
+            onChange={e => save({ playbackSpeed: e.currentTarget.value === '' ? null : Number(e.currentTarget.value) })}
+          >
+            <option value="">Padrão</option>
+            {PLAYBACK_SPEEDS.map(it => <option key={it} value={it}>{it}x</option>)}
+          </select>
+        </label>
+        <label>
+          <span>Pular os primeiros</span>
+          <input type="number" min={0} key={settings.data.skipIntroSeconds}
+                 defaultValue={settings.data.skipIntroSeconds}
+                 onBlur={e => {
+                   const seconds = parseInt(e.currentTarget.value)
+                   if (seconds >= 0) save({ skipIntroSeconds: seconds })
+                 }}/>
+          <span>segundos</span>
+        </label>
+        <label>
+          <span>Baixar automaticamente</span>
+          <select
+            value={settings.data.autoDownload}
+            onChange={e => save({ autoDownload: e.currentTarget.value as AutoDownloadPolicy })}
+          >
+            <option value="off">Nenhum episódio</option>
+            <option value="latest">O episódio mais recente</option>
+            <option value="all">Todos os episódios novos</option>
+          </select>
+        </label>
+        <label>
+          <span>Ordem</span>
+          <select
+            value={settings.data.sortOrder}
+            onChange={e => save({ sortOrder: e.currentTarget.value as EpisodeSortOrder })}
+          >
+            <option value="newest_first">Mais recentes primeiro</option>
+            <option value="oldest_first">Mais antigos primeiro</option>
+          </select>
+        </label>
+        <label>
+          <input type="checkbox" checked={settings.data.notifications}
+                 onChange={e => save({ notifications: e.currentTarget.checked })}/>
+          <span>Notificar novos episódios</span>
+        </label>
+      </div>
+    </PanelContainer>
+  )
+}
@@ -1,25 +1,62 @@
-import React, { useState } from 'react'
+import React, { useEffect, useState } from 'react'
 import { useMutation, useQueryClient } from '@tanstack/react-query'
-import { podcastApi } from '../../../backend/podcastApi.ts'
+import { FeedCandidate, podcastApi } from '../../../backend/podcastApi.ts'
 import { Modal } from '../../../components/Modal.tsx'
 import { ToolbarButton } from '../ToolbarButton.tsx'
+import { listen } from '@tauri-apps/api/event'
+import { PodcastSearch } from './PodcastSearch.tsx'
 
 export const ImportPodcastButton: React.FC = () => {
   const [open, setOpen] = useState(false)
   const [podcastUrl, setPodcastUrl] = useState('')
   const [errorMsg, setErrorMsg] = useState('')
+  const [importId, setImportId] = useState<string | null>(null)
+  const [candidates, setCandidates] = useState<FeedCandidate[] | null>(null)
   const queryClient = useQueryClient()
   const mutation = useMutation({
     mutationFn: podcastApi.importPodcast,
-    onSuccess: () => {
-      setPodcastUrl('')
-      setOpen(false)
-      queryClient.invalidateQueries({ queryKey: ['allPodcasts'] })
+    onMutate: () => {
+      setErrorMsg('')
+      setCandidates(null)
+    },
+    onSuccess: id => {
+      setImportId(id)
     },
     onError: error => {
       setErrorMsg((error as any).toString())
     }
   })
+  useEffect(() => {
+    if (importId === null) return
+    const listenDone = listen<string>('import-podcast-done', event => {
+      if (event.payload === importId) {
+        setImportId(null)
+        setPodcastUrl('')
+        setOpen(false)
+        queryClient.invalidateQueries({ queryKey: ['allPodcasts'] })
+      }
+    })
+    const listenCandidates = listen<[string, FeedCandidate[]]>('import-podcast-candidates', event => {
+      const [id, found] = event.payload
+      if (id === importId) {
+        setImportId(null)
+        setCandidates(found)
+      }
+    })
+    const listenError = listen<[string, string]>('import-podcast-error', event => {
+      const [id, error] = event.payload
+      if (id === importId) {
+        setImportId(null)
+        setErrorMsg(error)
+      }
+    })
+    return () => {
+      listenDone.then(unlisten => unlisten())
+      listenCandidates.then(unlisten => unlisten())
+      listenError.then(unlisten => unlisten())
+    }
+  }, [importId, queryClient])
+  const importing = mutation.isLoading || importId !== null
   return (
     <>
       <ToolbarButton type="button" onClick={() => setOpen(true)}>
@@ -30,14 +67,30 @@ export const ImportPodcastButton: React.FC = () => {
         onClose={() => setOpen(false)}
       >
         <div>
-          <p>Insira a URL do feed RSS do podcast.</p>
+          <p>Insira a URL do feed RSS ou do site do podcast.</p>
           <input type="text" value={podcastUrl} onChange={(e) => setPodcastUrl(e.target.value)}/>
           <p>{errorMsg}</p>
+          {candidates !== null && candidates.length === 0 && (
+            <p>Nenhum feed encontrado nesta página.</p>
+          )}
+          {candidates !== null && candidates.length > 0 && (
+            <div>
+              <p>Esta página tem mais de um feed. Escolha qual adicionar:</p>
+              {candidates.map(candidate => (
+                <button key={candidate.url} type="button" disabled={importing} title={candidate.url}
+                        onClick={() => mutation.mutate(candidate.url)}
+                        style={{ display: 'block', marginBottom: '4px' }}>
+                  {candidate.title || candidate.url}
+                </button>
+              ))}
+            </div>
+          )}
+          <PodcastSearch disabled={importing} onSelect={url => mutation.mutate(url)}/>
           <div style={{ display: 'flex' }}>
             <button type="button" onClick={() => setOpen(false)} style={{ marginLeft: 'auto' }}>
               Cancelar
             </button>
-            <button type="button" disabled={mutation.isLoading} onClick={() => mutation.mutate(podcastUrl)}
+            <button type="button" disabled={importing} onClick={() => mutation.mutate(podcastUrl)}
                     style={{ marginLeft: 'auto' }}>
               Adicionar
             </button>
@@ -0,0 +1,72 @@
+import React, { useState } from 'react'
+import { useQuery } from '@tanstack/react-query'
+import styled from 'styled-components'
+import { podcastApi } from '../../../backend/podcastApi.ts'
+
+const ResultList = styled.div`
+  max-height: 300px;
+  overflow-y: auto;
+`
+
+const ResultButton = styled.button`
+  display: flex;
+  align-items: center;
+  gap: 8px;
+  width: 100%;
+  margin-bottom: 4px;
+  text-align: left;
+
+  img {
+    width: 40px;
+    height: 40px;
+    object-fit: cover;
+    flex-shrink: 0;
+  }
+
+  small {
+    display: block;
+    opacity: 0.7;
+  }
+`
+
+export const PodcastSearch: React.FC<{
+  disabled: boolean
+  onSelect: (feedUrl: string) => void
+}> = ({ disabled, onSelect }) => {
+  const [term, setTerm] = useState('')
+  const [submitted, setSubmitted] = useState('')
+  const results = useQuery({
+    queryKey: ['podcastSearch', submitted],
+    queryFn: () => podcastApi.searchPodcasts(submitted),
+    enabled: submitted !== ''
+  })
+  return (
+    <div>
+      <p>Ou busque pelo nome:</p>
+      <form onSubmit={e => {
+        e.preventDefault()
+        setSubmitted(term.trim())
+      }}>
+        <input type="text" value={term} onChange={e => setTerm(e.target.value)}/>
+        <button type="submit">Buscar</button>
+      </form>
+      {results.isFetching && <p>Buscando...</p>}
+      {results.isError && <p>{(results.error as any).toString()}</p>}
+      {results.isSuccess && !results.isFetching && results.data.length === 0 && <p>Nenhum podcast encontrado.</p>}
+      {results.isSuccess && (
+        <ResultList>
+          {results.data.map(podcast => (
+            <ResultButton key={podcast.feedUrl} type="button" disabled={disabled} title={podcast.feedUrl}
+                          onClick={() => onSelect(podcast.feedUrl)}>
+              {podcast.artworkUrl !== '' && <img src={podcast.artworkUrl} alt=""/>}
+              <span>
+                {podcast.title}
+                <small>{podcast.author}</small>
+              </span>
+            </ResultButton>
+          ))}
+        </ResultList>
+      )}
+    </div>
+  )
+}
@@ -146,7 +146,7 @@ export const Sidebar: React.FC = () => {
           <SidebarLink key={podcast.id} to={podcastRoute.to} search={{}} params={{ podcastId: podcast.id.toString() }}
                        className="sidebar-link">
             <PodcastImageDiv style={{
-              backgroundImage: `url(${podcastUtil.imageUrl(podcast)})`,
+              backgroundImage: `url(${podcastUtil.imageUrl(podcast, 25)})`,
             }}>
               {syncingPodcasts[podcast.id.toString()] && (
                 <SpinningLoader>
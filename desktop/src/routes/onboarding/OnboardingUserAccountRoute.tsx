@@ -5,14 +5,23 @@ import { RootDiv } from '../../components/RootDiv.tsx'
 import { PrettyButton } from '../../components/PrettyButton.tsx'
 import { AccessKeyGroup, RadioGroup, Title, WrapperDiv } from './components.ts'
 
+// the code itself, or the `dimppl://pair?code=…` payload another device shows
+const codeFromInput = (input: string): string => {
+  if (!input.trim().startsWith('dimppl:')) return input
+  return new URL(input.trim()).searchParams.get('code') ?? input
+}
+
 export const OnboardingUserAccountRoute: React.FC = () => {
   const [selectedOption, setSelectedOption] = useState('')
   const [existingKey, setExistingKey] = useState('')
+  const [pairingCode, setPairingCode] = useState('')
+  const [deviceName, setDeviceName] = useState('')
   const [loading, setLoading] = useState(false)
   const navigate = useNavigate({ from: '/onboarding/user_account' })
 
   useEffect(() => {
     configApi.load().then((configData) => {
+      setDeviceName(configData.deviceName)
       if (configData.userAccessKey.length > 0) {
         setSelectedOption('existing')
         setExistingKey(configData.userAccessKey)
@@ -26,6 +35,17 @@ export const OnboardingUserAccountRoute: React.FC = () => {
 
   const submit = async () => {
     setLoading(true)
+    if (selectedOption === 'pairing') {
+      try {
+        await configApi.redeemPairingCode(codeFromInput(pairingCode), deviceName)
+        navigate({ to: '/' })
+      } catch (e) {
+        console.log(e)
+        alert(e)
+      }
+      setLoading(false)
+      return
+    }
     if (selectedOption === 'new') {
       await configApi.registerNewUser()
     } else {
@@ -57,7 +77,34 @@ export const OnboardingUserAccountRoute: React.FC = () => {
                    onChange={onSelectionChange}/>
             <span>Tenho uma conta e gostaria de usá-la neste dispositivo</span>
           </label>
+          <label htmlFor="opt_pairing">
+            <input type="radio" name="account_option" id="opt_pairing" value="pairing"
+                   checked={selectedOption === 'pairing'}
+                   onChange={onSelectionChange}/>
+            <span>Tenho um código de pareamento gerado em outro dispositivo</span>
+          </label>
         </RadioGroup>
+        {selectedOption === 'pairing' && (
+          <AccessKeyGroup>
+            <label htmlFor="pairing_code">
+              Código de pareamento:
+            </label>
+            <input
+              id="pairing_code"
+              value={pairingCode}
+              placeholder="000 000"
+              onChange={(e) => setPairingCode(e.target.value)}
+            />
+            <label htmlFor="pairing_device_name">
+              Nome deste dispositivo:
+            </label>
+            <input
+              id="pairing_device_name"
+              value={deviceName}
+              onChange={(e) => setDeviceName(e.target.value)}
+            />
+          </AccessKeyGroup>
+        )}
         <AccessKeyGroup style={{ visibility: selectedOption === 'existing' ? 'visible' : 'hidden' }}>
           <label htmlFor="existing_key">
             Código de acesso da conta:
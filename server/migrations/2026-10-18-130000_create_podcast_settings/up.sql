@@ -0,0 +1,10 @@
+CREATE TABLE podcast_settings (
+    id BIGSERIAL PRIMARY KEY,
+    podcast_id BIGINT NOT NULL UNIQUE REFERENCES podcasts(id) ON DELETE CASCADE,
+    playback_speed REAL,
+    skip_intro_seconds INT NOT NULL,
+    auto_download TEXT NOT NULL,
+    sort_order TEXT NOT NULL,
+    notifications BOOLEAN NOT NULL,
+    updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL
+);
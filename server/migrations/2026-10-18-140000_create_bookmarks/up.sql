@@ -0,0 +1,13 @@
+CREATE TABLE bookmarks (
+    id BIGSERIAL PRIMARY KEY,
+    podcast_id BIGINT NOT NULL REFERENCES podcasts(id) ON DELETE CASCADE,
+    guid TEXT NOT NULL,
+    episode_guid TEXT NOT NULL,
+    start_seconds INT NOT NULL,
+    end_seconds INT,
+    note TEXT NOT NULL,
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+    deleted_at TIMESTAMP WITHOUT TIME ZONE,
+    updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+    UNIQUE (podcast_id, guid)
+);
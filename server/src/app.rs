@@ -19,11 +19,9 @@ pub fn create_app(state: AppState) -> Router {
 
 #[cfg(test)]
 pub fn create_test_app() -> (AppState, Router) {
-    if std::env::var("DATABASE_URL").is_err() {
-        std::env::set_var("DATABASE_URL", "postgres://localhost/dimppl_test");
-    }
-    std::env::set_var("DIMPPL_TEST", "true");
-    let state = AppState::new();
+    let state = AppState::with_storage(std::sync::Arc::new(
+        crate::storage::memory::MemoryStorage::default(),
+    ));
 
     (state.clone(), create_app(state))
 }
@@ -29,7 +29,10 @@ pub fn create_database_pool() -> Pool {
 }
 
 #[cfg(test)]
-pub fn establish_pg_connection() -> PgConnection {
-    let db_url = env::var("DATABASE_URL").expect("No DATABASE_URL variable set!");
-    PgConnection::establish(db_url.as_ref()).unwrap()
+pub fn create_test_pool() -> Pool {
+    if env::var("DATABASE_URL").is_err() {
+        env::set_var("DATABASE_URL", "postgres://localhost/dimppl_test");
+    }
+    env::set_var("DIMPPL_TEST", "true");
+    create_database_pool()
 }
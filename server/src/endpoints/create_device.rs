@@ -4,10 +4,10 @@ use diesel::Selectable;
 
 use serde::{Deserialize, Serialize};
 
-use crate::database::Pool;
 use crate::error_handling::AppResult;
 use crate::models::user_device::CreateDeviceRequest;
-use crate::models::{user, user_device, UserDevice};
+use crate::models::UserDevice;
+use crate::storage::DynStorage;
 
 #[derive(Serialize, Deserialize, Selectable)]
 #[diesel(table_name = crate::schema::user_devices)]
@@ -26,12 +26,13 @@ impl From<UserDevice> for CreateDeviceResponse {
 }
 
 pub async fn create_device(
-    State(pool): State<Pool>,
+    State(storage): State<DynStorage>,
     Json(create_request): Json<CreateDeviceRequest>,
 ) -> AppResult<Json<CreateDeviceResponse>> {
-    let mut conn = pool.get().await?;
-    let user = user::find_by_access_key(&create_request.user_access_key, &mut conn).await?;
-    let response = user_device::create(&create_request, &user, &mut conn).await?;
+    let user = storage
+        .find_user_by_access_key(&create_request.user_access_key)
+        .await?;
+    let response = storage.create_device(&create_request, &user).await?;
     Ok(Json(response.into()))
 }
 
@@ -42,7 +43,6 @@ mod tests {
         http,
         http::{Request, StatusCode},
     };
-    use serial_test::serial;
     use tower::ServiceExt;
 
     use crate::app::create_test_app;
@@ -51,11 +51,13 @@ mod tests {
     use super::*;
 
     #[tokio::test]
-    #[serial]
     async fn test_create_device_happy_path() {
         let (state, app) = create_test_app();
-        let mut conn = state.pool.get().await.unwrap();
-        let user = user::create(&NewUser::default(), &mut conn).await.unwrap();
+        let user = state
+            .storage
+            .create_user(&NewUser::default())
+            .await
+            .unwrap();
 
         let request_body = CreateDeviceRequest {
             user_access_key: user.access_key,
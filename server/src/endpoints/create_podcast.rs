@@ -5,9 +5,9 @@ use axum::Json;
 use axum_macros::debug_handler;
 use dimppl_shared::sync::{CreatePodcastEpisodeRequest, CreatePodcastRequest};
 
-use crate::database::Pool;
 use crate::error_handling::AppResult;
 use crate::models::user_device;
+use crate::storage::DynStorage;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -45,14 +45,13 @@ impl CreatePodcastWebRequest {
 
 #[debug_handler]
 pub async fn create_podcast(
-    State(pool): State<Pool>,
+    State(storage): State<DynStorage>,
     headers: HeaderMap,
     Json(create_request): Json<CreatePodcastWebRequest>,
 ) -> AppResult<(StatusCode, ())> {
-    let mut conn = pool.get().await?;
-    let user = user_device::user_from_http_request(&headers, &mut conn).await?;
+    let user = user_device::user_from_http_request(&headers, storage.as_ref()).await?;
     let request = create_request.into_request(user.id);
-    crate::models::podcast::create(&request, &mut conn).await?;
+    storage.create_podcast(&request).await?;
     Ok((StatusCode::CREATED, ()))
 }
 
@@ -63,26 +62,28 @@ mod tests {
         http,
         http::{Request, StatusCode},
     };
-    use serial_test::serial;
     use tower::ServiceExt;
 
     use crate::app::create_test_app;
-    use crate::models::user;
     use crate::models::user::NewUser;
 
     use super::*;
 
     #[tokio::test]
-    #[serial]
     async fn test_create_podcast_happy_path() {
         let (state, app) = create_test_app();
-        let mut conn = state.pool.get().await.unwrap();
-        let user = user::create(&NewUser::default(), &mut conn).await.unwrap();
+        let user = state
+            .storage
+            .create_user(&NewUser::default())
+            .await
+            .unwrap();
         let device_request = user_device::CreateDeviceRequest {
             device_name: "test".to_string(),
             user_access_key: user.access_key.clone(),
         };
-        let device = user_device::create(&device_request, &user, &mut conn)
+        let device = state
+            .storage
+            .create_device(&device_request, &user)
             .await
             .unwrap();
 
@@ -114,16 +115,20 @@ mod tests {
     }
 
     #[tokio::test]
-    #[serial]
     async fn test_create_podcast_authorization_error() {
         let (state, app) = create_test_app();
-        let mut conn = state.pool.get().await.unwrap();
-        let user = user::create(&NewUser::default(), &mut conn).await.unwrap();
+        let user = state
+            .storage
+            .create_user(&NewUser::default())
+            .await
+            .unwrap();
         let device_request = user_device::CreateDeviceRequest {
             device_name: "test".to_string(),
             user_access_key: user.access_key.clone(),
         };
-        let _device = user_device::create(&device_request, &user, &mut conn)
+        let _device = state
+            .storage
+            .create_device(&device_request, &user)
             .await
             .unwrap();
 
@@ -155,16 +160,20 @@ mod tests {
     }
 
     #[tokio::test]
-    #[serial]
     async fn test_create_podcast_uniqueness() {
         let (state, app) = create_test_app();
-        let mut conn = state.pool.get().await.unwrap();
-        let user = user::create(&NewUser::default(), &mut conn).await.unwrap();
+        let user = state
+            .storage
+            .create_user(&NewUser::default())
+            .await
+            .unwrap();
         let device_request = user_device::CreateDeviceRequest {
             device_name: "test".to_string(),
             user_access_key: user.access_key.clone(),
         };
-        let device = user_device::create(&device_request, &user, &mut conn)
+        let device = state
+            .storage
+            .create_device(&device_request, &user)
             .await
             .unwrap();
 
@@ -182,9 +191,10 @@ mod tests {
                 },
             ],
         };
-        let _ =
-            crate::models::podcast::create(&request_body.clone().into_request(user.id), &mut conn)
-                .await;
+        let _ = state
+            .storage
+            .create_podcast(&request_body.clone().into_request(user.id))
+            .await;
 
         let request = Request::builder()
             .method(http::Method::POST)
@@ -1,12 +1,12 @@
 use axum::extract::State;
 use axum::Json;
 
-use crate::database::Pool;
 use crate::error_handling::AppResult;
+use crate::storage::DynStorage;
 use serde::{Deserialize, Serialize};
 
 use crate::models::user::NewUser;
-use crate::models::{user, User};
+use crate::models::User;
 
 #[derive(Serialize, Deserialize)]
 pub struct CreateUserResponse {
@@ -21,10 +21,9 @@ impl From<User> for CreateUserResponse {
     }
 }
 
-pub async fn create_user(State(pool): State<Pool>) -> AppResult<Json<CreateUserResponse>> {
-    let mut conn = pool.get().await?;
+pub async fn create_user(State(storage): State<DynStorage>) -> AppResult<Json<CreateUserResponse>> {
     let new_user = NewUser::default();
-    let user = user::create(&new_user, &mut conn).await?;
+    let user = storage.create_user(&new_user).await?;
     Ok(Json(user.into()))
 }
 
@@ -37,12 +36,10 @@ mod tests {
     use tower::ServiceExt;
 
     use crate::app::create_test_app;
-    use serial_test::serial;
 
     use super::*;
 
     #[tokio::test]
-    #[serial]
     async fn test_create_user_happy_path() {
         let (_, app) = create_test_app();
 
@@ -1,6 +1,7 @@
 use crate::endpoints::create_device::create_device;
 use crate::endpoints::create_podcast::create_podcast;
 use crate::endpoints::create_user::create_user;
+use crate::endpoints::pairing_codes::{create_pairing_code, redeem_pairing_code};
 use crate::endpoints::sync_state::sync_state;
 use crate::state::AppState;
 use axum::routing::{get, post};
@@ -10,6 +11,7 @@ use crate::endpoints::submit_progress::submit_progress;
 mod create_device;
 pub mod create_podcast;
 pub mod create_user;
+mod pairing_codes;
 mod sync_state;
 pub mod websocket;
 pub mod submit_progress;
@@ -22,6 +24,8 @@ impl RouterExt for Router<AppState> {
     fn apply_app_routes(self) -> Self {
         self.route("/user", post(create_user))
             .route("/devices", post(create_device))
+            .route("/pairing_codes", post(create_pairing_code))
+            .route("/pairing_codes/redeem", post(redeem_pairing_code))
             .route("/podcasts", post(create_podcast))
             .route("/ws", get(websocket::websocket_handler))
             .route("/sync", post(sync_state))
@@ -0,0 +1,219 @@
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, State};
+use axum::headers::HeaderMap;
+use axum::Json;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::endpoints::create_device::CreateDeviceResponse;
+use crate::error_handling::{AppError, AppResult};
+use crate::models::user_device;
+use crate::pairing_throttle::PairingThrottle;
+use crate::storage::DynStorage;
+
+/// A new code may still collide with another live one.
+const CREATE_ATTEMPTS: usize = 3;
+
+#[derive(Serialize, Deserialize)]
+pub struct CreatePairingCodeResponse {
+    pub code: String,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RedeemPairingCodeRequest {
+    pub code: String,
+    pub device_name: String,
+}
+
+pub async fn create_pairing_code(
+    State(storage): State<DynStorage>,
+    headers: HeaderMap,
+) -> AppResult<Json<CreatePairingCodeResponse>> {
+    let user = user_device::user_from_http_request(&headers, storage.as_ref()).await?;
+    let mut attempt = 1;
+    let pairing_code = loop {
+        match storage.create_pairing_code(&user).await {
+            Ok(pairing_code) => break pairing_code,
+            Err(e) if attempt < CREATE_ATTEMPTS && e.is_unique_violation() => {
+                tracing::debug!("Retrying pairing code creation: {e}")
+            }
+            Err(e) => return Err(e),
+        }
+        attempt += 1;
+    };
+    Ok(Json(CreatePairingCodeResponse {
+        code: pairing_code.code,
+        expires_at: pairing_code.expires_at,
+    }))
+}
+
+/// Needs no authentication, so failed attempts are throttled per client address and overall.
+pub async fn redeem_pairing_code(
+    State(storage): State<DynStorage>,
+    State(throttle): State<PairingThrottle>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(request): Json<RedeemPairingCodeRequest>,
+) -> AppResult<Json<CreateDeviceResponse>> {
+    let peer = connect_info.map(|ConnectInfo(address)| address.ip());
+    let client = throttle.client(&headers, peer);
+    throttle.check(client)?;
+    let redeemed = storage
+        .redeem_pairing_code(&request.code, &request.device_name)
+        .await
+        .map_err(|e| {
+            if e.is_unique_violation() {
+                AppError::conflict("A device with this name already exists")
+            } else {
+                e
+            }
+        })?;
+    let Some(device) = redeemed else {
+        throttle.record_failure(client);
+        return Err(AppError::unauthorized());
+    };
+    Ok(Json(device.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    use axum::Router;
+
+    use crate::app::create_test_app;
+    use crate::fixtures::test_user_and_device;
+    use crate::models::pairing_code::CODE_LENGTH;
+    use crate::models::UserDevice;
+
+    use super::*;
+
+    fn redeem_request(code: &str) -> Request<Body> {
+        redeem_request_named(code, "paired device")
+    }
+
+    fn redeem_request_named(code: &str, device_name: &str) -> Request<Body> {
+        let request_body = RedeemPairingCodeRequest {
+            code: code.into(),
+            device_name: device_name.into(),
+        };
+        Request::builder()
+            .method("POST")
+            .uri("/pairing_codes/redeem")
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(serde_json::to_vec(&request_body).unwrap()))
+            .unwrap()
+    }
+
+    async fn create_code(app: &Router, device: &UserDevice) -> String {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/pairing_codes")
+            .header("Authorization", format!("Bearer {}", device.access_token))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        serde_json::from_slice::<CreatePairingCodeResponse>(&body)
+            .unwrap()
+            .code
+    }
+
+    #[tokio::test]
+    async fn test_pairing_flow() {
+        let (state, app) = create_test_app();
+        let (user, device) = test_user_and_device(state.storage.as_ref()).await.unwrap();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/pairing_codes")
+            .header("Authorization", format!("Bearer {}", device.access_token))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body: CreatePairingCodeResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(CODE_LENGTH, body.code.len());
+
+        let response = app
+            .clone()
+            .oneshot(redeem_request(&body.code))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let device: CreateDeviceResponse = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!("paired device", device.name);
+        let paired = state
+            .storage
+            .find_device_by_access_token(&device.access_token)
+            .await
+            .unwrap();
+        assert_eq!(user.id, paired.user_id);
+
+        let response = app.oneshot(redeem_request(&body.code)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_taken_device_name_keeps_code() {
+        let (state, app) = create_test_app();
+        let (_user, device) = test_user_and_device(state.storage.as_ref()).await.unwrap();
+        let code = create_code(&app, &device).await;
+
+        let response = app
+            .clone()
+            .oneshot(redeem_request_named(&code, &device.name))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let response = app.oneshot(redeem_request(&code)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_guessing_is_throttled() {
+        let (state, app) = create_test_app();
+        let (_user, device) = test_user_and_device(state.storage.as_ref()).await.unwrap();
+        let code = create_code(&app, &device).await;
+        let from_client = |code: &str| {
+            let mut request = redeem_request(code);
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from(([192, 0, 2, 1], 4000))));
+            request
+        };
+
+        let mut status = StatusCode::UNAUTHORIZED;
+        while status == StatusCode::UNAUTHORIZED {
+            let response = app.clone().oneshot(from_client("0000000")).await.unwrap();
+            status = response.status();
+        }
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        let response = app.clone().oneshot(from_client(&code)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let response = app.oneshot(redeem_request(&code)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_create_pairing_code_requires_device() {
+        let (_state, app) = create_test_app();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/pairing_codes")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -1,6 +1,6 @@
-use crate::database::Pool;
 use crate::error_handling::AppResult;
-use crate::models::{episode, user_device};
+use crate::models::user_device;
+use crate::storage::DynStorage;
 use axum::extract::State;
 use axum::headers::HeaderMap;
 use axum::http::StatusCode;
@@ -10,13 +10,12 @@ use dimppl_shared::progress::ProgressUpdateRequest;
 
 #[debug_handler]
 pub async fn submit_progress(
-    State(pool): State<Pool>,
+    State(storage): State<DynStorage>,
     headers: HeaderMap,
     Json(request): Json<ProgressUpdateRequest>,
 ) -> AppResult<(StatusCode, ())> {
-    let mut conn = pool.get().await?;
-    let user = user_device::user_from_http_request(&headers, &mut conn).await?;
-    episode::update_progress(user.id, request, &mut conn).await?;
+    let user = user_device::user_from_http_request(&headers, storage.as_ref()).await?;
+    storage.update_progress(user.id, request).await?;
     Ok((StatusCode::OK, ()))
 }
 
@@ -24,33 +23,28 @@ pub async fn submit_progress(
 mod tests {
     use super::*;
     use crate::app::create_test_app;
-    use crate::models::podcast::test_podcast_with_episodes;
-    use crate::models::user_device::test_user_and_device;
-    use crate::models::PodcastEpisode;
+    use crate::fixtures::{now, test_podcast_with_episodes, test_user_and_device};
     use axum::http::Request;
-    use chrono::Local;
-    use diesel::prelude::*;
-    use diesel_async::RunQueryDsl;
     use hyper::{http, Body};
-    use serial_test::serial;
     use tower::ServiceExt;
 
-    #[serial]
     #[tokio::test]
     async fn test_update_progress_successful_update() {
         let (state, app) = create_test_app();
-        let mut conn = state.pool.get().await.unwrap();
-        let (user, device) = test_user_and_device(&mut conn).await.unwrap();
-        let (existing_podcast, episodes) = test_podcast_with_episodes(&user, &mut conn).await.unwrap();
+        let (user, device) = test_user_and_device(state.storage.as_ref()).await.unwrap();
+        let (existing_podcast, episodes) =
+            test_podcast_with_episodes(&user, state.storage.as_ref())
+                .await
+                .unwrap();
 
         let request = ProgressUpdateRequest {
             podcast_guid: existing_podcast.guid.clone(),
             episode_guid: episodes[1].guid.clone(),
             listened_seconds: 250,
             completed: true,
-            updated_at: Local::now().naive_utc(),
+            updated_at: now(),
         };
-        
+
         let web_request = Request::builder()
             .method(http::Method::POST)
             .uri("/submit_progress")
@@ -62,17 +56,15 @@ mod tests {
         let response = app.oneshot(web_request).await.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
 
-        let episode = {
-            use crate::schema::podcast_episodes::dsl::*;
-            podcast_episodes
-                .filter(guid.eq(&episodes[1].guid))
-                .select(PodcastEpisode::as_select())
-                .load(&mut conn)
-                .await.unwrap()
-                .remove(0)
-        };
+        let episode = state
+            .storage
+            .list_podcast_episodes(&existing_podcast)
+            .await
+            .unwrap()
+            .remove(1);
+        assert_eq!(episodes[1].guid, episode.guid);
         assert_eq!(250, episode.listened_seconds);
         assert!(episode.completed);
         assert_eq!(request.updated_at, episode.updated_at);
     }
-}
\ No newline at end of file
+}
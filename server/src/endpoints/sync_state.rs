@@ -1,28 +1,37 @@
-use crate::database::Pool;
 use crate::error_handling::AppResult;
-use crate::models::{podcast, user_device};
+use crate::models::user_device;
+use crate::negotiation::{Negotiated, Protocol, ResponseFormat};
+use crate::storage::DynStorage;
 use axum::extract::State;
 use axum::headers::HeaderMap;
-use axum::Json;
-use dimppl_shared::sync::{SyncStateRequest, SyncStateResponse};
+use axum::response::Response;
+use dimppl_shared::protocol::Capability;
+use dimppl_shared::sync::SyncStateRequest;
 
 pub async fn sync_state(
-    State(pool): State<Pool>,
+    State(storage): State<DynStorage>,
     headers: HeaderMap,
-    Json(sync_state_request): Json<SyncStateRequest>,
-) -> AppResult<Json<SyncStateResponse>> {
-    let mut conn = pool.get().await?;
+    format: ResponseFormat,
+    protocol: Protocol,
+    Negotiated(mut sync_state_request): Negotiated<SyncStateRequest>,
+) -> AppResult<Response> {
     let (user, device) =
-        user_device::user_and_device_from_http_request(&headers, &mut conn).await?;
+        user_device::user_and_device_from_http_request(&headers, storage.as_ref()).await?;
     tracing::debug!(
         "Starting sync for user id={} device name={}",
         user.id,
         device.name
     );
+    if !protocol.0.has(Capability::Bookmarks) {
+        sync_state_request.bookmarks.clear();
+    }
+    if !protocol.0.has(Capability::PodcastSettings) {
+        sync_state_request.settings.clear();
+    }
     // TODO: maybe lock by user so this can't run in parallel with another sync operation
     for podcast in &sync_state_request.podcasts {
         tracing::debug!("Syncing podcast guid={} url={}", podcast.guid, podcast.url);
-        let result = podcast::sync_upsert_podcast(&user, podcast, &mut conn).await?;
+        let result = storage.sync_upsert_podcast(&user, podcast).await?;
         tracing::debug!("Sync result: {:#?}", result);
     }
     for (guid, episodes) in &sync_state_request.episodes {
@@ -31,31 +40,66 @@ pub async fn sync_state(
             episodes.len(),
             guid
         );
-        podcast::sync_upsert_episodes(&user, guid, episodes, &mut conn).await?;
+        storage.sync_upsert_episodes(&user, guid, episodes).await?;
+    }
+    for (guid, bookmarks) in &sync_state_request.bookmarks {
+        tracing::debug!(
+            "Syncing {} bookmarks for podcast guid {}",
+            bookmarks.len(),
+            guid
+        );
+        storage
+            .sync_upsert_bookmarks(&user, guid, bookmarks)
+            .await?;
     }
-    Ok(Json(podcast::get_sync_response(&user, &mut conn).await?))
+    for (guid, settings) in &sync_state_request.settings {
+        tracing::debug!("Syncing settings for podcast guid {}", guid);
+        let result = storage
+            .sync_upsert_podcast_settings(&user, guid, settings)
+            .await?;
+        tracing::debug!("Sync result: {:#?}", result);
+    }
+    for (from_guid, into_guid) in &sync_state_request.merged_podcasts {
+        tracing::debug!("Merging podcast guid {} into {}", from_guid, into_guid);
+        let result = storage.merge_podcasts(&user, from_guid, into_guid).await?;
+        tracing::debug!("Merge result: {:#?}", result);
+    }
+    let mut sync_state_response = storage.get_sync_response(&user).await?;
+    if !protocol.0.has(Capability::Bookmarks) {
+        sync_state_response.bookmarks.clear();
+    }
+    if !protocol.0.has(Capability::PodcastSettings) {
+        sync_state_response.settings.clear();
+    }
+    let mut response = format.respond(&sync_state_response)?;
+    response.headers_mut().extend(protocol.response_headers());
+    Ok(response)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::app::create_test_app;
-    use crate::models::user_device::test_user_and_device;
+    use crate::fixtures::{now, test_user_and_device};
     use axum::http;
     use axum::http::{Request, StatusCode};
     use chrono::Local;
-    use dimppl_shared::sync::{SyncPodcast, SyncPodcastEpisode};
+    use dimppl_shared::encoding::{ContentEncoding, ContentType, MAX_BODY};
+    use dimppl_shared::protocol::{
+        ProtocolInfo, CAPABILITIES_HEADER, PROTOCOL_VERSION, PROTOCOL_VERSION_HEADER,
+    };
+    use dimppl_shared::sync::{
+        AutoDownloadPolicy, EpisodeSortOrder, SyncBookmark, SyncPodcast, SyncPodcastEpisode,
+        SyncPodcastSettings, SyncStateResponse,
+    };
     use hyper::Body;
-    use serial_test::serial;
     use std::collections::HashMap;
     use tower::ServiceExt;
 
     #[tokio::test]
-    #[serial]
     pub async fn test_sync_state() {
         let (state, app) = create_test_app();
-        let mut conn = state.pool.get().await.unwrap();
-        let (user, device) = test_user_and_device(&mut conn).await.unwrap();
+        let (_user, device) = test_user_and_device(state.storage.as_ref()).await.unwrap();
         let new_podcast = SyncPodcast {
             url: "https://google.com".into(),
             guid: "guid".into(),
@@ -90,6 +134,7 @@ mod tests {
         let payload = SyncStateRequest {
             podcasts: vec![new_podcast.clone()],
             episodes: episode_map,
+            ..Default::default()
         };
 
         let request = Request::builder()
@@ -109,4 +154,307 @@ mod tests {
         assert_eq!("ep2", response_body.episodes["guid"][1].guid);
         assert_eq!("ep3", response_body.episodes["guid"][2].guid);
     }
+
+    fn test_payload() -> SyncStateRequest {
+        let new_podcast = SyncPodcast {
+            url: "https://google.com".into(),
+            guid: "guid".into(),
+            deleted_at: None,
+            updated_at: Local::now().naive_utc(),
+        };
+        let episodes = vec![SyncPodcastEpisode {
+            guid: "ep1".into(),
+            url: "https://ep1".into(),
+            listened_seconds: 120,
+            completed: false,
+            updated_at: Local::now().naive_utc(),
+        }];
+        SyncStateRequest {
+            podcasts: vec![new_podcast.clone()],
+            episodes: HashMap::from([(new_podcast.guid, episodes)]),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    pub async fn test_sync_state_msgpack_zstd() {
+        let (state, app) = create_test_app();
+        let (_user, device) = test_user_and_device(state.storage.as_ref()).await.unwrap();
+        let body = ContentType::MessagePack.serialize(&test_payload()).unwrap();
+        let body = ContentEncoding::Zstd.compress(body).unwrap();
+
+        let request = Request::builder()
+            .method(http::Method::POST)
+            .uri("/sync")
+            .header("Content-Type", "application/msgpack")
+            .header("Content-Encoding", "zstd")
+            .header("Accept", "application/msgpack")
+            .header("Accept-Encoding", "gzip, zstd")
+            .header("Authorization", format!("Bearer {}", device.access_token))
+            .body(Body::from(body))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!("application/msgpack", response.headers()["Content-Type"]);
+        assert_eq!("zstd", response.headers()["Content-Encoding"]);
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body_bytes = ContentEncoding::Zstd
+            .decompress(body_bytes.to_vec())
+            .unwrap();
+        let response_body: SyncStateResponse =
+            ContentType::MessagePack.deserialize(&body_bytes).unwrap();
+        assert_eq!("guid", response_body.podcasts[0].guid);
+        assert_eq!(120, response_body.episodes["guid"][0].listened_seconds);
+    }
+
+    #[tokio::test]
+    pub async fn test_sync_state_gzip_json() {
+        let (state, app) = create_test_app();
+        let (_user, device) = test_user_and_device(state.storage.as_ref()).await.unwrap();
+        let body = serde_json::to_vec(&test_payload()).unwrap();
+        let body = ContentEncoding::Gzip.compress(body).unwrap();
+
+        let request = Request::builder()
+            .method(http::Method::POST)
+            .uri("/sync")
+            .header("Content-Type", "application/json")
+            .header("Content-Encoding", "gzip")
+            .header("Accept-Encoding", "gzip")
+            .header("Authorization", format!("Bearer {}", device.access_token))
+            .body(Body::from(body))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!("application/json", response.headers()["Content-Type"]);
+        assert_eq!("gzip", response.headers()["Content-Encoding"]);
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body_bytes = ContentEncoding::Gzip
+            .decompress(body_bytes.to_vec())
+            .unwrap();
+        let response_body: SyncStateResponse = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!("guid", response_body.podcasts[0].guid);
+    }
+
+    #[tokio::test]
+    pub async fn test_sync_state_settings() {
+        let (state, app) = create_test_app();
+        let (_user, device) = test_user_and_device(state.storage.as_ref()).await.unwrap();
+        let settings = SyncPodcastSettings {
+            playback_speed: Some(1.5),
+            skip_intro_seconds: 45,
+            auto_download: AutoDownloadPolicy::All,
+            sort_order: EpisodeSortOrder::NewestFirst,
+            notifications: false,
+            updated_at: now(),
+        };
+        let payload = SyncStateRequest {
+            settings: HashMap::from([("guid".to_string(), settings.clone())]),
+            ..test_payload()
+        };
+
+        let request = Request::builder()
+            .method(http::Method::POST)
+            .uri("/sync")
+            .header("Content-Type", "application/json")
+            .header(PROTOCOL_VERSION_HEADER, PROTOCOL_VERSION.to_string())
+            .header(
+                CAPABILITIES_HEADER,
+                ProtocolInfo::current().capabilities_header(),
+            )
+            .header("Authorization", format!("Bearer {}", device.access_token))
+            .body(Body::from(serde_json::to_vec(&payload).unwrap()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let response_body: SyncStateResponse = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!(settings, response_body.settings["guid"]);
+    }
+
+    #[tokio::test]
+    pub async fn test_sync_state_bookmarks() {
+        let (state, app) = create_test_app();
+        let (_user, device) = test_user_and_device(state.storage.as_ref()).await.unwrap();
+        let bookmark = SyncBookmark {
+            guid: "bookmark".into(),
+            episode_guid: "ep1".into(),
+            start_seconds: 600,
+            end_seconds: None,
+            note: "quote about methodology".into(),
+            created_at: now(),
+            deleted_at: None,
+            updated_at: now(),
+        };
+        let payload = SyncStateRequest {
+            bookmarks: HashMap::from([("guid".to_string(), vec![bookmark.clone()])]),
+            ..test_payload()
+        };
+
+        let request = Request::builder()
+            .method(http::Method::POST)
+            .uri("/sync")
+            .header("Content-Type", "application/json")
+            .header(PROTOCOL_VERSION_HEADER, PROTOCOL_VERSION.to_string())
+            .header(
+                CAPABILITIES_HEADER,
+                ProtocolInfo::current().capabilities_header(),
+            )
+            .header("Authorization", format!("Bearer {}", device.access_token))
+            .body(Body::from(serde_json::to_vec(&payload).unwrap()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            PROTOCOL_VERSION.to_string(),
+            response.headers()[PROTOCOL_VERSION_HEADER]
+        );
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let response_body: SyncStateResponse = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!(vec![bookmark], response_body.bookmarks["guid"]);
+    }
+
+    #[tokio::test]
+    pub async fn test_sync_state_merged_podcasts() {
+        let (state, app) = create_test_app();
+        let (user, device) = test_user_and_device(state.storage.as_ref()).await.unwrap();
+        let old_payload = test_payload();
+        for podcast in &old_payload.podcasts {
+            state
+                .storage
+                .sync_upsert_podcast(&user, podcast)
+                .await
+                .unwrap();
+        }
+        state
+            .storage
+            .sync_upsert_episodes(&user, "guid", &old_payload.episodes["guid"])
+            .await
+            .unwrap();
+        let stable_podcast = SyncPodcast {
+            url: "https://google.com".into(),
+            guid: "stable".into(),
+            deleted_at: None,
+            updated_at: now(),
+        };
+        let payload = SyncStateRequest {
+            podcasts: vec![stable_podcast],
+            merged_podcasts: HashMap::from([("guid".to_string(), "stable".to_string())]),
+            ..Default::default()
+        };
+
+        let request = Request::builder()
+            .method(http::Method::POST)
+            .uri("/sync")
+            .header("Content-Type", "application/json")
+            .header("Authorization", format!("Bearer {}", device.access_token))
+            .body(Body::from(serde_json::to_vec(&payload).unwrap()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let response_body: SyncStateResponse = serde_json::from_slice(&body_bytes).unwrap();
+        let old_podcast = response_body
+            .podcasts
+            .iter()
+            .find(|p| p.guid == "guid")
+            .unwrap();
+        assert!(old_podcast.deleted_at.is_some());
+        assert_eq!(120, response_body.episodes["stable"][0].listened_seconds);
+    }
+
+    #[tokio::test]
+    pub async fn test_sync_state_legacy_protocol() {
+        let (state, app) = create_test_app();
+        let (_user, device) = test_user_and_device(state.storage.as_ref()).await.unwrap();
+        let settings = SyncPodcastSettings {
+            playback_speed: None,
+            skip_intro_seconds: 30,
+            auto_download: AutoDownloadPolicy::Off,
+            sort_order: EpisodeSortOrder::OldestFirst,
+            notifications: true,
+            updated_at: now(),
+        };
+        let payload = SyncStateRequest {
+            settings: HashMap::from([("guid".to_string(), settings)]),
+            ..test_payload()
+        };
+
+        let request = Request::builder()
+            .method(http::Method::POST)
+            .uri("/sync")
+            .header("Content-Type", "application/json")
+            .header("Authorization", format!("Bearer {}", device.access_token))
+            .body(Body::from(serde_json::to_vec(&payload).unwrap()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!("1", response.headers()[PROTOCOL_VERSION_HEADER]);
+        assert_eq!("", response.headers()[CAPABILITIES_HEADER]);
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let response_body: SyncStateResponse = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!("ep1", response_body.episodes["guid"][0].guid);
+        assert!(response_body.settings.is_empty());
+    }
+
+    #[tokio::test]
+    pub async fn test_sync_state_outdated_protocol() {
+        let (state, app) = create_test_app();
+        let (_user, device) = test_user_and_device(state.storage.as_ref()).await.unwrap();
+
+        let request = Request::builder()
+            .method(http::Method::POST)
+            .uri("/sync")
+            .header("Content-Type", "application/json")
+            .header(PROTOCOL_VERSION_HEADER, "0")
+            .header("Authorization", format!("Bearer {}", device.access_token))
+            .body(Body::from(serde_json::to_vec(&test_payload()).unwrap()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UPGRADE_REQUIRED);
+    }
+
+    #[tokio::test]
+    pub async fn test_sync_state_unsupported_content_type() {
+        let (state, app) = create_test_app();
+        let (_user, device) = test_user_and_device(state.storage.as_ref()).await.unwrap();
+
+        let request = Request::builder()
+            .method(http::Method::POST)
+            .uri("/sync")
+            .header("Content-Type", "application/xml")
+            .header("Authorization", format!("Bearer {}", device.access_token))
+            .body(Body::from("<sync/>"))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    pub async fn test_sync_state_decompression_bomb() {
+        let (state, app) = create_test_app();
+        let (_user, device) = test_user_and_device(state.storage.as_ref()).await.unwrap();
+        let body = ContentEncoding::Zstd
+            .compress(vec![b' '; MAX_BODY + 1])
+            .unwrap();
+
+        let request = Request::builder()
+            .method(http::Method::POST)
+            .uri("/sync")
+            .header("Content-Type", "application/json")
+            .header("Content-Encoding", "zstd")
+            .header("Authorization", format!("Bearer {}", device.access_token))
+            .body(Body::from(body))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
 }
@@ -1,6 +1,7 @@
-use crate::database::Pool;
 use crate::error_handling::AppResult;
 use crate::models::{User, UserDevice};
+use crate::negotiation::Protocol;
+use crate::storage::DynStorage;
 use axum::extract::ws::{Message, WebSocket};
 use axum::extract::{State, WebSocketUpgrade};
 use axum::headers::HeaderMap;
@@ -9,12 +10,16 @@ use axum::response::IntoResponse;
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
     headers: HeaderMap,
-    State(pool): State<Pool>,
+    protocol: Protocol,
+    State(storage): State<DynStorage>,
 ) -> AppResult<impl IntoResponse> {
-    let mut conn = pool.get().await?;
     let (user, device) =
-        crate::models::user_device::user_and_device_from_http_request(&headers, &mut conn).await?;
-    Ok(ws.on_upgrade(move |socket| handle_socket(socket, user, device)))
+        crate::models::user_device::user_and_device_from_http_request(&headers, storage.as_ref())
+            .await?;
+    Ok((
+        protocol.response_headers(),
+        ws.on_upgrade(move |socket| handle_socket(socket, user, device)),
+    ))
 }
 
 async fn handle_socket(mut socket: WebSocket, user: User, device: UserDevice) {
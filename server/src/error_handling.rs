@@ -1,4 +1,5 @@
 use axum::response::{IntoResponse, Response};
+use diesel::result::DatabaseErrorKind;
 use hyper::StatusCode;
 use std::fmt::{Debug, Display, Formatter};
 
@@ -37,4 +38,49 @@ impl AppError {
     pub fn unauthorized() -> Self {
         Self(anyhow::anyhow!("Unauthorized"), StatusCode::UNAUTHORIZED)
     }
+
+    pub fn bad_request(error: impl Into<anyhow::Error>) -> Self {
+        Self(error.into(), StatusCode::BAD_REQUEST)
+    }
+
+    pub fn unsupported_media_type(value: &str) -> Self {
+        Self(
+            anyhow::anyhow!("Unsupported media type or encoding: {value}"),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        )
+    }
+
+    pub fn payload_too_large(error: impl Into<anyhow::Error>) -> Self {
+        Self(error.into(), StatusCode::PAYLOAD_TOO_LARGE)
+    }
+
+    pub fn conflict(message: &'static str) -> Self {
+        Self(anyhow::anyhow!(message), StatusCode::CONFLICT)
+    }
+
+    pub fn too_many_requests() -> Self {
+        Self(
+            anyhow::anyhow!("Too many attempts, try again later"),
+            StatusCode::TOO_MANY_REQUESTS,
+        )
+    }
+
+    pub fn is_unique_violation(&self) -> bool {
+        matches!(
+            self.0.downcast_ref::<diesel::result::Error>(),
+            Some(diesel::result::Error::DatabaseError(
+                DatabaseErrorKind::UniqueViolation,
+                _
+            ))
+        )
+    }
+
+    pub fn upgrade_required(client_version: u32) -> Self {
+        Self(
+            anyhow::anyhow!(
+                "Protocol version {client_version} is no longer supported, please update"
+            ),
+            StatusCode::UPGRADE_REQUIRED,
+        )
+    }
 }
@@ -1 +1,62 @@
+use chrono::{Local, NaiveDateTime, SubsecRound};
+use dimppl_shared::sync::{SyncPodcast, SyncPodcastEpisode};
 
+use crate::error_handling::AppResult;
+use crate::models::user::NewUser;
+use crate::models::user_device::CreateDeviceRequest;
+use crate::models::{Podcast, PodcastEpisode, User, UserDevice};
+use crate::storage::Storage;
+
+/// The current time at the precision Postgres stores it with.
+pub fn now() -> NaiveDateTime {
+    Local::now().naive_utc().trunc_subsecs(6)
+}
+
+pub async fn test_user_and_device(storage: &dyn Storage) -> AppResult<(User, UserDevice)> {
+    let user = storage.create_user(&NewUser::default()).await?;
+    let device = storage
+        .create_device(
+            &CreateDeviceRequest {
+                user_access_key: user.access_key.clone(),
+                device_name: "Test Device".into(),
+            },
+            &user,
+        )
+        .await?;
+    Ok((user, device))
+}
+
+pub async fn test_podcast_with_episodes(
+    user: &User,
+    storage: &dyn Storage,
+) -> AppResult<(Podcast, Vec<PodcastEpisode>)> {
+    let podcast = SyncPodcast {
+        guid: "guid".into(),
+        url: "https://google.com".into(),
+        deleted_at: None,
+        updated_at: now(),
+    };
+    storage.sync_upsert_podcast(user, &podcast).await?;
+    let episodes = vec![
+        SyncPodcastEpisode {
+            guid: "ep1".into(),
+            url: "https://ep1".into(),
+            listened_seconds: 300,
+            completed: true,
+            updated_at: now(),
+        },
+        SyncPodcastEpisode {
+            guid: "ep2".into(),
+            url: "https://ep2".into(),
+            listened_seconds: 0,
+            completed: false,
+            updated_at: NaiveDateTime::default(),
+        },
+    ];
+    storage
+        .sync_upsert_episodes(user, &podcast.guid, &episodes)
+        .await?;
+    let podcast = storage.find_podcast_by_guid(user, &podcast.guid).await?;
+    let episodes = storage.list_podcast_episodes(&podcast).await?;
+    Ok((podcast, episodes))
+}
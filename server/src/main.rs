@@ -8,10 +8,14 @@ mod app;
 mod database;
 mod endpoints;
 mod error_handling;
+#[cfg(test)]
 mod fixtures;
 mod models;
+mod negotiation;
+mod pairing_throttle;
 mod schema;
 mod state;
+mod storage;
 mod sync_lock;
 
 #[tokio::main]
@@ -30,7 +34,7 @@ async fn main() {
         .expect("could not parse LISTEN env variable");
     tracing::info!("listening on {addr}");
     axum::Server::bind(&addr)
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .unwrap();
 }
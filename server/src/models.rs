@@ -1,11 +1,13 @@
+pub mod bookmark;
 pub mod podcast;
 pub mod user;
 pub mod user_device;
 pub mod episode;
+pub mod pairing_code;
 
 use diesel::prelude::*;
 
-#[derive(Queryable, Selectable)]
+#[derive(Queryable, Selectable, Clone)]
 #[diesel(table_name = crate::schema::users)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct User {
@@ -13,7 +15,7 @@ pub struct User {
     pub access_key: String,
 }
 
-#[derive(Queryable, Selectable)]
+#[derive(Queryable, Selectable, Clone)]
 #[diesel(table_name = crate::schema::user_devices)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct UserDevice {
@@ -24,7 +26,7 @@ pub struct UserDevice {
     pub access_token: String,
 }
 
-#[derive(Queryable, Selectable)]
+#[derive(Queryable, Selectable, Clone)]
 #[diesel(table_name = crate::schema::podcasts)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Podcast {
@@ -36,7 +38,7 @@ pub struct Podcast {
     pub updated_at: chrono::NaiveDateTime,
 }
 
-#[derive(Queryable, Selectable)]
+#[derive(Queryable, Selectable, Clone)]
 #[diesel(table_name = crate::schema::podcast_episodes)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct PodcastEpisode {
@@ -48,3 +50,43 @@ pub struct PodcastEpisode {
     pub completed: bool,
     pub updated_at: chrono::NaiveDateTime,
 }
+
+#[derive(Queryable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::podcast_settings)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PodcastSettings {
+    pub id: i64,
+    pub podcast_id: i64,
+    pub playback_speed: Option<f32>,
+    pub skip_intro_seconds: i32,
+    pub auto_download: String,
+    pub sort_order: String,
+    pub notifications: bool,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::pairing_codes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PairingCode {
+    pub id: i64,
+    pub user_id: i64,
+    pub code: String,
+    pub expires_at: chrono::NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Clone)]
+#[diesel(table_name = crate::schema::bookmarks)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Bookmark {
+    pub id: i64,
+    pub podcast_id: i64,
+    pub guid: String,
+    pub episode_guid: String,
+    pub start_seconds: i32,
+    pub end_seconds: Option<i32>,
+    pub note: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub deleted_at: Option<chrono::NaiveDateTime>,
+    pub updated_at: chrono::NaiveDateTime,
+}
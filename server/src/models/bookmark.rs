@@ -0,0 +1,150 @@
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use dimppl_shared::sync::SyncBookmark;
+
+use crate::error_handling::AppResult;
+use crate::models::{podcast, Bookmark, User};
+
+impl From<Bookmark> for SyncBookmark {
+    fn from(value: Bookmark) -> Self {
+        let Bookmark {
+            guid,
+            episode_guid,
+            start_seconds,
+            end_seconds,
+            note,
+            created_at,
+            deleted_at,
+            updated_at,
+            ..
+        } = value;
+        Self {
+            guid,
+            episode_guid,
+            start_seconds,
+            end_seconds,
+            note,
+            created_at,
+            deleted_at,
+            updated_at,
+        }
+    }
+}
+
+pub async fn list_for_podcast(
+    the_podcast_id: i64,
+    conn: &mut AsyncPgConnection,
+) -> AppResult<Vec<Bookmark>> {
+    use crate::schema::bookmarks::dsl::*;
+    Ok(bookmarks
+        .filter(podcast_id.eq(the_podcast_id))
+        .order(guid.asc())
+        .select(Bookmark::as_select())
+        .load(conn)
+        .await?)
+}
+
+pub async fn sync_upsert_bookmarks(
+    user: &User,
+    podcast_guid: &str,
+    sync_bookmarks: &[SyncBookmark],
+    conn: &mut AsyncPgConnection,
+) -> AppResult<()> {
+    let podcast_record_id = podcast::find_by_guid(user.id, podcast_guid, conn).await?.id;
+    for bookmark in sync_bookmarks {
+        use crate::schema::bookmarks::dsl::*;
+        use diesel::query_dsl::methods::FilterDsl;
+
+        diesel::insert_into(bookmarks)
+            .values((
+                podcast_id.eq(podcast_record_id),
+                guid.eq(&bookmark.guid),
+                episode_guid.eq(&bookmark.episode_guid),
+                start_seconds.eq(bookmark.start_seconds),
+                end_seconds.eq(bookmark.end_seconds),
+                note.eq(&bookmark.note),
+                created_at.eq(bookmark.created_at),
+                deleted_at.eq(bookmark.deleted_at),
+                updated_at.eq(bookmark.updated_at),
+            ))
+            .on_conflict((podcast_id, guid))
+            .do_update()
+            .set((
+                start_seconds.eq(bookmark.start_seconds),
+                end_seconds.eq(bookmark.end_seconds),
+                note.eq(&bookmark.note),
+                deleted_at.eq(bookmark.deleted_at),
+                updated_at.eq(bookmark.updated_at),
+            ))
+            .filter(updated_at.lt(bookmark.updated_at))
+            .execute(conn)
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::create_test_pool;
+    use crate::fixtures::{now, test_podcast_with_episodes, test_user_and_device};
+    use crate::storage::postgres::PgStorage;
+    use chrono::TimeDelta;
+    use serial_test::serial;
+
+    #[tokio::test]
+    #[serial]
+    async fn test_sync_upsert_bookmarks() {
+        let pool = create_test_pool();
+        let storage = PgStorage::new(pool.clone());
+        let mut conn = pool.get().await.unwrap();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
+        let (podcast, _) = test_podcast_with_episodes(&user, &storage).await.unwrap();
+        let bookmark = SyncBookmark {
+            guid: "bookmark".into(),
+            episode_guid: "ep1".into(),
+            start_seconds: 60,
+            end_seconds: Some(90),
+            note: "interesting".into(),
+            created_at: now(),
+            deleted_at: None,
+            updated_at: now(),
+        };
+        let stale = SyncBookmark {
+            note: "stale".into(),
+            updated_at: bookmark.updated_at - TimeDelta::minutes(1),
+            ..bookmark.clone()
+        };
+        let deleted = SyncBookmark {
+            deleted_at: Some(now()),
+            updated_at: bookmark.updated_at + TimeDelta::minutes(1),
+            ..bookmark.clone()
+        };
+
+        sync_upsert_bookmarks(
+            &user,
+            &podcast.guid,
+            std::slice::from_ref(&bookmark),
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        sync_upsert_bookmarks(&user, &podcast.guid, &[stale], &mut conn)
+            .await
+            .unwrap();
+        let stored = list_for_podcast(podcast.id, &mut conn).await.unwrap();
+        assert_eq!(1, stored.len());
+        assert_eq!(bookmark, stored[0].clone().into());
+
+        sync_upsert_bookmarks(
+            &user,
+            &podcast.guid,
+            std::slice::from_ref(&deleted),
+            &mut conn,
+        )
+        .await
+        .unwrap();
+        let stored = list_for_podcast(podcast.id, &mut conn).await.unwrap();
+        assert_eq!(deleted.deleted_at, stored[0].deleted_at);
+    }
+}
@@ -34,29 +34,31 @@ pub async fn update_progress<'a>(the_user_id: i64, request: ProgressUpdateReques
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::{Local, TimeDelta};
+    use chrono::TimeDelta;
     use serial_test::serial;
     use dimppl_shared::progress::ProgressUpdateRequest;
-    use crate::app::create_test_app;
+    use crate::database::create_test_pool;
+    use crate::fixtures::{now, test_podcast_with_episodes, test_user_and_device};
     use crate::models::episode::update_progress;
-    use crate::models::podcast::{test_podcast_with_episodes, SaveResult};
+    use crate::models::podcast::SaveResult;
     use crate::models::PodcastEpisode;
-    use crate::models::user_device::test_user_and_device;
+    use crate::storage::postgres::PgStorage;
 
     #[serial]
     #[tokio::test]
     async fn test_update_progress_successful_update() {
-        let (state, _) = create_test_app();
-        let mut conn = state.pool.get().await.unwrap();
-        let (user, _device) = test_user_and_device(&mut conn).await.unwrap();
-        let (existing_podcast, episodes) = test_podcast_with_episodes(&user, &mut conn).await.unwrap();
+        let pool = create_test_pool();
+        let storage = PgStorage::new(pool.clone());
+        let mut conn = pool.get().await.unwrap();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
+        let (existing_podcast, episodes) = test_podcast_with_episodes(&user, &storage).await.unwrap();
         
         let request = ProgressUpdateRequest {
             podcast_guid: existing_podcast.guid.clone(),
             episode_guid: episodes[1].guid.clone(),
             listened_seconds: 250,
             completed: true,
-            updated_at: Local::now().naive_utc(),
+            updated_at: now(),
         };
         
         let result = update_progress(user.id, request.clone(), &mut conn).await;
@@ -80,17 +82,18 @@ mod tests {
     #[serial]
     #[tokio::test]
     async fn test_update_progress_unsuccessful() {
-        let (state, _) = create_test_app();
-        let mut conn = state.pool.get().await.unwrap();
-        let (user, _device) = test_user_and_device(&mut conn).await.unwrap();
-        let (existing_podcast, episodes) = test_podcast_with_episodes(&user, &mut conn).await.unwrap();
+        let pool = create_test_pool();
+        let storage = PgStorage::new(pool.clone());
+        let mut conn = pool.get().await.unwrap();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
+        let (existing_podcast, episodes) = test_podcast_with_episodes(&user, &storage).await.unwrap();
 
         let request = ProgressUpdateRequest {
             podcast_guid: existing_podcast.guid.clone(),
             episode_guid: episodes[0].guid.clone(),
             listened_seconds: 250,
             completed: true,
-            updated_at: Local::now().naive_utc() - TimeDelta::days(1),
+            updated_at: now() - TimeDelta::days(1),
         };
 
         let result = update_progress(user.id, request.clone(), &mut conn).await;
@@ -114,17 +117,18 @@ mod tests {
     #[serial]
     #[tokio::test]
     async fn test_update_progress_no_podcast() {
-        let (state, _) = create_test_app();
-        let mut conn = state.pool.get().await.unwrap();
-        let (user, _device) = test_user_and_device(&mut conn).await.unwrap();
-        let (_existing_podcast, _episodes) = test_podcast_with_episodes(&user, &mut conn).await.unwrap();
+        let pool = create_test_pool();
+        let storage = PgStorage::new(pool.clone());
+        let mut conn = pool.get().await.unwrap();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
+        let (_existing_podcast, _episodes) = test_podcast_with_episodes(&user, &storage).await.unwrap();
 
         let request = ProgressUpdateRequest {
             podcast_guid: String::from("null"),
             episode_guid: String::from("null"),
             listened_seconds: 250,
             completed: true,
-            updated_at: Local::now().naive_utc(),
+            updated_at: now(),
         };
 
         let result = update_progress(user.id, request.clone(), &mut conn).await;
@@ -0,0 +1,172 @@
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, QueryDsl, SelectableHelper};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection as _, RunQueryDsl};
+use rand::Rng;
+
+use crate::database::AsyncConnection;
+use crate::error_handling::{AppError, AppResult};
+use crate::models::user_device::{CreateDeviceRequest, NewUserDevice};
+use crate::models::{PairingCode, User, UserDevice};
+use crate::schema::{pairing_codes, user_devices, users};
+
+/// How long a pairing code can be redeemed for after it's issued.
+pub const PAIRING_CODE_TTL: TimeDelta = TimeDelta::minutes(5);
+/// Codes are typed in by hand; `PairingThrottle` is what keeps six digits from being guessed.
+pub const CODE_LENGTH: usize = 6;
+
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::pairing_codes)]
+pub struct NewPairingCode {
+    pub user_id: i64,
+    pub code: String,
+    pub expires_at: NaiveDateTime,
+}
+
+impl NewPairingCode {
+    pub fn new(user: &User) -> Self {
+        Self {
+            user_id: user.id,
+            code: generate_code(),
+            expires_at: Utc::now().naive_utc() + PAIRING_CODE_TTL,
+        }
+    }
+}
+
+fn generate_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..CODE_LENGTH)
+        .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+        .collect()
+}
+
+/// Codes are accepted with the spaces or dashes people type to group them.
+pub fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect()
+}
+
+pub async fn create<'a>(user: &User, conn: &mut AsyncConnection<'a>) -> AppResult<PairingCode> {
+    diesel::delete(pairing_codes::table)
+        .filter(pairing_codes::expires_at.le(Utc::now().naive_utc()))
+        .execute(conn)
+        .await?;
+    Ok(diesel::insert_into(pairing_codes::table)
+        .values(NewPairingCode::new(user))
+        .returning(PairingCode::as_returning())
+        .get_result(conn)
+        .await?)
+}
+
+/// Consumes an unexpired code and enrolls a device for its user, or returns `None` if there's no
+/// such code. Each code enrolls a single device; if the device can't be created, say because the
+/// name is taken, the code is kept.
+pub async fn redeem<'a>(
+    code: &str,
+    device_name: &str,
+    conn: &mut AsyncConnection<'a>,
+) -> AppResult<Option<UserDevice>> {
+    let code = normalize_code(code);
+    let device_name = device_name.to_string();
+    conn.transaction::<_, AppError, _>(|conn| {
+        async move {
+            let Some(pairing_code) = diesel::delete(pairing_codes::table)
+                .filter(pairing_codes::code.eq(code))
+                .filter(pairing_codes::expires_at.gt(Utc::now().naive_utc()))
+                .returning(PairingCode::as_returning())
+                .get_result(conn)
+                .await
+                .optional()?
+            else {
+                return Ok(None);
+            };
+            let user: User = users::table
+                .find(pairing_code.user_id)
+                .select(User::as_select())
+                .first(conn)
+                .await?;
+            let request = CreateDeviceRequest {
+                user_access_key: user.access_key.clone(),
+                device_name,
+            };
+            let device = diesel::insert_into(user_devices::table)
+                .values(NewUserDevice::new(&request, &user))
+                .returning(UserDevice::as_returning())
+                .get_result(conn)
+                .await?;
+            Ok(Some(device))
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::create_test_pool;
+    use crate::fixtures::test_user_and_device;
+    use crate::storage::postgres::PgStorage;
+    use serial_test::serial;
+
+    #[tokio::test]
+    #[serial]
+    async fn test_redeem_is_single_use() {
+        let pool = create_test_pool();
+        let storage = PgStorage::new(pool.clone());
+        let mut conn = pool.get().await.unwrap();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
+        let pairing_code = create(&user, &mut conn).await.unwrap();
+        assert_eq!(CODE_LENGTH, pairing_code.code.len());
+
+        let device = redeem(&pairing_code.code, "paired", &mut conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.id, device.user_id);
+        assert!(redeem(&pairing_code.code, "other", &mut conn)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_code_is_kept_when_device_name_is_taken() {
+        let pool = create_test_pool();
+        let storage = PgStorage::new(pool.clone());
+        let mut conn = pool.get().await.unwrap();
+        let (user, device) = test_user_and_device(&storage).await.unwrap();
+        let pairing_code = create(&user, &mut conn).await.unwrap();
+
+        let taken = redeem(&pairing_code.code, &device.name, &mut conn).await;
+        assert!(taken.is_err_and(|e| e.is_unique_violation()));
+        let grouped = format!("{} {}", &pairing_code.code[..3], &pairing_code.code[3..]);
+        let paired = redeem(&grouped, "paired", &mut conn).await.unwrap();
+        assert!(paired.is_some());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_expired_code_is_not_redeemed() {
+        let pool = create_test_pool();
+        let storage = PgStorage::new(pool.clone());
+        let mut conn = pool.get().await.unwrap();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
+        let expired = NewPairingCode {
+            expires_at: Utc::now().naive_utc() - TimeDelta::seconds(1),
+            ..NewPairingCode::new(&user)
+        };
+        diesel::insert_into(pairing_codes::table)
+            .values(&expired)
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        assert!(redeem(&expired.code, "paired", &mut conn)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -1,11 +1,13 @@
 use crate::database::AsyncConnection;
-use crate::error_handling::AppResult;
-use crate::models::{Podcast, PodcastEpisode, User};
-use chrono::{Local, NaiveDateTime};
+use crate::error_handling::{AppError, AppResult};
+use crate::models::{bookmark, Podcast, PodcastEpisode, PodcastSettings, User};
+use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection as _, AsyncPgConnection, RunQueryDsl};
 use dimppl_shared::sync::{
-    CreatePodcastRequest, SyncPodcast, SyncPodcastEpisode, SyncStateResponse,
+    AutoDownloadPolicy, CreatePodcastRequest, EpisodeSortOrder, SyncBookmark, SyncPodcast,
+    SyncPodcastEpisode, SyncPodcastSettings, SyncStateResponse,
 };
 use std::collections::HashMap;
 
@@ -63,6 +65,19 @@ impl From<PodcastEpisode> for SyncPodcastEpisode {
     }
 }
 
+impl From<PodcastSettings> for SyncPodcastSettings {
+    fn from(value: PodcastSettings) -> Self {
+        Self {
+            playback_speed: value.playback_speed,
+            skip_intro_seconds: value.skip_intro_seconds,
+            auto_download: AutoDownloadPolicy::from_str_or_default(&value.auto_download),
+            sort_order: EpisodeSortOrder::from_str_or_default(&value.sort_order),
+            notifications: value.notifications,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
 pub async fn create<'a>(
     create_request: &CreatePodcastRequest,
     conn: &mut AsyncConnection<'a>,
@@ -95,10 +110,36 @@ pub async fn create<'a>(
     Ok(())
 }
 
-pub async fn sync_upsert_podcast<'a>(
+pub async fn find_by_guid(
+    the_user_id: i64,
+    podcast_guid: &str,
+    conn: &mut AsyncPgConnection,
+) -> AppResult<Podcast> {
+    use crate::schema::podcasts::dsl::*;
+    Ok(podcasts
+        .filter(user_id.eq(the_user_id).and(guid.eq(podcast_guid)))
+        .select(Podcast::as_select())
+        .first(conn)
+        .await?)
+}
+
+pub async fn list_episodes(
+    the_podcast_id: i64,
+    conn: &mut AsyncPgConnection,
+) -> AppResult<Vec<PodcastEpisode>> {
+    use crate::schema::podcast_episodes::dsl::*;
+    Ok(podcast_episodes
+        .filter(podcast_id.eq(the_podcast_id))
+        .order(guid.asc())
+        .select(PodcastEpisode::as_select())
+        .load(conn)
+        .await?)
+}
+
+pub async fn sync_upsert_podcast(
     user: &User,
     sync_podcast: &SyncPodcast,
-    conn: &mut AsyncConnection<'a>,
+    conn: &mut AsyncPgConnection,
 ) -> AppResult<SaveResult> {
     use diesel::query_dsl::methods::FilterDsl;
     let update_count = {
@@ -125,11 +166,11 @@ pub async fn sync_upsert_podcast<'a>(
     Ok(update_count.into())
 }
 
-pub async fn sync_upsert_episodes<'a>(
+pub async fn sync_upsert_episodes(
     user: &User,
     podcast_guid: &str,
     episodes: &[SyncPodcastEpisode],
-    conn: &mut AsyncConnection<'a>,
+    conn: &mut AsyncPgConnection,
 ) -> AppResult<()> {
     let podcast_record_id = {
         use crate::schema::podcasts::dsl::*;
@@ -167,9 +208,59 @@ pub async fn sync_upsert_episodes<'a>(
     Ok(())
 }
 
-pub async fn get_sync_response<'a>(
+pub async fn sync_upsert_settings(
     user: &User,
-    conn: &mut AsyncConnection<'a>,
+    podcast_guid: &str,
+    settings: &SyncPodcastSettings,
+    conn: &mut AsyncPgConnection,
+) -> AppResult<SaveResult> {
+    let podcast_record_id = find_by_guid(user.id, podcast_guid, conn).await?.id;
+    let update_count = {
+        use crate::schema::podcast_settings::dsl::*;
+        use diesel::query_dsl::methods::FilterDsl;
+        diesel::insert_into(podcast_settings)
+            .values((
+                podcast_id.eq(podcast_record_id),
+                playback_speed.eq(settings.playback_speed),
+                skip_intro_seconds.eq(settings.skip_intro_seconds),
+                auto_download.eq(settings.auto_download.as_str()),
+                sort_order.eq(settings.sort_order.as_str()),
+                notifications.eq(settings.notifications),
+                updated_at.eq(settings.updated_at),
+            ))
+            .on_conflict(podcast_id)
+            .do_update()
+            .set((
+                playback_speed.eq(settings.playback_speed),
+                skip_intro_seconds.eq(settings.skip_intro_seconds),
+                auto_download.eq(settings.auto_download.as_str()),
+                sort_order.eq(settings.sort_order.as_str()),
+                notifications.eq(settings.notifications),
+                updated_at.eq(settings.updated_at),
+            ))
+            .filter(updated_at.lt(settings.updated_at))
+            .execute(conn)
+            .await?
+    };
+    Ok(update_count.into())
+}
+
+pub async fn find_settings(
+    the_podcast_id: i64,
+    conn: &mut AsyncPgConnection,
+) -> AppResult<Option<PodcastSettings>> {
+    use crate::schema::podcast_settings::dsl::*;
+    Ok(podcast_settings
+        .filter(podcast_id.eq(the_podcast_id))
+        .select(PodcastSettings::as_select())
+        .first(conn)
+        .await
+        .optional()?)
+}
+
+pub async fn get_sync_response(
+    user: &User,
+    conn: &mut AsyncPgConnection,
 ) -> AppResult<SyncStateResponse> {
     let podcasts = {
         use crate::schema::podcasts::dsl::*;
@@ -181,84 +272,122 @@ pub async fn get_sync_response<'a>(
             .await?
     };
     let mut map: HashMap<String, Vec<SyncPodcastEpisode>> = HashMap::new();
+    let mut settings: HashMap<String, SyncPodcastSettings> = HashMap::new();
+    let mut bookmarks: HashMap<String, Vec<SyncBookmark>> = HashMap::new();
     for podcast in &podcasts {
-        let episodes = {
-            use crate::schema::podcast_episodes::dsl::*;
-            podcast_episodes
-                .filter(podcast_id.eq(podcast.id))
-                .order(guid.asc())
-                .select(PodcastEpisode::as_select())
-                .load(conn)
-                .await?
-                .into_iter()
-                .map(|e| e.into())
-                .collect::<Vec<_>>()
-        };
+        let episodes = list_episodes(podcast.id, conn)
+            .await?
+            .into_iter()
+            .map(|e| e.into())
+            .collect::<Vec<_>>();
         map.insert(podcast.guid.clone(), episodes);
+        if let Some(podcast_settings) = find_settings(podcast.id, conn).await? {
+            settings.insert(podcast.guid.clone(), podcast_settings.into());
+        }
+        let podcast_bookmarks = bookmark::list_for_podcast(podcast.id, conn)
+            .await?
+            .into_iter()
+            .map(|b| b.into())
+            .collect::<Vec<_>>();
+        bookmarks.insert(podcast.guid.clone(), podcast_bookmarks);
     }
     Ok(SyncStateResponse {
         podcasts: podcasts.into_iter().map(|p| p.into()).collect(),
         episodes: map,
+        settings,
+        bookmarks,
     })
 }
 
-pub async fn test_podcast_with_episodes<'a>(user: &User, conn: &mut AsyncConnection<'a>) -> AppResult<(Podcast, Vec<PodcastEpisode>)> {
-    let podcast_instance: Podcast = {
-        use crate::schema::podcasts::dsl::*;
-        diesel::insert_into(podcasts)
-            .values((
-                user_id.eq(user.id),
-                url.eq("https://google.com"),
-                guid.eq("guid"),
-                updated_at.eq(Local::now().naive_utc()),
-            ))
-            .returning(Podcast::as_returning())
-            .get_result(conn)
-            .await?
-    };
-    let episodes = {
-        use crate::schema::podcast_episodes::dsl::*;
-        diesel::insert_into(podcast_episodes)
-            .values(&[
-                (
-                    podcast_id.eq(podcast_instance.id),
-                    guid.eq("ep1"),
-                    url.eq("https://ep1"),
-                    listened_seconds.eq(300),
-                    completed.eq(true),
-                    updated_at.eq(Local::now().naive_utc()),
-                ),
-                (
-                    podcast_id.eq(podcast_instance.id),
-                    guid.eq("ep2"),
-                    url.eq("https://ep2"),
-                    listened_seconds.eq(0),
-                    completed.eq(false),
-                    updated_at.eq(NaiveDateTime::default()),
-                ),
-            ])
-            .returning(PodcastEpisode::as_returning())
-            .load(conn)
-            .await?
-    };
-    Ok((podcast_instance, episodes))
+/// Folds the podcast `from_guid` into `into_guid`: episode progress, settings and bookmarks are
+/// upserted into the target, keeping whichever side is newer, and the old podcast is marked as
+/// deleted so other devices drop it. Done in one transaction, so a failure leaves both untouched.
+pub async fn merge_podcasts<'a>(
+    user: &User,
+    from_guid: &str,
+    into_guid: &str,
+    conn: &mut AsyncConnection<'a>,
+) -> AppResult<SaveResult> {
+    if from_guid == into_guid {
+        return Ok(SaveResult::NotSaved);
+    }
+    let user = user.clone();
+    let from_guid = from_guid.to_string();
+    let into_guid = into_guid.to_string();
+    conn.transaction::<_, AppError, _>(|conn| {
+        async move {
+            let (user, into_guid) = (&user, into_guid.as_str());
+            let Some(from) = find_live_by_guid(user.id, &from_guid, conn).await? else {
+                return Ok(SaveResult::NotSaved);
+            };
+            let now = Utc::now().naive_utc();
+            if find_live_by_guid(user.id, into_guid, conn).await?.is_none() {
+                let target = SyncPodcast {
+                    guid: into_guid.into(),
+                    url: from.url.clone(),
+                    deleted_at: None,
+                    updated_at: now,
+                };
+                sync_upsert_podcast(user, &target, conn).await?;
+            }
+            let episodes = list_episodes(from.id, conn)
+                .await?
+                .into_iter()
+                .map(|e| e.into())
+                .collect::<Vec<_>>();
+            sync_upsert_episodes(user, into_guid, &episodes, conn).await?;
+            if let Some(settings) = find_settings(from.id, conn).await? {
+                sync_upsert_settings(user, into_guid, &settings.into(), conn).await?;
+            }
+            let bookmarks = bookmark::list_for_podcast(from.id, conn)
+                .await?
+                .into_iter()
+                .map(|b| b.into())
+                .collect::<Vec<_>>();
+            bookmark::sync_upsert_bookmarks(user, into_guid, &bookmarks, conn).await?;
+            let deleted = SyncPodcast {
+                deleted_at: Some(now),
+                updated_at: now,
+                ..from.into()
+            };
+            sync_upsert_podcast(user, &deleted, conn).await
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+async fn find_live_by_guid(
+    the_user_id: i64,
+    podcast_guid: &str,
+    conn: &mut AsyncPgConnection,
+) -> AppResult<Option<Podcast>> {
+    use crate::schema::podcasts::dsl::*;
+    Ok(podcasts
+        .filter(user_id.eq(the_user_id).and(guid.eq(podcast_guid)))
+        .filter(deleted_at.is_null())
+        .select(Podcast::as_select())
+        .first(conn)
+        .await
+        .optional()?)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::app::create_test_app;
-    use crate::models::user_device::test_user_and_device;
+    use crate::database::create_test_pool;
+    use crate::fixtures::{now, test_podcast_with_episodes, test_user_and_device};
     use crate::models::PodcastEpisode;
-    use chrono::Local;
+    use crate::storage::postgres::PgStorage;
     use serial_test::serial;
 
     use super::*;
     #[tokio::test]
     #[serial]
     async fn test_sync_upsert_podcast_insertion() {
-        let (state, _) = create_test_app();
-        let mut conn = state.pool.get().await.unwrap();
-        let (user, _device) = test_user_and_device(&mut conn).await.unwrap();
+        let pool = create_test_pool();
+        let storage = PgStorage::new(pool.clone());
+        let mut conn = pool.get().await.unwrap();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
         let new_podcast = SyncPodcast {
             url: "https://google.com".into(),
             guid: "guid".into(),
@@ -275,16 +404,17 @@ mod tests {
                 .await
         };
         assert_eq!(Some(SaveResult::Saved), result.ok());
-        assert_eq!(Some(true), query.ok().map(|v| v.get(0).is_some()));
+        assert_eq!(Some(true), query.ok().map(|v| !v.is_empty()));
     }
 
     #[tokio::test]
     #[serial]
     async fn test_sync_upsert_podcast_update() {
         use crate::schema::podcasts::dsl::*;
-        let (state, _) = create_test_app();
-        let mut conn = state.pool.get().await.unwrap();
-        let (user, _device) = test_user_and_device(&mut conn).await.unwrap();
+        let pool = create_test_pool();
+        let storage = PgStorage::new(pool.clone());
+        let mut conn = pool.get().await.unwrap();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
         let _existing_podcast = diesel::insert_into(podcasts)
             .values((
                 user_id.eq(user.id),
@@ -300,7 +430,7 @@ mod tests {
             url: "https://google2.com".into(),
             guid: "guid".into(),
             deleted_at: None,
-            updated_at: Local::now().naive_utc(),
+            updated_at: now(),
         };
         let result = sync_upsert_podcast(&user, &new_podcast, &mut conn).await;
         let query = {
@@ -312,7 +442,7 @@ mod tests {
                 .await
         }
         .unwrap();
-        let updated_podcast = query.get(0).expect("no podcast!");
+        let updated_podcast = query.into_iter().next().expect("no podcast!");
         assert_eq!(Some(SaveResult::Saved), result.ok());
         assert_eq!(new_podcast.url, updated_podcast.url);
         assert_eq!(new_podcast.updated_at, updated_podcast.updated_at);
@@ -322,15 +452,16 @@ mod tests {
     #[serial]
     async fn test_sync_upsert_podcast_no_update() {
         use crate::schema::podcasts::dsl::*;
-        let (state, _) = create_test_app();
-        let mut conn = state.pool.get().await.unwrap();
-        let (user, _device) = test_user_and_device(&mut conn).await.unwrap();
+        let pool = create_test_pool();
+        let storage = PgStorage::new(pool.clone());
+        let mut conn = pool.get().await.unwrap();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
         let existing_podcast = diesel::insert_into(podcasts)
             .values((
                 user_id.eq(user.id),
                 url.eq("https://google.com"),
                 guid.eq("guid"),
-                updated_at.eq(Local::now().naive_utc()),
+                updated_at.eq(now()),
             ))
             .returning(Podcast::as_returning())
             .get_result(&mut conn)
@@ -352,7 +483,7 @@ mod tests {
                 .await
         }
         .unwrap();
-        let updated_podcast = query.get(0).expect("no podcast!");
+        let updated_podcast = query.into_iter().next().expect("no podcast!");
         assert_eq!(Some(SaveResult::NotSaved), result.ok());
         assert_eq!(existing_podcast.url, updated_podcast.url);
         assert_eq!(existing_podcast.updated_at, updated_podcast.updated_at);
@@ -361,10 +492,11 @@ mod tests {
     #[tokio::test]
     #[serial]
     async fn test_sync_upsert_episodes() {
-        let (state, _) = create_test_app();
-        let mut conn = state.pool.get().await.unwrap();
-        let (user, _device) = test_user_and_device(&mut conn).await.unwrap();
-        let (existing_podcast, _) = test_podcast_with_episodes(&user, &mut conn).await.unwrap();
+        let pool = create_test_pool();
+        let storage = PgStorage::new(pool.clone());
+        let mut conn = pool.get().await.unwrap();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
+        let (existing_podcast, _) = test_podcast_with_episodes(&user, &storage).await.unwrap();
         let episodes = vec![
             SyncPodcastEpisode {
                 guid: "ep1".into(),
@@ -378,14 +510,14 @@ mod tests {
                 url: "https://ep2.changed".into(),
                 listened_seconds: 500,
                 completed: true,
-                updated_at: Local::now().naive_utc(),
+                updated_at: now(),
             },
             SyncPodcastEpisode {
                 guid: "ep3".into(),
                 url: "https://ep3".into(),
                 listened_seconds: 350,
                 completed: false,
-                updated_at: Local::now().naive_utc(),
+                updated_at: now(),
             },
         ];
         sync_upsert_episodes(&user, &existing_podcast.guid, &episodes, &mut conn)
@@ -412,9 +544,10 @@ mod tests {
     #[tokio::test]
     #[serial]
     pub async fn test_get_sync_response() {
-        let (state, _) = create_test_app();
-        let mut conn = state.pool.get().await.unwrap();
-        let (user, _device) = test_user_and_device(&mut conn).await.unwrap();
+        let pool = create_test_pool();
+        let storage = PgStorage::new(pool.clone());
+        let mut conn = pool.get().await.unwrap();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
         let existing_podcast = {
             use crate::schema::podcasts::dsl::*;
             diesel::insert_into(podcasts)
@@ -422,7 +555,7 @@ mod tests {
                     user_id.eq(user.id),
                     url.eq("https://google.com"),
                     guid.eq("guid"),
-                    updated_at.eq(Local::now().naive_utc()),
+                    updated_at.eq(now()),
                 ))
                 .returning(Podcast::as_returning())
                 .get_result(&mut conn)
@@ -439,7 +572,7 @@ mod tests {
                         url.eq("https://ep1"),
                         listened_seconds.eq(300),
                         completed.eq(true),
-                        updated_at.eq(Local::now().naive_utc()),
+                        updated_at.eq(now()),
                     ),
                     (
                         podcast_id.eq(existing_podcast.id),
@@ -462,4 +595,67 @@ mod tests {
         assert_eq!("ep2", sync_response.episodes["guid"][1].guid);
         assert_eq!(2, sync_response.episodes["guid"].len());
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_sync_upsert_settings_last_writer_wins() {
+        let pool = create_test_pool();
+        let storage = PgStorage::new(pool.clone());
+        let mut conn = pool.get().await.unwrap();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
+        let (existing_podcast, _) = test_podcast_with_episodes(&user, &storage).await.unwrap();
+        let newer = SyncPodcastSettings {
+            playback_speed: Some(1.5),
+            skip_intro_seconds: 30,
+            auto_download: AutoDownloadPolicy::Latest,
+            sort_order: EpisodeSortOrder::OldestFirst,
+            notifications: true,
+            updated_at: now(),
+        };
+        let older = SyncPodcastSettings {
+            playback_speed: None,
+            updated_at: NaiveDateTime::default(),
+            ..newer.clone()
+        };
+        let result = sync_upsert_settings(&user, &existing_podcast.guid, &newer, &mut conn).await;
+        assert_eq!(Some(SaveResult::Saved), result.ok());
+        let result = sync_upsert_settings(&user, &existing_podcast.guid, &older, &mut conn).await;
+        assert_eq!(Some(SaveResult::NotSaved), result.ok());
+
+        let sync_response = get_sync_response(&user, &mut conn).await.unwrap();
+        assert_eq!(newer, sync_response.settings["guid"]);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_merge_podcasts() {
+        let pool = create_test_pool();
+        let storage = PgStorage::new(pool.clone());
+        let mut conn = pool.get().await.unwrap();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
+        let (podcast, _episodes) = test_podcast_with_episodes(&user, &storage).await.unwrap();
+
+        let result = merge_podcasts(&user, &podcast.guid, "stable", &mut conn).await;
+        assert_eq!(SaveResult::Saved, result.unwrap());
+        let result = merge_podcasts(&user, &podcast.guid, "stable", &mut conn).await;
+        assert_eq!(SaveResult::NotSaved, result.unwrap());
+
+        let sync_response = get_sync_response(&user, &mut conn).await.unwrap();
+        let old = sync_response
+            .podcasts
+            .iter()
+            .find(|p| p.guid == podcast.guid)
+            .unwrap();
+        assert!(old.deleted_at.is_some());
+        let merged = sync_response
+            .podcasts
+            .iter()
+            .find(|p| p.guid == "stable")
+            .unwrap();
+        assert_eq!(podcast.url, merged.url);
+        assert!(merged.deleted_at.is_none());
+        let merged_episodes = &sync_response.episodes["stable"];
+        assert_eq!(2, merged_episodes.len());
+        assert_eq!(300, merged_episodes[0].listened_seconds);
+    }
 }
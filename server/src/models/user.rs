@@ -39,7 +39,7 @@ pub async fn find_one<'a>(id: i64, conn: &mut AsyncConnection<'a>) -> AppResult<
 #[derive(Insertable)]
 #[diesel(table_name = crate::schema::users)]
 pub struct NewUser {
-    access_key: String,
+    pub access_key: String,
 }
 
 impl Default for NewUser {
@@ -3,22 +3,21 @@ use axum::headers::{HeaderMap, HeaderValue};
 
 use crate::database::AsyncConnection;
 use crate::error_handling::AppResult;
-use crate::models::{user, User, UserDevice};
+use crate::models::{User, UserDevice};
 use crate::schema::user_devices::table as user_devices;
+use crate::storage::Storage;
 use chrono::{NaiveDateTime, Utc};
 use diesel::associations::HasTable;
 use diesel::{insert_into, ExpressionMethods, Insertable, QueryDsl, SelectableHelper};
 use diesel_async::RunQueryDsl;
 
-#[cfg(test)]
-use crate::models::user::NewUser;
 use rand::distributions::Alphanumeric;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 #[derive(Insertable)]
 #[diesel(table_name = crate::schema::user_devices)]
-struct NewUserDevice {
+pub struct NewUserDevice {
     pub user_id: i64,
     pub name: String,
     pub access_token: String,
@@ -58,46 +57,52 @@ pub async fn create<'a>(
     Ok(user_device)
 }
 
-pub async fn user_from_http_request<'a>(
-    headers: &HeaderMap<HeaderValue>,
+pub async fn find_by_access_token<'a>(
+    token: &str,
     conn: &mut AsyncConnection<'a>,
+) -> AppResult<UserDevice> {
+    use crate::schema::user_devices::dsl::*;
+
+    Ok(user_devices
+        .select(UserDevice::as_select())
+        .filter(access_token.eq(token))
+        .first(conn)
+        .await?)
+}
+
+pub async fn user_from_http_request(
+    headers: &HeaderMap<HeaderValue>,
+    storage: &dyn Storage,
 ) -> AppResult<User> {
-    let (user, _) = user_and_device_from_http_request(headers, conn).await?;
+    let (user, _) = user_and_device_from_http_request(headers, storage).await?;
 
     Ok(user)
 }
 
-pub async fn user_and_device_from_http_request<'a>(
+pub async fn user_and_device_from_http_request(
     headers: &HeaderMap<HeaderValue>,
-    conn: &mut AsyncConnection<'a>,
+    storage: &dyn Storage,
 ) -> AppResult<(User, UserDevice)> {
     let unauthorized = Err(crate::error_handling::AppError::unauthorized());
 
-    let device = device_from_http_request(headers, conn).await?;
-    let Ok(user) = user::find_one(device.user_id, conn).await else {
+    let device = device_from_http_request(headers, storage).await?;
+    let Ok(user) = storage.find_user(device.user_id).await else {
         return unauthorized;
     };
 
     Ok((user, device))
 }
 
-pub async fn device_from_http_request<'a>(
+pub async fn device_from_http_request(
     headers: &HeaderMap<HeaderValue>,
-    conn: &mut AsyncConnection<'a>,
+    storage: &dyn Storage,
 ) -> AppResult<UserDevice> {
-    use crate::schema::user_devices::dsl::*;
-
     let unauthorized = Err(crate::error_handling::AppError::unauthorized());
     let Ok(token) = token_from_request(headers) else {
         return unauthorized;
     };
 
-    let Ok(device) = user_devices
-        .select(UserDevice::as_select())
-        .filter(access_token.eq(token))
-        .first(conn)
-        .await
-    else {
+    let Ok(device) = storage.find_device_by_access_token(&token).await else {
         return unauthorized;
     };
     Ok(device)
@@ -121,20 +126,3 @@ fn token_from_request(headers: &HeaderMap<HeaderValue>) -> AppResult<String> {
         unauthorized
     }
 }
-
-#[cfg(test)]
-pub async fn test_user_and_device<'a>(
-    conn: &mut AsyncConnection<'a>,
-) -> AppResult<(User, UserDevice)> {
-    let user = user::create(&NewUser::default(), conn).await.unwrap();
-    let device = create(
-        &CreateDeviceRequest {
-            user_access_key: user.access_key.clone(),
-            device_name: "Test Device".into(),
-        },
-        &user,
-        conn,
-    )
-    .await?;
-    Ok((user, device))
-}
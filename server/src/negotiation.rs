@@ -0,0 +1,146 @@
+use anyhow::anyhow;
+use axum::async_trait;
+use axum::body::{Bytes, HttpBody};
+use axum::extract::{FromRequest, FromRequestParts};
+use axum::http::header::{ACCEPT, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE, VARY};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, HeaderValue, Request};
+use axum::response::{IntoResponse, Response};
+use axum::BoxError;
+use dimppl_shared::encoding::{ContentEncoding, ContentType, PayloadTooLarge};
+use dimppl_shared::protocol::{ProtocolInfo, CAPABILITIES_HEADER, PROTOCOL_VERSION_HEADER};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error_handling::{AppError, AppResult};
+
+/// Request body decoded according to its `Content-Type` and `Content-Encoding`. A missing
+/// `Content-Type` is read as JSON.
+pub struct Negotiated<T>(pub T);
+
+#[async_trait]
+impl<T, S, B> FromRequest<S, B> for Negotiated<T>
+where
+    T: DeserializeOwned,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = match header_str(req.headers(), CONTENT_TYPE.as_str()) {
+            None => ContentType::default(),
+            Some(value) => {
+                ContentType::from_mime(value).ok_or(AppError::unsupported_media_type(value))?
+            }
+        };
+        let content_encoding = match header_str(req.headers(), CONTENT_ENCODING.as_str()) {
+            None => ContentEncoding::default(),
+            Some(value) => {
+                ContentEncoding::from_token(value).ok_or(AppError::unsupported_media_type(value))?
+            }
+        };
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| AppError(anyhow!(e.body_text()), e.status()))?;
+        let bytes = content_encoding.decompress(bytes.to_vec()).map_err(|e| {
+            if e.is::<PayloadTooLarge>() {
+                AppError::payload_too_large(anyhow!(e))
+            } else {
+                AppError::bad_request(anyhow!(e))
+            }
+        })?;
+        let value = content_type
+            .deserialize(&bytes)
+            .map_err(|e| AppError::bad_request(anyhow!(e)))?;
+        Ok(Negotiated(value))
+    }
+}
+
+/// Response format picked from the request's `Accept` and `Accept-Encoding` headers.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ResponseFormat {
+    pub content_type: ContentType,
+    pub content_encoding: ContentEncoding,
+}
+
+impl ResponseFormat {
+    pub fn respond<T: Serialize>(&self, value: &T) -> AppResult<Response> {
+        let body = self.content_type.serialize(value).map_err(|e| anyhow!(e))?;
+        let body = self
+            .content_encoding
+            .compress(body)
+            .map_err(|e| anyhow!(e))?;
+        let mut response = body.into_response();
+        let headers = response.headers_mut();
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(self.content_type.mime()),
+        );
+        if self.content_encoding != ContentEncoding::Identity {
+            headers.insert(
+                CONTENT_ENCODING,
+                HeaderValue::from_static(self.content_encoding.token()),
+            );
+        }
+        headers.insert(VARY, HeaderValue::from_static("Accept, Accept-Encoding"));
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ResponseFormat {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(ResponseFormat {
+            content_type: ContentType::negotiate(header_str(&parts.headers, ACCEPT.as_str())),
+            content_encoding: ContentEncoding::negotiate(header_str(
+                &parts.headers,
+                ACCEPT_ENCODING.as_str(),
+            )),
+        })
+    }
+}
+
+/// Protocol version and capabilities both the client and this server speak, read from the
+/// request's protocol headers. Clients below the minimum version are rejected with 426.
+#[derive(Debug, Clone)]
+pub struct Protocol(pub ProtocolInfo);
+
+impl Protocol {
+    /// Headers telling the client what was picked.
+    pub fn response_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Ok(value) = HeaderValue::from_str(&self.0.version_header()) {
+            headers.insert(PROTOCOL_VERSION_HEADER, value);
+        }
+        if let Ok(value) = HeaderValue::from_str(&self.0.capabilities_header()) {
+            headers.insert(CAPABILITIES_HEADER, value);
+        }
+        headers
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Protocol {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let client = ProtocolInfo::from_headers(
+            header_str(&parts.headers, PROTOCOL_VERSION_HEADER),
+            header_str(&parts.headers, CAPABILITIES_HEADER),
+        )
+        .ok_or(AppError::bad_request(anyhow!("Invalid protocol version")))?;
+        if !client.is_supported() {
+            return Err(AppError::upgrade_required(client.version));
+        }
+        Ok(Protocol(ProtocolInfo::current().negotiate(&client)))
+    }
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
@@ -0,0 +1,157 @@
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::headers::{HeaderMap, HeaderName};
+
+use crate::error_handling::{AppError, AppResult};
+
+/// How long a failed redemption counts against the limits.
+const WINDOW: Duration = Duration::from_secs(10 * 60);
+const MAX_FAILURES_PER_CLIENT: usize = 10;
+/// Caps guessing spread over many addresses, which takes at least this many over
+/// `MAX_FAILURES_PER_CLIENT` of them to trip.
+const MAX_FAILURES: usize = 2000;
+
+/// Limits failed pairing code redemptions, per client address and overall, so live codes can't be
+/// found by trying codes until one works. Once a limit is hit every redemption is refused until old
+/// failures leave the window, valid codes included.
+#[derive(Clone, Default)]
+pub struct PairingThrottle {
+    failures: Arc<Mutex<Failures>>,
+    /// Header a trusted proxy puts the client's address in, such as Fly's `Fly-Client-IP`. Without
+    /// it every client behind the proxy would share the proxy's address.
+    client_ip_header: Option<HeaderName>,
+}
+
+#[derive(Default)]
+struct Failures {
+    all: VecDeque<Instant>,
+    by_client: HashMap<IpAddr, VecDeque<Instant>>,
+}
+
+impl Failures {
+    fn forget_old(&mut self, now: Instant) {
+        let is_old = |at: &Instant| now.duration_since(*at) >= WINDOW;
+        while self.all.front().is_some_and(is_old) {
+            self.all.pop_front();
+        }
+        self.by_client.retain(|_, failures| {
+            while failures.front().is_some_and(is_old) {
+                failures.pop_front();
+            }
+            !failures.is_empty()
+        });
+    }
+}
+
+impl PairingThrottle {
+    /// Reads the client address header from `CLIENT_IP_HEADER`; only set it when every request
+    /// comes through a proxy that overwrites that header.
+    pub fn from_env() -> Self {
+        Self::with_client_ip_header(
+            env::var("CLIENT_IP_HEADER")
+                .ok()
+                .and_then(|name| name.parse().ok()),
+        )
+    }
+
+    pub fn with_client_ip_header(client_ip_header: Option<HeaderName>) -> Self {
+        Self {
+            client_ip_header,
+            ..Self::default()
+        }
+    }
+
+    /// The client's address: the last one in the proxy's header, or the connection's peer address.
+    pub fn client(&self, headers: &HeaderMap, peer: Option<IpAddr>) -> Option<IpAddr> {
+        let from_header = self
+            .client_ip_header
+            .as_ref()
+            .and_then(|name| headers.get(name))
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|address| address.trim().parse().ok());
+        from_header.or(peer)
+    }
+
+    pub fn check(&self, client: Option<IpAddr>) -> AppResult<()> {
+        let mut failures = self.failures.lock().unwrap();
+        failures.forget_old(Instant::now());
+        let client_failures = client
+            .and_then(|client| failures.by_client.get(&client))
+            .map_or(0, VecDeque::len);
+        if failures.all.len() >= MAX_FAILURES || client_failures >= MAX_FAILURES_PER_CLIENT {
+            return Err(AppError::too_many_requests());
+        }
+        Ok(())
+    }
+
+    pub fn record_failure(&self, client: Option<IpAddr>) {
+        let now = Instant::now();
+        let mut failures = self.failures.lock().unwrap();
+        failures.forget_old(now);
+        failures.all.push_back(now);
+        if let Some(client) = client {
+            failures.by_client.entry(client).or_default().push_back(now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_limit() {
+        let throttle = PairingThrottle::default();
+        let client: IpAddr = "192.0.2.1".parse().unwrap();
+        let other_client: IpAddr = "192.0.2.2".parse().unwrap();
+        for _ in 0..MAX_FAILURES_PER_CLIENT {
+            assert!(throttle.check(Some(client)).is_ok());
+            throttle.record_failure(Some(client));
+        }
+        assert!(throttle.check(Some(client)).is_err());
+        assert!(throttle.check(Some(other_client)).is_ok());
+    }
+
+    #[test]
+    fn test_overall_limit() {
+        let throttle = PairingThrottle::default();
+        for _ in 0..MAX_FAILURES {
+            throttle.record_failure(None);
+        }
+        assert!(throttle.check(None).is_err());
+        assert!(throttle.check(Some("192.0.2.1".parse().unwrap())).is_err());
+    }
+
+    #[test]
+    fn test_client_from_proxy_header() {
+        let peer: IpAddr = "10.0.0.1".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("fly-client-ip", "192.0.2.1".parse().unwrap());
+        headers.insert(
+            "x-forwarded-for",
+            "198.51.100.7, 192.0.2.2".parse().unwrap(),
+        );
+
+        let direct = PairingThrottle::default();
+        assert_eq!(Some(peer), direct.client(&headers, Some(peer)));
+        let fly =
+            PairingThrottle::with_client_ip_header(Some(HeaderName::from_static("fly-client-ip")));
+        assert_eq!(
+            Some("192.0.2.1".parse().unwrap()),
+            fly.client(&headers, Some(peer))
+        );
+        assert_eq!(Some(peer), fly.client(&HeaderMap::new(), Some(peer)));
+        let forwarded = PairingThrottle::with_client_ip_header(Some(HeaderName::from_static(
+            "x-forwarded-for",
+        )));
+        assert_eq!(
+            Some("192.0.2.2".parse().unwrap()),
+            forwarded.client(&headers, Some(peer))
+        );
+    }
+}
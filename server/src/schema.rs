@@ -1,5 +1,29 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    bookmarks (id) {
+        id -> Int8,
+        podcast_id -> Int8,
+        guid -> Text,
+        episode_guid -> Text,
+        start_seconds -> Int4,
+        end_seconds -> Nullable<Int4>,
+        note -> Text,
+        created_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    pairing_codes (id) {
+        id -> Int8,
+        user_id -> Int8,
+        code -> Text,
+        expires_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     podcast_episodes (id) {
         id -> Int8,
@@ -12,6 +36,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    podcast_settings (id) {
+        id -> Int8,
+        podcast_id -> Int8,
+        playback_speed -> Nullable<Float4>,
+        skip_intro_seconds -> Int4,
+        auto_download -> Text,
+        sort_order -> Text,
+        notifications -> Bool,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     podcasts (id) {
         id -> Int8,
@@ -40,8 +77,19 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(bookmarks -> podcasts (podcast_id));
+diesel::joinable!(pairing_codes -> users (user_id));
 diesel::joinable!(podcast_episodes -> podcasts (podcast_id));
+diesel::joinable!(podcast_settings -> podcasts (podcast_id));
 diesel::joinable!(podcasts -> users (user_id));
 diesel::joinable!(user_devices -> users (user_id));
 
-diesel::allow_tables_to_appear_in_same_query!(podcast_episodes, podcasts, user_devices, users,);
+diesel::allow_tables_to_appear_in_same_query!(
+    bookmarks,
+    pairing_codes,
+    podcast_episodes,
+    podcast_settings,
+    podcasts,
+    user_devices,
+    users,
+);
@@ -1,11 +1,17 @@
-use crate::database::{create_database_pool, Pool};
+use std::sync::Arc;
+
+use crate::database::create_database_pool;
+use crate::pairing_throttle::PairingThrottle;
+use crate::storage::postgres::PgStorage;
+use crate::storage::DynStorage;
 use crate::sync_lock::SyncLock;
 use axum::extract::FromRef;
 
 #[derive(Clone)]
 pub struct AppState {
-    pub pool: Pool,
+    pub storage: DynStorage,
     pub sync_lock: SyncLock,
+    pub pairing_throttle: PairingThrottle,
 }
 
 impl Default for AppState {
@@ -17,14 +23,28 @@ impl Default for AppState {
 impl AppState {
     pub fn new() -> Self {
         Self {
-            pool: create_database_pool(),
+            pairing_throttle: PairingThrottle::from_env(),
+            ..Self::with_storage(Arc::new(PgStorage::new(create_database_pool())))
+        }
+    }
+
+    pub fn with_storage(storage: DynStorage) -> Self {
+        Self {
+            storage,
             sync_lock: SyncLock::default(),
+            pairing_throttle: PairingThrottle::default(),
         }
     }
 }
 
-impl FromRef<AppState> for Pool {
+impl FromRef<AppState> for DynStorage {
+    fn from_ref(input: &AppState) -> Self {
+        input.storage.clone()
+    }
+}
+
+impl FromRef<AppState> for PairingThrottle {
     fn from_ref(input: &AppState) -> Self {
-        input.pool.clone()
+        input.pairing_throttle.clone()
     }
 }
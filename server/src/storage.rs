@@ -0,0 +1,99 @@
+#[cfg(test)]
+pub mod memory;
+pub mod postgres;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use dimppl_shared::progress::ProgressUpdateRequest;
+use dimppl_shared::sync::{
+    CreatePodcastRequest, SyncBookmark, SyncPodcast, SyncPodcastEpisode, SyncPodcastSettings,
+    SyncStateResponse,
+};
+
+use crate::error_handling::AppResult;
+use crate::models::podcast::SaveResult;
+use crate::models::user::NewUser;
+use crate::models::user_device::CreateDeviceRequest;
+use crate::models::{PairingCode, Podcast, PodcastEpisode, User, UserDevice};
+
+pub type DynStorage = Arc<dyn Storage>;
+
+/// Data access for everything the endpoints need. `PgStorage` is the real thing, `MemoryStorage`
+/// mirrors its semantics so endpoint tests can run without a database.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn create_user(&self, new_user: &NewUser) -> AppResult<User>;
+
+    async fn find_user(&self, id: i64) -> AppResult<User>;
+
+    async fn find_user_by_access_key(&self, access_key: &str) -> AppResult<User>;
+
+    async fn create_device(
+        &self,
+        request: &CreateDeviceRequest,
+        user: &User,
+    ) -> AppResult<UserDevice>;
+
+    async fn find_device_by_access_token(&self, access_token: &str) -> AppResult<UserDevice>;
+
+    async fn create_pairing_code(&self, user: &User) -> AppResult<PairingCode>;
+
+    /// Enrolls a device with a pairing code, `None` meaning the code is wrong or expired.
+    async fn redeem_pairing_code(
+        &self,
+        code: &str,
+        device_name: &str,
+    ) -> AppResult<Option<UserDevice>>;
+
+    async fn create_podcast(&self, request: &CreatePodcastRequest) -> AppResult<()>;
+
+    async fn find_podcast_by_guid(&self, user: &User, guid: &str) -> AppResult<Podcast>;
+
+    async fn list_podcast_episodes(&self, podcast: &Podcast) -> AppResult<Vec<PodcastEpisode>>;
+
+    async fn sync_upsert_podcast(
+        &self,
+        user: &User,
+        sync_podcast: &SyncPodcast,
+    ) -> AppResult<SaveResult>;
+
+    async fn sync_upsert_episodes(
+        &self,
+        user: &User,
+        podcast_guid: &str,
+        episodes: &[SyncPodcastEpisode],
+    ) -> AppResult<()>;
+
+    async fn sync_upsert_podcast_settings(
+        &self,
+        user: &User,
+        podcast_guid: &str,
+        settings: &SyncPodcastSettings,
+    ) -> AppResult<SaveResult>;
+
+    async fn sync_upsert_bookmarks(
+        &self,
+        user: &User,
+        podcast_guid: &str,
+        bookmarks: &[SyncBookmark],
+    ) -> AppResult<()>;
+
+    async fn get_sync_response(&self, user: &User) -> AppResult<SyncStateResponse>;
+
+    async fn update_progress(
+        &self,
+        user_id: i64,
+        request: ProgressUpdateRequest,
+    ) -> AppResult<SaveResult>;
+
+    /// Folds the podcast `from_guid` into `into_guid`: episode progress, settings and bookmarks
+    /// are upserted into the target, keeping whichever side is newer, and the old podcast is
+    /// marked as deleted so other devices drop it. All or nothing.
+    async fn merge_podcasts(
+        &self,
+        user: &User,
+        from_guid: &str,
+        into_guid: &str,
+    ) -> AppResult<SaveResult>;
+}
@@ -0,0 +1,713 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, SubsecRound, Utc};
+use diesel::result::DatabaseErrorKind;
+use dimppl_shared::progress::ProgressUpdateRequest;
+use dimppl_shared::sync::{
+    CreatePodcastRequest, SyncBookmark, SyncPodcast, SyncPodcastEpisode, SyncPodcastSettings,
+    SyncStateResponse,
+};
+
+use crate::error_handling::{AppError, AppResult};
+use crate::models::pairing_code::{normalize_code, NewPairingCode};
+use crate::models::podcast::SaveResult;
+use crate::models::user::NewUser;
+use crate::models::user_device::{CreateDeviceRequest, NewUserDevice};
+use crate::models::{
+    Bookmark, PairingCode, Podcast, PodcastEpisode, PodcastSettings, User, UserDevice,
+};
+use crate::storage::Storage;
+
+/// In-memory `Storage`, used by the endpoint tests. Every table keeps the same unique constraints
+/// and timestamps are truncated to microseconds, like Postgres does.
+#[derive(Clone, Default)]
+pub struct MemoryStorage {
+    data: Arc<Mutex<MemoryData>>,
+}
+
+#[derive(Clone, Default)]
+struct MemoryData {
+    users: Vec<User>,
+    user_devices: Vec<UserDevice>,
+    pairing_codes: Vec<PairingCode>,
+    podcasts: Vec<Podcast>,
+    podcast_episodes: Vec<PodcastEpisode>,
+    podcast_settings: Vec<PodcastSettings>,
+    bookmarks: Vec<Bookmark>,
+    last_id: i64,
+}
+
+impl MemoryData {
+    fn next_id(&mut self) -> i64 {
+        self.last_id += 1;
+        self.last_id
+    }
+
+    fn insert_device(&mut self, new_device: NewUserDevice) -> AppResult<UserDevice> {
+        if self
+            .user_devices
+            .iter()
+            .any(|d| d.user_id == new_device.user_id && d.name == new_device.name)
+        {
+            return Err(unique_violation("user_devices_user_id_name_key"));
+        }
+        if self
+            .user_devices
+            .iter()
+            .any(|d| d.access_token == new_device.access_token)
+        {
+            return Err(unique_violation("user_devices_access_token_key"));
+        }
+        let device = UserDevice {
+            id: self.next_id(),
+            user_id: new_device.user_id,
+            name: new_device.name,
+            last_session_at: truncate(new_device.last_session_at),
+            access_token: new_device.access_token,
+        };
+        self.user_devices.push(device.clone());
+        Ok(device)
+    }
+
+    fn find_podcast(&self, user_id: i64, guid: &str) -> AppResult<&Podcast> {
+        self.podcasts
+            .iter()
+            .find(|p| p.user_id == user_id && p.guid == guid)
+            .ok_or_else(not_found)
+    }
+
+    fn create_podcast(&mut self, request: &CreatePodcastRequest) -> AppResult<()> {
+        if self.find_podcast(request.user_id, &request.guid).is_ok() {
+            return Err(unique_violation("podcasts_user_id_guid_key"));
+        }
+        let podcast_id = self.next_id();
+        self.podcasts.push(Podcast {
+            id: podcast_id,
+            user_id: request.user_id,
+            guid: request.guid.clone(),
+            url: request.url.clone(),
+            deleted_at: None,
+            updated_at: NaiveDateTime::default(),
+        });
+        for episode_request in &request.episodes {
+            if self
+                .podcast_episodes
+                .iter()
+                .any(|e| e.podcast_id == podcast_id && e.guid == episode_request.guid)
+            {
+                return Err(unique_violation("podcast_episodes_podcast_id_guid_key"));
+            }
+            let episode_id = self.next_id();
+            self.podcast_episodes.push(PodcastEpisode {
+                id: episode_id,
+                podcast_id,
+                guid: episode_request.guid.clone(),
+                url: episode_request.url.clone(),
+                listened_seconds: 0,
+                completed: false,
+                updated_at: NaiveDateTime::default(),
+            });
+        }
+        Ok(())
+    }
+
+    fn list_podcast_episodes(&self, podcast_id: i64) -> Vec<PodcastEpisode> {
+        let mut episodes = self
+            .podcast_episodes
+            .iter()
+            .filter(|e| e.podcast_id == podcast_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        episodes.sort_by(|a, b| a.guid.cmp(&b.guid));
+        episodes
+    }
+
+    fn upsert_podcast(&mut self, user: &User, sync_podcast: &SyncPodcast) -> SaveResult {
+        let updated_at = truncate(sync_podcast.updated_at);
+        let existing = self
+            .podcasts
+            .iter_mut()
+            .find(|p| p.user_id == user.id && p.guid == sync_podcast.guid);
+        if let Some(podcast) = existing {
+            if podcast.updated_at >= updated_at {
+                return SaveResult::NotSaved;
+            }
+            podcast.url.clone_from(&sync_podcast.url);
+            podcast.deleted_at = sync_podcast.deleted_at.map(truncate);
+            podcast.updated_at = updated_at;
+            return SaveResult::Saved;
+        }
+        let id = self.next_id();
+        self.podcasts.push(Podcast {
+            id,
+            user_id: user.id,
+            guid: sync_podcast.guid.clone(),
+            url: sync_podcast.url.clone(),
+            deleted_at: sync_podcast.deleted_at.map(truncate),
+            updated_at,
+        });
+        SaveResult::Saved
+    }
+
+    fn upsert_episodes(
+        &mut self,
+        user: &User,
+        podcast_guid: &str,
+        episodes: &[SyncPodcastEpisode],
+    ) -> AppResult<()> {
+        let podcast_id = self.find_podcast(user.id, podcast_guid)?.id;
+        for episode in episodes {
+            let updated_at = truncate(episode.updated_at);
+            let existing = self
+                .podcast_episodes
+                .iter_mut()
+                .find(|e| e.podcast_id == podcast_id && e.guid == episode.guid);
+            if let Some(existing) = existing {
+                if existing.updated_at < updated_at {
+                    existing.url.clone_from(&episode.url);
+                    existing.listened_seconds = episode.listened_seconds;
+                    existing.completed = episode.completed;
+                    existing.updated_at = updated_at;
+                }
+                continue;
+            }
+            let id = self.next_id();
+            self.podcast_episodes.push(PodcastEpisode {
+                id,
+                podcast_id,
+                guid: episode.guid.clone(),
+                url: episode.url.clone(),
+                listened_seconds: episode.listened_seconds,
+                completed: episode.completed,
+                updated_at,
+            });
+        }
+        Ok(())
+    }
+
+    fn upsert_podcast_settings(
+        &mut self,
+        user: &User,
+        podcast_guid: &str,
+        settings: &SyncPodcastSettings,
+    ) -> AppResult<SaveResult> {
+        let podcast_id = self.find_podcast(user.id, podcast_guid)?.id;
+        let updated_at = truncate(settings.updated_at);
+        let existing = self
+            .podcast_settings
+            .iter()
+            .position(|s| s.podcast_id == podcast_id);
+        let id = match existing {
+            Some(index) if self.podcast_settings[index].updated_at >= updated_at => {
+                return Ok(SaveResult::NotSaved);
+            }
+            Some(index) => self.podcast_settings.remove(index).id,
+            None => self.next_id(),
+        };
+        self.podcast_settings.push(PodcastSettings {
+            id,
+            podcast_id,
+            playback_speed: settings.playback_speed,
+            skip_intro_seconds: settings.skip_intro_seconds,
+            auto_download: settings.auto_download.as_str().into(),
+            sort_order: settings.sort_order.as_str().into(),
+            notifications: settings.notifications,
+            updated_at,
+        });
+        Ok(SaveResult::Saved)
+    }
+
+    fn upsert_bookmarks(
+        &mut self,
+        user: &User,
+        podcast_guid: &str,
+        bookmarks: &[SyncBookmark],
+    ) -> AppResult<()> {
+        let podcast_id = self.find_podcast(user.id, podcast_guid)?.id;
+        for bookmark in bookmarks {
+            let updated_at = truncate(bookmark.updated_at);
+            let existing = self
+                .bookmarks
+                .iter_mut()
+                .find(|b| b.podcast_id == podcast_id && b.guid == bookmark.guid);
+            if let Some(existing) = existing {
+                if existing.updated_at < updated_at {
+                    existing.start_seconds = bookmark.start_seconds;
+                    existing.end_seconds = bookmark.end_seconds;
+                    existing.note.clone_from(&bookmark.note);
+                    existing.deleted_at = bookmark.deleted_at.map(truncate);
+                    existing.updated_at = updated_at;
+                }
+                continue;
+            }
+            let id = self.next_id();
+            self.bookmarks.push(Bookmark {
+                id,
+                podcast_id,
+                guid: bookmark.guid.clone(),
+                episode_guid: bookmark.episode_guid.clone(),
+                start_seconds: bookmark.start_seconds,
+                end_seconds: bookmark.end_seconds,
+                note: bookmark.note.clone(),
+                created_at: truncate(bookmark.created_at),
+                deleted_at: bookmark.deleted_at.map(truncate),
+                updated_at,
+            });
+        }
+        Ok(())
+    }
+
+    fn sync_response(&self, user: &User) -> SyncStateResponse {
+        let mut podcasts = self
+            .podcasts
+            .iter()
+            .filter(|p| p.user_id == user.id)
+            .cloned()
+            .collect::<Vec<_>>();
+        podcasts.sort_by(|a, b| a.guid.cmp(&b.guid));
+        let mut map: HashMap<String, Vec<SyncPodcastEpisode>> = HashMap::new();
+        let mut settings: HashMap<String, SyncPodcastSettings> = HashMap::new();
+        let mut bookmarks: HashMap<String, Vec<SyncBookmark>> = HashMap::new();
+        for podcast in &podcasts {
+            let episodes = self
+                .list_podcast_episodes(podcast.id)
+                .into_iter()
+                .map(|e| e.into())
+                .collect::<Vec<_>>();
+            map.insert(podcast.guid.clone(), episodes);
+            if let Some(podcast_settings) = self
+                .podcast_settings
+                .iter()
+                .find(|s| s.podcast_id == podcast.id)
+            {
+                settings.insert(podcast.guid.clone(), podcast_settings.clone().into());
+            }
+            let mut podcast_bookmarks = self
+                .bookmarks
+                .iter()
+                .filter(|b| b.podcast_id == podcast.id)
+                .cloned()
+                .collect::<Vec<_>>();
+            podcast_bookmarks.sort_by(|a, b| a.guid.cmp(&b.guid));
+            bookmarks.insert(
+                podcast.guid.clone(),
+                podcast_bookmarks.into_iter().map(|b| b.into()).collect(),
+            );
+        }
+        SyncStateResponse {
+            podcasts: podcasts.into_iter().map(|p| p.into()).collect(),
+            episodes: map,
+            settings,
+            bookmarks,
+        }
+    }
+
+    fn merge_podcasts(
+        &mut self,
+        user: &User,
+        from_guid: &str,
+        into_guid: &str,
+    ) -> AppResult<SaveResult> {
+        if from_guid == into_guid {
+            return Ok(SaveResult::NotSaved);
+        }
+        let state = self.sync_response(user);
+        let Some(from) = state
+            .podcasts
+            .iter()
+            .find(|p| p.guid == from_guid && p.deleted_at.is_none())
+        else {
+            return Ok(SaveResult::NotSaved);
+        };
+        let now = Utc::now().naive_utc();
+        let target_is_live = state
+            .podcasts
+            .iter()
+            .any(|p| p.guid == into_guid && p.deleted_at.is_none());
+        if !target_is_live {
+            let target = SyncPodcast {
+                guid: into_guid.into(),
+                url: from.url.clone(),
+                deleted_at: None,
+                updated_at: now,
+            };
+            self.upsert_podcast(user, &target);
+        }
+        if let Some(episodes) = state.episodes.get(from_guid) {
+            self.upsert_episodes(user, into_guid, episodes)?;
+        }
+        if let Some(settings) = state.settings.get(from_guid) {
+            self.upsert_podcast_settings(user, into_guid, settings)?;
+        }
+        if let Some(bookmarks) = state.bookmarks.get(from_guid) {
+            self.upsert_bookmarks(user, into_guid, bookmarks)?;
+        }
+        let deleted = SyncPodcast {
+            deleted_at: Some(now),
+            updated_at: now,
+            ..from.clone()
+        };
+        Ok(self.upsert_podcast(user, &deleted))
+    }
+}
+
+impl MemoryStorage {
+    /// Applies `change` to a copy of the data and keeps the copy only if it succeeds, so a failure
+    /// halfway leaves nothing behind, as in a Postgres transaction.
+    fn transaction<T>(&self, change: impl FnOnce(&mut MemoryData) -> AppResult<T>) -> AppResult<T> {
+        let mut data = self.data.lock().unwrap();
+        let mut staged = data.clone();
+        let result = change(&mut staged)?;
+        *data = staged;
+        Ok(result)
+    }
+}
+
+fn not_found() -> AppError {
+    anyhow!("Record not found").into()
+}
+
+fn unique_violation(constraint: &str) -> AppError {
+    diesel::result::Error::DatabaseError(
+        DatabaseErrorKind::UniqueViolation,
+        Box::new(format!(
+            "duplicate key value violates unique constraint \"{constraint}\""
+        )),
+    )
+    .into()
+}
+
+fn truncate(timestamp: NaiveDateTime) -> NaiveDateTime {
+    timestamp.trunc_subsecs(6)
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn create_user(&self, new_user: &NewUser) -> AppResult<User> {
+        let mut data = self.data.lock().unwrap();
+        if data
+            .users
+            .iter()
+            .any(|u| u.access_key == new_user.access_key)
+        {
+            return Err(unique_violation("users_access_key_key"));
+        }
+        let user = User {
+            id: data.next_id(),
+            access_key: new_user.access_key.clone(),
+        };
+        data.users.push(user.clone());
+        Ok(user)
+    }
+
+    async fn find_user(&self, id: i64) -> AppResult<User> {
+        let data = self.data.lock().unwrap();
+        data.users
+            .iter()
+            .find(|u| u.id == id)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn find_user_by_access_key(&self, access_key: &str) -> AppResult<User> {
+        let data = self.data.lock().unwrap();
+        data.users
+            .iter()
+            .find(|u| u.access_key == access_key)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn create_device(
+        &self,
+        request: &CreateDeviceRequest,
+        user: &User,
+    ) -> AppResult<UserDevice> {
+        let mut data = self.data.lock().unwrap();
+        data.insert_device(NewUserDevice::new(request, user))
+    }
+
+    async fn find_device_by_access_token(&self, access_token: &str) -> AppResult<UserDevice> {
+        let data = self.data.lock().unwrap();
+        data.user_devices
+            .iter()
+            .find(|d| d.access_token == access_token)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn create_pairing_code(&self, user: &User) -> AppResult<PairingCode> {
+        let new_code = NewPairingCode::new(user);
+        let mut data = self.data.lock().unwrap();
+        let now = Utc::now().naive_utc();
+        data.pairing_codes.retain(|c| c.expires_at > now);
+        if data.pairing_codes.iter().any(|c| c.code == new_code.code) {
+            return Err(unique_violation("pairing_codes_code_key"));
+        }
+        let pairing_code = PairingCode {
+            id: data.next_id(),
+            user_id: new_code.user_id,
+            code: new_code.code,
+            expires_at: truncate(new_code.expires_at),
+        };
+        data.pairing_codes.push(pairing_code.clone());
+        Ok(pairing_code)
+    }
+
+    async fn redeem_pairing_code(
+        &self,
+        code: &str,
+        device_name: &str,
+    ) -> AppResult<Option<UserDevice>> {
+        let code = normalize_code(code);
+        let mut data = self.data.lock().unwrap();
+        let now = Utc::now().naive_utc();
+        let Some(index) = data
+            .pairing_codes
+            .iter()
+            .position(|c| c.code == code && c.expires_at > now)
+        else {
+            return Ok(None);
+        };
+        let user_id = data.pairing_codes[index].user_id;
+        let user = data
+            .users
+            .iter()
+            .find(|u| u.id == user_id)
+            .cloned()
+            .ok_or_else(not_found)?;
+        let request = CreateDeviceRequest {
+            user_access_key: user.access_key.clone(),
+            device_name: device_name.to_string(),
+        };
+        let device = data.insert_device(NewUserDevice::new(&request, &user))?;
+        data.pairing_codes.remove(index);
+        Ok(Some(device))
+    }
+
+    async fn create_podcast(&self, request: &CreatePodcastRequest) -> AppResult<()> {
+        self.transaction(|data| data.create_podcast(request))
+    }
+
+    async fn find_podcast_by_guid(&self, user: &User, guid: &str) -> AppResult<Podcast> {
+        let data = self.data.lock().unwrap();
+        data.find_podcast(user.id, guid).cloned()
+    }
+
+    async fn list_podcast_episodes(&self, podcast: &Podcast) -> AppResult<Vec<PodcastEpisode>> {
+        let data = self.data.lock().unwrap();
+        Ok(data.list_podcast_episodes(podcast.id))
+    }
+
+    async fn sync_upsert_podcast(
+        &self,
+        user: &User,
+        sync_podcast: &SyncPodcast,
+    ) -> AppResult<SaveResult> {
+        let mut data = self.data.lock().unwrap();
+        Ok(data.upsert_podcast(user, sync_podcast))
+    }
+
+    async fn sync_upsert_episodes(
+        &self,
+        user: &User,
+        podcast_guid: &str,
+        episodes: &[SyncPodcastEpisode],
+    ) -> AppResult<()> {
+        let mut data = self.data.lock().unwrap();
+        data.upsert_episodes(user, podcast_guid, episodes)
+    }
+
+    async fn sync_upsert_podcast_settings(
+        &self,
+        user: &User,
+        podcast_guid: &str,
+        settings: &SyncPodcastSettings,
+    ) -> AppResult<SaveResult> {
+        let mut data = self.data.lock().unwrap();
+        data.upsert_podcast_settings(user, podcast_guid, settings)
+    }
+
+    async fn sync_upsert_bookmarks(
+        &self,
+        user: &User,
+        podcast_guid: &str,
+        bookmarks: &[SyncBookmark],
+    ) -> AppResult<()> {
+        let mut data = self.data.lock().unwrap();
+        data.upsert_bookmarks(user, podcast_guid, bookmarks)
+    }
+
+    async fn get_sync_response(&self, user: &User) -> AppResult<SyncStateResponse> {
+        let data = self.data.lock().unwrap();
+        Ok(data.sync_response(user))
+    }
+
+    async fn update_progress(
+        &self,
+        user_id: i64,
+        request: ProgressUpdateRequest,
+    ) -> AppResult<SaveResult> {
+        let mut data = self.data.lock().unwrap();
+        let podcast_id = data.find_podcast(user_id, &request.podcast_guid)?.id;
+        let updated_at = truncate(request.updated_at);
+        let episode = data.podcast_episodes.iter_mut().find(|e| {
+            e.podcast_id == podcast_id
+                && e.guid == request.episode_guid
+                && e.updated_at < updated_at
+        });
+        let Some(episode) = episode else {
+            return Ok(SaveResult::NotSaved);
+        };
+        episode.listened_seconds = request.listened_seconds;
+        episode.completed = request.completed;
+        episode.updated_at = updated_at;
+        Ok(SaveResult::Saved)
+    }
+
+    async fn merge_podcasts(
+        &self,
+        user: &User,
+        from_guid: &str,
+        into_guid: &str,
+    ) -> AppResult<SaveResult> {
+        self.transaction(|data| data.merge_podcasts(user, from_guid, into_guid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{test_podcast_with_episodes, test_user_and_device};
+    use chrono::{Local, TimeDelta};
+    use dimppl_shared::sync::CreatePodcastEpisodeRequest;
+
+    #[tokio::test]
+    async fn test_timestamps_are_truncated_to_microseconds() {
+        let storage = MemoryStorage::default();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
+        let new_podcast = SyncPodcast {
+            url: "https://google.com".into(),
+            guid: "guid".into(),
+            deleted_at: None,
+            updated_at: NaiveDateTime::parse_from_str(
+                "2024-01-01 10:00:00.123456789",
+                "%Y-%m-%d %H:%M:%S%.f",
+            )
+            .unwrap(),
+        };
+        storage
+            .sync_upsert_podcast(&user, &new_podcast)
+            .await
+            .unwrap();
+        let podcast = storage.find_podcast_by_guid(&user, "guid").await.unwrap();
+        assert_eq!(
+            123456000,
+            podcast.updated_at.and_utc().timestamp_subsec_nanos()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_podcast_uniqueness() {
+        let storage = MemoryStorage::default();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
+        let request = CreatePodcastRequest {
+            user_id: user.id,
+            url: "https://example.com/podcast.rss".into(),
+            guid: "guid".into(),
+            episodes: vec![],
+        };
+        assert!(storage.create_podcast(&request).await.is_ok());
+        assert!(storage.create_podcast(&request).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_create_podcast_leaves_nothing_behind() {
+        let storage = MemoryStorage::default();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
+        let episode = CreatePodcastEpisodeRequest {
+            url: "https://example.com/episode.mp3".into(),
+            guid: "episode".into(),
+        };
+        let request = CreatePodcastRequest {
+            user_id: user.id,
+            url: "https://example.com/podcast.rss".into(),
+            guid: "guid".into(),
+            episodes: vec![episode.clone(), episode],
+        };
+        assert!(storage.create_podcast(&request).await.is_err());
+        assert!(storage.find_podcast_by_guid(&user, "guid").await.is_err());
+        let state = storage.get_sync_response(&user).await.unwrap();
+        assert!(state.podcasts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_progress_is_scoped_to_user() {
+        let storage = MemoryStorage::default();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
+        let (podcast, episodes) = test_podcast_with_episodes(&user, &storage).await.unwrap();
+        let other_user = storage.create_user(&NewUser::default()).await.unwrap();
+        let request = ProgressUpdateRequest {
+            podcast_guid: podcast.guid.clone(),
+            episode_guid: episodes[1].guid.clone(),
+            listened_seconds: 250,
+            completed: true,
+            updated_at: Local::now().naive_utc() + TimeDelta::days(1),
+        };
+        assert!(storage
+            .update_progress(other_user.id, request)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_merge_podcasts_keeps_newest_progress() {
+        let storage = MemoryStorage::default();
+        let (user, _device) = test_user_and_device(&storage).await.unwrap();
+        let (podcast, _episodes) = test_podcast_with_episodes(&user, &storage).await.unwrap();
+        let target = SyncPodcast {
+            guid: "stable".into(),
+            url: podcast.url.clone(),
+            deleted_at: None,
+            updated_at: Local::now().naive_utc(),
+        };
+        storage.sync_upsert_podcast(&user, &target).await.unwrap();
+        let target_episodes = vec![
+            SyncPodcastEpisode {
+                guid: "ep1".into(),
+                url: "https://ep1".into(),
+                listened_seconds: 10,
+                completed: false,
+                updated_at: NaiveDateTime::default(),
+            },
+            SyncPodcastEpisode {
+                guid: "ep2".into(),
+                url: "https://ep2".into(),
+                listened_seconds: 900,
+                completed: false,
+                updated_at: Local::now().naive_utc() + TimeDelta::days(1),
+            },
+        ];
+        storage
+            .sync_upsert_episodes(&user, "stable", &target_episodes)
+            .await
+            .unwrap();
+
+        let result = storage
+            .merge_podcasts(&user, &podcast.guid, "stable")
+            .await
+            .unwrap();
+
+        assert_eq!(SaveResult::Saved, result);
+        let state = storage.get_sync_response(&user).await.unwrap();
+        let old = state.podcasts.iter().find(|p| p.guid == "guid").unwrap();
+        assert!(old.deleted_at.is_some());
+        let merged = &state.episodes["stable"];
+        assert_eq!(300, merged[0].listened_seconds);
+        assert!(merged[0].completed);
+        assert_eq!(900, merged[1].listened_seconds);
+    }
+}
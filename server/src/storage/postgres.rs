@@ -0,0 +1,152 @@
+use async_trait::async_trait;
+use dimppl_shared::progress::ProgressUpdateRequest;
+use dimppl_shared::sync::{
+    CreatePodcastRequest, SyncBookmark, SyncPodcast, SyncPodcastEpisode, SyncPodcastSettings,
+    SyncStateResponse,
+};
+
+use crate::database::Pool;
+use crate::error_handling::AppResult;
+use crate::models::podcast::SaveResult;
+use crate::models::user::NewUser;
+use crate::models::user_device::CreateDeviceRequest;
+use crate::models::{
+    bookmark, episode, pairing_code, podcast, user, user_device, PairingCode, Podcast,
+    PodcastEpisode, User, UserDevice,
+};
+use crate::storage::Storage;
+
+#[derive(Clone)]
+pub struct PgStorage {
+    pool: Pool,
+}
+
+impl PgStorage {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl Storage for PgStorage {
+    async fn create_user(&self, new_user: &NewUser) -> AppResult<User> {
+        let mut conn = self.pool.get().await?;
+        user::create(new_user, &mut conn).await
+    }
+
+    async fn find_user(&self, id: i64) -> AppResult<User> {
+        let mut conn = self.pool.get().await?;
+        user::find_one(id, &mut conn).await
+    }
+
+    async fn find_user_by_access_key(&self, access_key: &str) -> AppResult<User> {
+        let mut conn = self.pool.get().await?;
+        user::find_by_access_key(access_key, &mut conn).await
+    }
+
+    async fn create_device(
+        &self,
+        request: &CreateDeviceRequest,
+        user: &User,
+    ) -> AppResult<UserDevice> {
+        let mut conn = self.pool.get().await?;
+        user_device::create(request, user, &mut conn).await
+    }
+
+    async fn find_device_by_access_token(&self, access_token: &str) -> AppResult<UserDevice> {
+        let mut conn = self.pool.get().await?;
+        user_device::find_by_access_token(access_token, &mut conn).await
+    }
+
+    async fn create_pairing_code(&self, user: &User) -> AppResult<PairingCode> {
+        let mut conn = self.pool.get().await?;
+        pairing_code::create(user, &mut conn).await
+    }
+
+    async fn redeem_pairing_code(
+        &self,
+        code: &str,
+        device_name: &str,
+    ) -> AppResult<Option<UserDevice>> {
+        let mut conn = self.pool.get().await?;
+        pairing_code::redeem(code, device_name, &mut conn).await
+    }
+
+    async fn create_podcast(&self, request: &CreatePodcastRequest) -> AppResult<()> {
+        let mut conn = self.pool.get().await?;
+        podcast::create(request, &mut conn).await
+    }
+
+    async fn find_podcast_by_guid(&self, user: &User, guid: &str) -> AppResult<Podcast> {
+        let mut conn = self.pool.get().await?;
+        podcast::find_by_guid(user.id, guid, &mut conn).await
+    }
+
+    async fn list_podcast_episodes(&self, podcast: &Podcast) -> AppResult<Vec<PodcastEpisode>> {
+        let mut conn = self.pool.get().await?;
+        podcast::list_episodes(podcast.id, &mut conn).await
+    }
+
+    async fn sync_upsert_podcast(
+        &self,
+        user: &User,
+        sync_podcast: &SyncPodcast,
+    ) -> AppResult<SaveResult> {
+        let mut conn = self.pool.get().await?;
+        podcast::sync_upsert_podcast(user, sync_podcast, &mut conn).await
+    }
+
+    async fn sync_upsert_episodes(
+        &self,
+        user: &User,
+        podcast_guid: &str,
+        episodes: &[SyncPodcastEpisode],
+    ) -> AppResult<()> {
+        let mut conn = self.pool.get().await?;
+        podcast::sync_upsert_episodes(user, podcast_guid, episodes, &mut conn).await
+    }
+
+    async fn sync_upsert_podcast_settings(
+        &self,
+        user: &User,
+        podcast_guid: &str,
+        settings: &SyncPodcastSettings,
+    ) -> AppResult<SaveResult> {
+        let mut conn = self.pool.get().await?;
+        podcast::sync_upsert_settings(user, podcast_guid, settings, &mut conn).await
+    }
+
+    async fn sync_upsert_bookmarks(
+        &self,
+        user: &User,
+        podcast_guid: &str,
+        bookmarks: &[SyncBookmark],
+    ) -> AppResult<()> {
+        let mut conn = self.pool.get().await?;
+        bookmark::sync_upsert_bookmarks(user, podcast_guid, bookmarks, &mut conn).await
+    }
+
+    async fn get_sync_response(&self, user: &User) -> AppResult<SyncStateResponse> {
+        let mut conn = self.pool.get().await?;
+        podcast::get_sync_response(user, &mut conn).await
+    }
+
+    async fn update_progress(
+        &self,
+        user_id: i64,
+        request: ProgressUpdateRequest,
+    ) -> AppResult<SaveResult> {
+        let mut conn = self.pool.get().await?;
+        episode::update_progress(user_id, request, &mut conn).await
+    }
+
+    async fn merge_podcasts(
+        &self,
+        user: &User,
+        from_guid: &str,
+        into_guid: &str,
+    ) -> AppResult<SaveResult> {
+        let mut conn = self.pool.get().await?;
+        podcast::merge_podcasts(user, from_guid, into_guid, &mut conn).await
+    }
+}
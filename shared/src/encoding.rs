@@ -0,0 +1,263 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub type EncodingResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+pub const ACCEPT_ENCODING_ALL: &str = "zstd, gzip";
+
+/// Largest payload, once decompressed, that `ContentEncoding::decompress` gives back.
+pub const MAX_BODY: usize = 32 * 1024 * 1024;
+
+/// Returned by `ContentEncoding::decompress` for payloads that decompress to more than `MAX_BODY`.
+#[derive(Debug)]
+pub struct PayloadTooLarge;
+
+impl Display for PayloadTooLarge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Payload is larger than {MAX_BODY} bytes once decompressed"
+        )
+    }
+}
+
+impl Error for PayloadTooLarge {}
+
+/// Serialization format of a payload, picked through `Content-Type`/`Accept`. JSON is the default
+/// so clients that don't know about the other formats keep working.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ContentType {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl ContentType {
+    pub fn mime(&self) -> &'static str {
+        match self {
+            ContentType::Json => "application/json",
+            ContentType::MessagePack => "application/msgpack",
+        }
+    }
+
+    /// Parses a single media type, ignoring parameters such as `charset`.
+    pub fn from_mime(value: &str) -> Option<Self> {
+        let essence = value
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        match essence.as_str() {
+            "application/json" => Some(ContentType::Json),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(ContentType::MessagePack)
+            }
+            _ => None,
+        }
+    }
+
+    /// Picks the response format for an `Accept` header, falling back to JSON.
+    pub fn negotiate(accept: Option<&str>) -> Self {
+        let Some(accept) = accept else {
+            return ContentType::default();
+        };
+        preferences(accept)
+            .into_iter()
+            .find_map(|value| ContentType::from_mime(&value))
+            .unwrap_or_default()
+    }
+
+    pub fn serialize<T: Serialize>(&self, value: &T) -> EncodingResult<Vec<u8>> {
+        Ok(match self {
+            ContentType::Json => serde_json::to_vec(value)?,
+            ContentType::MessagePack => rmp_serde::to_vec_named(value)?,
+        })
+    }
+
+    pub fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> EncodingResult<T> {
+        Ok(match self {
+            ContentType::Json => serde_json::from_slice(bytes)?,
+            ContentType::MessagePack => rmp_serde::from_slice(bytes)?,
+        })
+    }
+}
+
+/// Compression applied to a payload, picked through `Content-Encoding`/`Accept-Encoding`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ContentEncoding {
+    #[default]
+    Identity,
+    Gzip,
+    Zstd,
+}
+
+impl ContentEncoding {
+    pub fn token(&self) -> &'static str {
+        match self {
+            ContentEncoding::Identity => "identity",
+            ContentEncoding::Gzip => "gzip",
+            ContentEncoding::Zstd => "zstd",
+        }
+    }
+
+    pub fn from_token(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "identity" => Some(ContentEncoding::Identity),
+            "gzip" | "x-gzip" => Some(ContentEncoding::Gzip),
+            "zstd" => Some(ContentEncoding::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Picks the response compression for an `Accept-Encoding` header. zstd wins over gzip when
+    /// both are acceptable, no header means no compression.
+    pub fn negotiate(accept_encoding: Option<&str>) -> Self {
+        let Some(accept_encoding) = accept_encoding else {
+            return ContentEncoding::Identity;
+        };
+        let accepted = preferences(accept_encoding)
+            .into_iter()
+            .filter_map(|value| ContentEncoding::from_token(&value))
+            .collect::<Vec<_>>();
+        [ContentEncoding::Zstd, ContentEncoding::Gzip]
+            .into_iter()
+            .find(|encoding| accepted.contains(encoding))
+            .unwrap_or_default()
+    }
+
+    pub fn compress(&self, bytes: Vec<u8>) -> EncodingResult<Vec<u8>> {
+        Ok(match self {
+            ContentEncoding::Identity => bytes,
+            ContentEncoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(&bytes)?;
+                encoder.finish()?
+            }
+            ContentEncoding::Zstd => zstd::encode_all(bytes.as_slice(), 0)?,
+        })
+    }
+
+    /// Fails with `PayloadTooLarge` rather than decompressing more than `MAX_BODY` bytes.
+    pub fn decompress(&self, bytes: Vec<u8>) -> EncodingResult<Vec<u8>> {
+        let decoder: Box<dyn Read + '_> = match self {
+            ContentEncoding::Identity => {
+                if bytes.len() > MAX_BODY {
+                    return Err(PayloadTooLarge.into());
+                }
+                return Ok(bytes);
+            }
+            ContentEncoding::Gzip => Box::new(GzDecoder::new(bytes.as_slice())),
+            ContentEncoding::Zstd => Box::new(zstd::Decoder::new(bytes.as_slice())?),
+        };
+        let mut decoded = Vec::new();
+        decoder
+            .take(MAX_BODY as u64 + 1)
+            .read_to_end(&mut decoded)?;
+        if decoded.len() > MAX_BODY {
+            return Err(PayloadTooLarge.into());
+        }
+        Ok(decoded)
+    }
+}
+
+/// Values of a comma-separated header with `q=0` entries dropped, ordered by quality.
+fn preferences(header: &str) -> Vec<String> {
+    let mut values = header
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let value = parts.next()?.trim().to_string();
+            let quality = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (!value.is_empty() && quality > 0.0).then_some((value, quality))
+        })
+        .collect::<Vec<_>>();
+    values.sort_by(|a, b| b.1.total_cmp(&a.1));
+    values.into_iter().map(|(value, _)| value).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferences() {
+        assert_eq!(
+            vec!["zstd", "br", "gzip"],
+            preferences("gzip;q=0.5, zstd, identity;q=0, br;q=0.8")
+        );
+        assert_eq!(vec!["gzip", "zstd"], preferences("gzip, zstd;q=invalid, "));
+        assert!(preferences("").is_empty());
+    }
+
+    #[test]
+    fn test_negotiate_content_type() {
+        assert_eq!(ContentType::Json, ContentType::negotiate(None));
+        assert_eq!(
+            ContentType::MessagePack,
+            ContentType::negotiate(Some("application/json;q=0.5, application/msgpack"))
+        );
+        assert_eq!(
+            ContentType::Json,
+            ContentType::negotiate(Some("application/msgpack;q=0, text/html"))
+        );
+    }
+
+    #[test]
+    fn test_negotiate_content_encoding() {
+        assert_eq!(ContentEncoding::Identity, ContentEncoding::negotiate(None));
+        assert_eq!(
+            ContentEncoding::Zstd,
+            ContentEncoding::negotiate(Some(ACCEPT_ENCODING_ALL))
+        );
+        assert_eq!(
+            ContentEncoding::Gzip,
+            ContentEncoding::negotiate(Some("zstd;q=0, gzip"))
+        );
+        assert_eq!(
+            ContentEncoding::Identity,
+            ContentEncoding::negotiate(Some("br"))
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let payload = b"{\"podcasts\":[]}".repeat(100);
+        for encoding in [
+            ContentEncoding::Identity,
+            ContentEncoding::Gzip,
+            ContentEncoding::Zstd,
+        ] {
+            let compressed = encoding.compress(payload.clone()).unwrap();
+            assert_eq!(payload, encoding.decompress(compressed).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_decompress_limit() {
+        for encoding in [
+            ContentEncoding::Identity,
+            ContentEncoding::Gzip,
+            ContentEncoding::Zstd,
+        ] {
+            let payload = vec![0; MAX_BODY];
+            let compressed = encoding.compress(payload).unwrap();
+            assert_eq!(MAX_BODY, encoding.decompress(compressed).unwrap().len());
+
+            let payload = vec![0; MAX_BODY + 1];
+            let compressed = encoding.compress(payload).unwrap();
+            let error = encoding.decompress(compressed).unwrap_err();
+            assert!(error.is::<PayloadTooLarge>());
+        }
+    }
+}
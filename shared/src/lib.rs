@@ -1,3 +1,5 @@
 pub mod sync;
 mod websocket;
 pub mod progress;
+pub mod encoding;
+pub mod protocol;
\ No newline at end of file
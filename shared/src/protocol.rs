@@ -0,0 +1,111 @@
+/// Header carrying the protocol version, sent by clients on `/sync` and `/ws` and echoed back by
+/// the server with the version it picked.
+pub const PROTOCOL_VERSION_HEADER: &str = "x-dimppl-protocol-version";
+
+/// Header carrying a comma-separated list of capabilities, see [Capability].
+pub const CAPABILITIES_HEADER: &str = "x-dimppl-capabilities";
+
+/// Version spoken by this build. Bump it whenever a sync or websocket payload changes in a way
+/// older peers can't read.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Oldest version the server still serves. Clients from before versioning don't send the header
+/// and are treated as version 1.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Optional features of the protocol. Data for a capability the peer didn't announce is left out.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Capability {
+    PodcastSettings,
+    Bookmarks,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 2] = [Capability::PodcastSettings, Capability::Bookmarks];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Capability::PodcastSettings => "podcast_settings",
+            Capability::Bookmarks => "bookmarks",
+        }
+    }
+
+    pub fn from_token(value: &str) -> Option<Self> {
+        Capability::ALL
+            .into_iter()
+            .find(|capability| capability.as_str() == value.trim())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolInfo {
+    pub version: u32,
+    pub capabilities: Vec<Capability>,
+}
+
+impl ProtocolInfo {
+    /// What this build speaks.
+    pub fn current() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            capabilities: Capability::ALL.to_vec(),
+        }
+    }
+
+    /// What a peer that doesn't send the headers speaks.
+    pub fn legacy() -> Self {
+        Self {
+            version: 1,
+            capabilities: Vec::new(),
+        }
+    }
+
+    /// Reads the header values. Unknown capabilities are ignored, an unparseable version gives
+    /// `None` and missing headers mean [ProtocolInfo::legacy].
+    pub fn from_headers(version: Option<&str>, capabilities: Option<&str>) -> Option<Self> {
+        let Some(version) = version else {
+            return Some(Self::legacy());
+        };
+        Some(Self {
+            version: version.trim().parse().ok()?,
+            capabilities: capabilities
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(Capability::from_token)
+                .collect(),
+        })
+    }
+
+    pub fn version_header(&self) -> String {
+        self.version.to_string()
+    }
+
+    pub fn capabilities_header(&self) -> String {
+        self.capabilities
+            .iter()
+            .map(|capability| capability.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn has(&self, capability: Capability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+
+    pub fn is_supported(&self) -> bool {
+        self.version >= MIN_PROTOCOL_VERSION
+    }
+
+    /// The lower of both versions with the capabilities both sides have.
+    pub fn negotiate(&self, peer: &ProtocolInfo) -> ProtocolInfo {
+        Self {
+            version: self.version.min(peer.version),
+            capabilities: self
+                .capabilities
+                .iter()
+                .copied()
+                .filter(|capability| peer.has(*capability))
+                .collect(),
+        }
+    }
+}
@@ -33,14 +33,167 @@ pub struct SyncPodcastEpisode {
     pub updated_at: NaiveDateTime,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Deserialized through `from_str_or_default`, so a value this version doesn't know doesn't fail
+/// the whole payload.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case", from = "String")]
+pub enum AutoDownloadPolicy {
+    #[default]
+    Off,
+    Latest,
+    All,
+}
+
+/// Deserialized like `AutoDownloadPolicy`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case", from = "String")]
+pub enum EpisodeSortOrder {
+    #[default]
+    NewestFirst,
+    OldestFirst,
+}
+
+impl AutoDownloadPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AutoDownloadPolicy::Off => "off",
+            AutoDownloadPolicy::Latest => "latest",
+            AutoDownloadPolicy::All => "all",
+        }
+    }
+
+    /// Unknown values (e.g. written by a newer client) fall back to the default.
+    pub fn from_str_or_default(value: &str) -> Self {
+        match value {
+            "latest" => AutoDownloadPolicy::Latest,
+            "all" => AutoDownloadPolicy::All,
+            _ => AutoDownloadPolicy::Off,
+        }
+    }
+}
+
+impl From<String> for AutoDownloadPolicy {
+    fn from(value: String) -> Self {
+        Self::from_str_or_default(&value)
+    }
+}
+
+impl EpisodeSortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EpisodeSortOrder::NewestFirst => "newest_first",
+            EpisodeSortOrder::OldestFirst => "oldest_first",
+        }
+    }
+
+    /// Unknown values (e.g. written by a newer client) fall back to the default.
+    pub fn from_str_or_default(value: &str) -> Self {
+        match value {
+            "oldest_first" => EpisodeSortOrder::OldestFirst,
+            _ => EpisodeSortOrder::NewestFirst,
+        }
+    }
+}
+
+impl From<String> for EpisodeSortOrder {
+    fn from(value: String) -> Self {
+        Self::from_str_or_default(&value)
+    }
+}
+
+/// Per-podcast preferences, merged last-writer-wins on `updated_at` like everything else.
+/// `playback_speed` is `None` when the podcast follows the global setting.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct SyncPodcastSettings {
+    pub playback_speed: Option<f32>,
+    pub skip_intro_seconds: i32,
+    pub auto_download: AutoDownloadPolicy,
+    pub sort_order: EpisodeSortOrder,
+    pub notifications: bool,
+    pub updated_at: NaiveDateTime,
+}
+
+/// A moment in an episode, or a clip when `end_seconds` is set. `guid` is generated by the device
+/// that created it, deletions are kept as `deleted_at` so they reach the other devices.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct SyncBookmark {
+    pub guid: String,
+    pub episode_guid: String,
+    pub start_seconds: i32,
+    pub end_seconds: Option<i32>,
+    pub note: String,
+    pub created_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Default)]
 pub struct SyncStateRequest {
     pub podcasts: Vec<SyncPodcast>,
     pub episodes: HashMap<String, Vec<SyncPodcastEpisode>>,
+    /// Keyed by podcast guid. Older clients don't send this.
+    #[serde(default)]
+    pub settings: HashMap<String, SyncPodcastSettings>,
+    /// Keyed by podcast guid. Older clients don't send this.
+    #[serde(default)]
+    pub bookmarks: HashMap<String, Vec<SyncBookmark>>,
+    /// Old podcast guid to the guid it was merged into, for podcasts whose identity changed on
+    /// the client. Older clients don't send this.
+    #[serde(default)]
+    pub merged_podcasts: HashMap<String, String>,
 }
 
 #[derive(Serialize, Deserialize, Default)]
 pub struct SyncStateResponse {
     pub podcasts: Vec<SyncPodcast>,
     pub episodes: HashMap<String, Vec<SyncPodcastEpisode>>,
+    #[serde(default)]
+    pub settings: HashMap<String, SyncPodcastSettings>,
+    #[serde(default)]
+    pub bookmarks: HashMap<String, Vec<SyncBookmark>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_settings_values_fall_back_to_default() {
+        let json = r#"{
+            "podcasts": [],
+            "episodes": {},
+            "settings": {
+                "guid": {
+                    "playback_speed": 1.5,
+                    "skip_intro_seconds": 30,
+                    "auto_download": "latest_two",
+                    "sort_order": "shuffled",
+                    "notifications": true,
+                    "updated_at": "2024-01-01T10:00:00"
+                }
+            }
+        }"#;
+        let request: SyncStateRequest = serde_json::from_str(json).unwrap();
+        let settings = &request.settings["guid"];
+        assert_eq!(AutoDownloadPolicy::Off, settings.auto_download);
+        assert_eq!(EpisodeSortOrder::NewestFirst, settings.sort_order);
+        assert_eq!(Some(1.5), settings.playback_speed);
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let settings = SyncPodcastSettings {
+            playback_speed: None,
+            skip_intro_seconds: 0,
+            auto_download: AutoDownloadPolicy::All,
+            sort_order: EpisodeSortOrder::OldestFirst,
+            notifications: false,
+            updated_at: NaiveDateTime::default(),
+        };
+        let json = serde_json::to_string(&settings).unwrap();
+        assert!(json.contains(r#""auto_download":"all""#));
+        assert_eq!(settings, serde_json::from_str(&json).unwrap());
+        let msgpack = rmp_serde::to_vec_named(&settings).unwrap();
+        assert_eq!(settings, rmp_serde::from_slice(&msgpack).unwrap());
+    }
 }